@@ -0,0 +1,313 @@
+use axum::{Json, extract::State, http::StatusCode};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+use super::auth::AdminUser;
+use super::extract::{JsonBody, Path, Query};
+use super::handlers::{AppState, parse_date};
+use super::rate_limit::flush_usage;
+use crate::error::{AppError, Problem, Result};
+use crate::models::{ApiUsage, ManualRate, ManualRateAudit, QuarantinedRate};
+
+/// Date range query for admin listings
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DateRangeQuery {
+    /// First date (default: 30 days before `end`)
+    pub start: Option<String>,
+    /// Last date (default: today)
+    pub end: Option<String>,
+    /// Maximum number of entries (audit trail only, default: 100)
+    pub limit: Option<i64>,
+    /// API key name (usage only)
+    pub key: Option<String>,
+}
+
+impl DateRangeQuery {
+    /// Defaults to the last 30 days
+    fn range(&self) -> Result<(NaiveDate, NaiveDate)> {
+        let today = chrono::Utc::now().date_naive();
+        let end = self
+            .end
+            .as_deref()
+            .map(parse_date)
+            .transpose()?
+            .unwrap_or(today);
+        let start = self
+            .start
+            .as_deref()
+            .map(parse_date)
+            .transpose()?
+            .unwrap_or(end - chrono::Duration::days(30));
+
+        if start > end {
+            return Err(AppError::InvalidDate(
+                "Start date must be before or equal to end date".to_string(),
+            ));
+        }
+
+        Ok((start, end))
+    }
+}
+
+/// Body for creating a manual rate
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateManualRate {
+    #[schema(example = "2025-03-03")]
+    pub date: String,
+    /// Base currency (1 unit of `from` = `rate` units of `to`)
+    pub from: String,
+    pub to: String,
+    pub rate: f64,
+    pub note: Option<String>,
+}
+
+/// Body for updating a manual rate
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateManualRate {
+    pub rate: f64,
+    pub note: Option<String>,
+}
+
+/// GET /admin/rates?start=YYYY-MM-DD&end=YYYY-MM-DD
+/// List manual override rates
+#[utoipa::path(
+    get,
+    path = "/admin/rates",
+    tag = "admin",
+    params(DateRangeQuery),
+    security(("bearer" = []), ("api_key" = [])),
+    responses((status = 200, description = "Manual rates in the range", body = Vec<ManualRate>))
+)]
+pub async fn list_manual_rates(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Json<Vec<ManualRate>>> {
+    let (start, end) = query.range()?;
+    Ok(Json(state.service.list_manual_rates(start, end).await?))
+}
+
+/// POST /admin/rates
+/// Create a manual override rate
+#[utoipa::path(
+    post,
+    path = "/admin/rates",
+    tag = "admin",
+    request_body = CreateManualRate,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 201, description = "Created", body = ManualRate),
+        (status = 400, description = "Invalid rate or date", body = Problem),
+        (status = 409, description = "A manual rate exists for the pair and date", body = Problem),
+    )
+)]
+pub async fn create_manual_rate(
+    AdminUser(actor): AdminUser,
+    State(state): State<Arc<AppState>>,
+    JsonBody(body): JsonBody<CreateManualRate>,
+) -> Result<(StatusCode, Json<ManualRate>)> {
+    let date = parse_date(&body.date)?;
+    let rate = state
+        .service
+        .create_manual_rate(
+            date,
+            &body.from,
+            &body.to,
+            body.rate,
+            body.note.as_deref(),
+            &actor,
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(rate)))
+}
+
+/// PUT /admin/rates/{date}/{from}/{to}
+/// Update a manual override rate
+#[utoipa::path(
+    put,
+    path = "/admin/rates/{date}/{from}/{to}",
+    tag = "admin",
+    params(("date" = String, Path), ("from" = String, Path), ("to" = String, Path)),
+    request_body = UpdateManualRate,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Updated", body = ManualRate),
+        (status = 404, description = "No manual rate for the pair and date", body = Problem),
+    )
+)]
+pub async fn update_manual_rate(
+    AdminUser(actor): AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path((date, from, to)): Path<(String, String, String)>,
+    JsonBody(body): JsonBody<UpdateManualRate>,
+) -> Result<Json<ManualRate>> {
+    let date = parse_date(&date)?;
+    let rate = state
+        .service
+        .update_manual_rate(date, &from, &to, body.rate, body.note.as_deref(), &actor)
+        .await?;
+
+    Ok(Json(rate))
+}
+
+/// DELETE /admin/rates/{date}/{from}/{to}
+/// Delete a manual override rate
+#[utoipa::path(
+    delete,
+    path = "/admin/rates/{date}/{from}/{to}",
+    tag = "admin",
+    params(("date" = String, Path), ("from" = String, Path), ("to" = String, Path)),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No manual rate for the pair and date", body = Problem),
+    )
+)]
+pub async fn delete_manual_rate(
+    AdminUser(actor): AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path((date, from, to)): Path<(String, String, String)>,
+) -> Result<StatusCode> {
+    let date = parse_date(&date)?;
+    state
+        .service
+        .delete_manual_rate(date, &from, &to, &actor)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /admin/rates/audit?start=YYYY-MM-DD&end=YYYY-MM-DD&limit=100
+/// Audit trail of manual rate changes
+#[utoipa::path(
+    get,
+    path = "/admin/rates/audit",
+    tag = "admin",
+    params(DateRangeQuery),
+    security(("bearer" = []), ("api_key" = [])),
+    responses((status = 200, description = "Changes, newest first", body = Vec<ManualRateAudit>))
+)]
+pub async fn manual_rates_audit(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Json<Vec<ManualRateAudit>>> {
+    let (start, end) = query.range()?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    Ok(Json(
+        state.service.get_manual_audit(start, end, limit).await?,
+    ))
+}
+
+/// Query parameters for the quarantine listing
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuarantineQuery {
+    pub provider: Option<String>,
+    /// Maximum number of entries (default: 100)
+    pub limit: Option<i64>,
+}
+
+/// GET /admin/quarantine?provider=nbu&limit=100
+/// Provider rates held back by the data quality checks
+#[utoipa::path(
+    get,
+    path = "/admin/quarantine",
+    tag = "admin",
+    params(QuarantineQuery),
+    security(("bearer" = []), ("api_key" = [])),
+    responses((status = 200, description = "Quarantined rates", body = Vec<QuarantinedRate>))
+)]
+pub async fn list_quarantined(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<QuarantineQuery>,
+) -> Result<Json<Vec<QuarantinedRate>>> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    Ok(Json(
+        state
+            .service
+            .list_quarantined(query.provider.as_deref(), limit)
+            .await?,
+    ))
+}
+
+/// POST /admin/quarantine/{id}/release
+/// Store a quarantined rate as published
+#[utoipa::path(
+    post,
+    path = "/admin/quarantine/{id}/release",
+    tag = "admin",
+    params(("id" = i64, Path)),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Released rate", body = QuarantinedRate),
+        (status = 404, description = "Unknown id", body = Problem),
+    )
+)]
+pub async fn release_quarantined(
+    AdminUser(actor): AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<QuarantinedRate>> {
+    let rate = state.service.release_quarantined(id).await?;
+    tracing::info!("Quarantined rate {} released by {}", id, actor);
+
+    Ok(Json(rate))
+}
+
+/// DELETE /admin/quarantine/{id}
+/// Discard a quarantined rate
+#[utoipa::path(
+    delete,
+    path = "/admin/quarantine/{id}",
+    tag = "admin",
+    params(("id" = i64, Path)),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "Discarded"),
+        (status = 404, description = "Unknown id", body = Problem),
+    )
+)]
+pub async fn discard_quarantined(
+    AdminUser(actor): AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    state.service.discard_quarantined(id).await?;
+    tracing::info!("Quarantined rate {} discarded by {}", id, actor);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /admin/usage?start=YYYY-MM-DD&end=YYYY-MM-DD&key=name
+/// Requests per API key and day, including rejected ones
+#[utoipa::path(
+    get,
+    path = "/admin/usage",
+    tag = "admin",
+    params(DateRangeQuery),
+    security(("bearer" = []), ("api_key" = [])),
+    responses((status = 200, description = "Usage per key and day", body = Vec<ApiUsage>))
+)]
+pub async fn list_usage(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Json<Vec<ApiUsage>>> {
+    let (start, end) = query.range()?;
+    flush_usage(&state).await?;
+
+    Ok(Json(
+        state
+            .service
+            .get_api_usage(start, end, query.key.as_deref())
+            .await?,
+    ))
+}
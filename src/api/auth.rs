@@ -0,0 +1,205 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{
+        HeaderMap, HeaderValue,
+        header::{AUTHORIZATION, RETRY_AFTER},
+        request::Parts,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::handlers::AppState;
+use super::rate_limit::{client_ip, seconds};
+use crate::error::{AppError, Result};
+use crate::models::Scope;
+use crate::usage::{Client, ClientLimits};
+
+/// Header accepted as an alternative to `Authorization: Bearer <key>`
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Caller authenticated by an API key or an `ADMIN_TOKENS` token
+#[derive(Debug, Clone)]
+pub struct Caller {
+    /// Name recorded in audit trails and usage counters
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// Per-key rate limit override
+    pub rate_limit: Option<f64>,
+    /// Per-key daily quota override
+    pub daily_quota: Option<u64>,
+}
+
+impl Caller {
+    /// The caller was granted `scope`, directly or through a broader scope
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.iter().any(|s| *s >= scope)
+    }
+}
+
+/// Who sent a request, added to request extensions by `identify`
+#[derive(Debug, Clone)]
+pub enum Identity {
+    /// No key was sent
+    Anonymous,
+    /// A key was sent that matches no active key
+    Invalid,
+    /// A key was sent from an address with too many failed lookups; it was
+    /// not checked and can be retried after the given time
+    Throttled(Duration),
+    Caller(Caller),
+}
+
+impl Identity {
+    pub fn caller(&self) -> Option<&Caller> {
+        match self {
+            Identity::Caller(caller) => Some(caller),
+            _ => None,
+        }
+    }
+
+    /// A key was sent but not accepted
+    fn rejected(&self) -> bool {
+        matches!(self, Identity::Invalid | Identity::Throttled(_))
+    }
+}
+
+/// Key sent with the request, if any
+fn request_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+}
+
+/// Identify the caller from the request headers. Failed key lookups are
+/// limited per IP like requests, so invalid keys cannot flood the database.
+async fn authenticate(state: &AppState, headers: &HeaderMap, ip: IpAddr) -> Result<Identity> {
+    let Some(key) = request_key(headers) else {
+        return Ok(Identity::Anonymous);
+    };
+
+    if let Some(name) = state.admin_tokens.get(key) {
+        return Ok(Identity::Caller(Caller {
+            name: name.clone(),
+            scopes: vec![Scope::Admin],
+            rate_limit: None,
+            daily_quota: None,
+        }));
+    }
+
+    let client = Client::Ip(ip);
+    if let Some(wait) = state.failed_keys.retry_after(&client) {
+        return Ok(Identity::Throttled(wait));
+    }
+
+    Ok(match state.service.authenticate_api_key(key).await? {
+        Some(api_key) => Identity::Caller(Caller {
+            name: api_key.name,
+            scopes: api_key.scopes,
+            rate_limit: api_key.rate_limit,
+            daily_quota: api_key.daily_quota,
+        }),
+        None => {
+            let limits = ClientLimits {
+                daily_quota: 0,
+                ..state.client_limits
+            };
+            state.failed_keys.check(&client, limits, 0, Utc::now());
+            Identity::Invalid
+        }
+    })
+}
+
+/// Fail unless the caller has `scope`
+fn check_scope(identity: &Identity, scope: Scope) -> Result<()> {
+    match identity {
+        Identity::Caller(caller) if caller.allows(scope) => Ok(()),
+        Identity::Caller(caller) => Err(AppError::Forbidden(format!(
+            "API key {} does not have the {} scope",
+            caller.name,
+            scope.as_str()
+        ))),
+        Identity::Invalid => Err(AppError::Unauthorized("Invalid API key".to_string())),
+        Identity::Throttled(_) => Err(AppError::TooManyRequests(
+            "Too many invalid API keys".to_string(),
+        )),
+        Identity::Anonymous => Err(AppError::Unauthorized("Missing API key".to_string())),
+    }
+}
+
+/// Layer identifying the caller of every request. Rejections happen in
+/// `require_scope`, after the request was counted by the rate limiter.
+pub async fn identify(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response> {
+    let ip = client_ip(&request);
+    let identity = authenticate(&state, request.headers(), ip).await?;
+    request.extensions_mut().insert(identity);
+
+    Ok(next.run(request).await)
+}
+
+/// Route layer requiring `scope`. The `read` scope is not required when the
+/// API is configured as public-read (`PUBLIC_READ`), but a key that is sent
+/// must still be valid.
+pub async fn require_scope(
+    State((state, scope)): State<(Arc<AppState>, Scope)>,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    let identity = request
+        .extensions()
+        .get::<Identity>()
+        .unwrap_or(&Identity::Anonymous);
+    let public = scope == Scope::Read && state.public_read;
+    if !public || identity.rejected() {
+        if let Err(e) = check_scope(identity, scope) {
+            let mut response = e.into_response();
+            if let Identity::Throttled(wait) = identity {
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(seconds(*wait)));
+            }
+            return Ok(response);
+        }
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Authenticated caller with the `admin` scope.
+/// The contained name is recorded in audit trails.
+pub struct AdminUser(pub String);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AdminUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &Arc<AppState>,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let identity = parts
+            .extensions
+            .get::<Identity>()
+            .unwrap_or(&Identity::Anonymous);
+        check_scope(identity, Scope::Admin)?;
+
+        Ok(AdminUser(
+            identity
+                .caller()
+                .map(|c| c.name.clone())
+                .unwrap_or_default(),
+        ))
+    }
+}
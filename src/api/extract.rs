@@ -0,0 +1,21 @@
+use axum::extract::{FromRequest, FromRequestParts};
+
+use crate::error::AppError;
+
+// axum's extractors answer malformed input with plain-text rejections; these
+// wrappers answer with problem responses like every other error
+
+/// `axum::extract::Query` rejecting with a problem response
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct Query<T>(pub T);
+
+/// `axum::extract::Path` rejecting with a problem response
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct Path<T>(pub T);
+
+/// `axum::Json` request body rejecting with a problem response
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct JsonBody<T>(pub T);
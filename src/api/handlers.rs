@@ -1,75 +1,208 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
 };
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::Instrument;
+use utoipa::IntoParams;
 
-use crate::error::{AppError, Result};
-use crate::models::{CurrenciesResponse, HealthResponse, RatesResponse};
-use crate::service::RatesService;
+use super::auth::AdminUser;
+use super::extract::{Path, Query};
+use crate::db::{RevisionFilter, SyncHistoryFilter};
+use crate::error::{AppError, ErrorCode, ParamError, Problem, Result};
+use crate::models::{
+    BackfillMode, BackfillRequest, CurrenciesResponse, CurrencyInfo, HealthResponse,
+    HistoricalResponse, ProviderDetails, ProviderStatus, RateRevision, RatesResponse,
+    ReadinessResponse, ReconciliationReport, SyncHistoryResponse, SyncJob, SyncTrigger,
+};
+use crate::providers::http::CircuitState;
+use crate::service::{RateQueryOptions, RatesService};
+use crate::usage::{ClientLimiter, ClientLimits};
 
 /// Shared application state
 pub struct AppState {
     pub service: RatesService,
     /// Default base currency for API responses when client doesn't specify one
     pub default_api_base: String,
+    /// Static admin tokens mapped to the caller name used in audit trails
+    pub admin_tokens: HashMap<String, String>,
+    /// Serve read endpoints without an API key
+    pub public_read: bool,
+    /// Default request limits per API key or IP
+    pub client_limits: ClientLimits,
+    /// Request counters per API key or IP
+    pub limiter: ClientLimiter,
+    /// Failed API key lookups per IP, limited like requests: an IP out of
+    /// budget is refused before its keys reach the database
+    pub failed_keys: ClientLimiter,
+    /// Startup work (seeding) has finished
+    pub startup_complete: AtomicBool,
 }
 
 /// Query parameters for rate endpoints
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct RatesQuery {
-    /// Amount to convert (default: 1)
-    pub amount: Option<f64>,
+    /// Amount to convert, a positive number (default: 1)
+    #[param(value_type = Option<f64>)]
+    pub amount: Option<String>,
     /// Base currency (default: configured base, e.g., USD)
     #[serde(rename = "from")]
     pub base: Option<String>,
     /// Target currencies, comma-separated
     #[serde(rename = "to")]
+    #[param(example = "EUR,GBP")]
     pub symbols: Option<String>,
+    /// Let manual override rates win over provider rates
+    pub overrides: Option<bool>,
+    /// Answer with data stored by this RFC 3339 timestamp
+    pub as_of: Option<String>,
+}
+
+/// Rate query parameters after validation
+struct RateParams {
+    base: String,
+    symbols: Option<Vec<String>>,
+    amount: f64,
+    options: RateQueryOptions,
 }
 
 impl RatesQuery {
-    fn parse_symbols(&self) -> Option<Vec<String>> {
-        self.symbols.as_ref().map(|s| {
-            s.split(',')
-                .map(|s| s.trim().to_uppercase())
-                .filter(|s| !s.is_empty())
-                .collect()
+    /// Check the parameters before anything is read from the database
+    fn validate(&self, default_base: &str) -> Result<RateParams> {
+        let base = match &self.base {
+            Some(base) => currency_code("from", base)?,
+            None => default_base.to_string(),
+        };
+
+        let symbols = match &self.symbols {
+            Some(symbols) => {
+                let codes = symbols
+                    .split(',')
+                    .filter(|s| !s.trim().is_empty())
+                    .map(|s| currency_code("to", s))
+                    .collect::<Result<Vec<_>>>()?;
+                (!codes.is_empty()).then_some(codes)
+            }
+            None => None,
+        };
+
+        let amount = match &self.amount {
+            Some(value) => match value.trim().parse::<f64>() {
+                Ok(amount) if amount.is_finite() && amount > 0.0 => amount,
+                _ => {
+                    return Err(ParamError::new(
+                        ErrorCode::InvalidParameter,
+                        format!("Amount must be a positive number, got {:?}", value),
+                    )
+                    .param("amount", value.as_str())
+                    .into());
+                }
+            },
+            None => 1.0,
+        };
+
+        let as_of = self
+            .as_of
+            .as_deref()
+            .map(|s| {
+                DateTime::parse_from_rfc3339(s)
+                    .map(|t| t.with_timezone(&Utc))
+                    .map_err(|_| -> AppError {
+                        ParamError::new(
+                            ErrorCode::InvalidDate,
+                            format!(
+                                "as_of {} is not an RFC 3339 timestamp, e.g. 2025-03-01T12:00:00Z",
+                                s
+                            ),
+                        )
+                        .param("as_of", s)
+                        .into()
+                    })
+            })
+            .transpose()?;
+
+        Ok(RateParams {
+            base,
+            symbols,
+            amount,
+            options: RateQueryOptions {
+                overrides: self.overrides.unwrap_or(false),
+                as_of,
+            },
         })
     }
 }
 
+/// Normalize a currency code parameter: three letters, uppercased
+fn currency_code(param: &str, value: &str) -> Result<String> {
+    let code = value.trim().to_uppercase();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(ParamError::new(
+            ErrorCode::InvalidParameter,
+            format!(
+                "{:?} is not a currency code, use three letters such as EUR",
+                value
+            ),
+        )
+        .param(param, value)
+        .into());
+    }
+    Ok(code)
+}
+
 /// GET /
-/// Returns basic API info
+/// Returns basic API info and the endpoints documented in `/openapi.json`
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "meta",
+    responses((status = 200, description = "API name and endpoint summaries", body = serde_json::Value))
+)]
 pub async fn root() -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "name": "USD Currency Rates API",
         "description": "Currency exchange rates API with multiple providers",
-        "endpoints": {
-            "/currencies": "List supported currencies",
-            "/latest": "Get latest rates",
-            "/{date}": "Get rates for a specific date (YYYY-MM-DD)",
-            "/{start_date}..{end_date}": "Get rates for a date range",
-            "/health": "Health check"
-        }
+        "docs": "/docs",
+        "openapi": "/openapi.json",
+        "endpoints": super::openapi::endpoint_summaries(),
     }))
 }
 
 /// GET /latest
 /// Get the latest exchange rates
+#[utoipa::path(
+    get,
+    path = "/latest",
+    tag = "rates",
+    params(RatesQuery),
+    security((), ("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Rates of the latest stored date", body = RatesResponse),
+        (status = 400, description = "Invalid parameter", body = Problem),
+        (status = 404, description = "Unknown currency or no data", body = Problem),
+    )
+)]
 pub async fn get_latest(
     State(state): State<Arc<AppState>>,
     Query(query): Query<RatesQuery>,
 ) -> Result<Json<RatesResponse>> {
-    let base = query.base.as_deref().unwrap_or(&state.default_api_base);
-    let symbols = query.parse_symbols();
-    let amount = query.amount.unwrap_or(1.0);
+    let params = query.validate(&state.default_api_base)?;
 
     let response = state
         .service
-        .get_latest(Some(base), symbols.as_deref(), Some(amount))
+        .get_latest(
+            Some(&params.base),
+            params.symbols.as_deref(),
+            Some(params.amount),
+            params.options,
+        )
         .await?;
 
     Ok(Json(response))
@@ -77,6 +210,15 @@ pub async fn get_latest(
 
 /// GET /currencies
 /// List all available currencies
+#[utoipa::path(
+    get,
+    path = "/currencies",
+    tag = "rates",
+    security((), ("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Currencies by code with their stored date range", body = HashMap<String, CurrencyInfo>),
+    )
+)]
 pub async fn get_currencies(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<CurrenciesResponse>> {
@@ -84,54 +226,184 @@ pub async fn get_currencies(
     Ok(Json(currencies))
 }
 
+/// GET /providers
+/// List registered providers with their capabilities
+#[utoipa::path(
+    get,
+    path = "/providers",
+    tag = "providers",
+    security((), ("bearer" = []), ("api_key" = [])),
+    responses((status = 200, description = "Providers in registration order", body = Vec<ProviderDetails>))
+)]
+pub async fn list_providers(State(state): State<Arc<AppState>>) -> Json<Vec<ProviderDetails>> {
+    Json(state.service.list_providers())
+}
+
+/// GET /providers/:name
+/// Capabilities, stored data and sync state of one provider
+#[utoipa::path(
+    get,
+    path = "/providers/{name}",
+    tag = "providers",
+    params(("name" = String, Path, description = "Provider name", example = "ecb")),
+    security((), ("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Provider status", body = ProviderStatus),
+        (status = 404, description = "Unknown provider", body = Problem),
+    )
+)]
+pub async fn get_provider(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<ProviderStatus>> {
+    Ok(Json(state.service.get_provider_status(&name).await?))
+}
+
+/// Whether the instance can serve requests: startup finished and database reachable
+async fn readiness(state: &AppState) -> ReadinessResponse {
+    let startup_complete = state.startup_complete.load(Ordering::Acquire);
+    let database = match state.service.check_database().await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::error!("Readiness check failed: {}", e);
+            false
+        }
+    };
+
+    let status = if !database {
+        "unavailable"
+    } else if !startup_complete {
+        "starting"
+    } else {
+        "ready"
+    };
+
+    ReadinessResponse {
+        status: status.to_string(),
+        database,
+        startup_complete,
+    }
+}
+
 /// GET /health
-/// Health check endpoint
-pub async fn health_check(State(state): State<Arc<AppState>>) -> Result<Json<HealthResponse>> {
+/// Health check: provider freshness and circuit breakers, 503 when not ready
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "`ok` or `degraded`", body = HealthResponse),
+        (status = 503, description = "`starting` or `unavailable`", body = HealthResponse),
+    )
+)]
+pub async fn health_check(State(state): State<Arc<AppState>>) -> Result<Response> {
+    let version = env!("CARGO_PKG_VERSION").to_string();
+    let ready = readiness(&state).await;
+    if ready.status != "ready" {
+        let response = HealthResponse {
+            status: ready.status,
+            version,
+            providers: Vec::new(),
+        };
+        return Ok((StatusCode::SERVICE_UNAVAILABLE, Json(response)).into_response());
+    }
+
     let providers = state.service.get_providers_info().await?;
 
+    // An enabled provider that is stale or failing fast degrades the service
+    let degraded = providers.iter().any(|p| {
+        p.stale
+            || p.enabled
+                && p.circuit
+                    .as_ref()
+                    .is_some_and(|c| c.state != CircuitState::Closed)
+    });
+
     Ok(Json(HealthResponse {
-        status: "ok".to_string(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
+        status: if degraded { "degraded" } else { "ok" }.to_string(),
+        version,
         providers,
-    }))
+    })
+    .into_response())
+}
+
+/// GET /health/live
+/// Liveness probe: the process is serving requests
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses((status = 200, description = "`{\"status\": \"ok\"}`", body = serde_json::Value))
+)]
+pub async fn health_live() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+/// GET /health/ready
+/// Readiness probe: 503 while seeding or when the database is unreachable
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready to serve requests", body = ReadinessResponse),
+        (status = 503, description = "`starting` or `unavailable`", body = ReadinessResponse),
+    )
+)]
+pub async fn health_ready(State(state): State<Arc<AppState>>) -> Response {
+    let ready = readiness(&state).await;
+    let status = if ready.status == "ready" {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(ready)).into_response()
 }
 
 /// GET /{date}
 /// Get rates for a specific date or date range
 /// Supports: YYYY-MM-DD or YYYY-MM-DD..YYYY-MM-DD
+#[utoipa::path(
+    get,
+    path = "/{date}",
+    tag = "rates",
+    params(
+        ("date" = String, Path, description = "`YYYY-MM-DD`, or `YYYY-MM-DD..YYYY-MM-DD` for a time series", example = "2025-11-01..2025-11-27"),
+        RatesQuery,
+    ),
+    security((), ("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Rates of the day, or a time series for a range", body = HistoricalResponse),
+        (status = 400, description = "Invalid date or parameter", body = Problem),
+        (status = 404, description = "Unknown currency or no data", body = Problem),
+    )
+)]
 pub async fn get_historical(
     State(state): State<Arc<AppState>>,
     Path(date_path): Path<String>,
     Query(query): Query<RatesQuery>,
-) -> Result<Json<serde_json::Value>> {
-    let base = query.base.as_deref().unwrap_or(&state.default_api_base);
-    let symbols = query.parse_symbols();
-    let amount = query.amount.unwrap_or(1.0);
+) -> Result<Json<HistoricalResponse>> {
+    let params = query.validate(&state.default_api_base)?;
+    let base = params.base.as_str();
 
     // Check if it's a date range (YYYY-MM-DD..YYYY-MM-DD)
     if date_path.contains("..") {
-        let parts: Vec<&str> = date_path.split("..").collect();
-        if parts.len() != 2 {
-            return Err(AppError::InvalidDate(
-                "Invalid date range format. Use YYYY-MM-DD..YYYY-MM-DD".to_string(),
-            ));
-        }
-
-        let start = parse_date(parts[0])?;
-        let end = parse_date(parts[1])?;
-
-        if start > end {
-            return Err(AppError::InvalidDate(
-                "Start date must be before or equal to end date".to_string(),
-            ));
-        }
+        let (start, end) = parse_date_range(&date_path)?;
 
         let response = state
             .service
-            .get_time_series(start, end, base, symbols.as_deref(), amount)
+            .get_time_series(
+                start,
+                end,
+                base,
+                params.symbols.as_deref(),
+                params.amount,
+                params.options,
+            )
             .await?;
 
-        return Ok(Json(serde_json::to_value(response)?));
+        return Ok(Json(HistoricalResponse::Range(response)));
     }
 
     // Single date
@@ -140,41 +412,408 @@ pub async fn get_historical(
 
     let response = state
         .service
-        .get_rates_for_date(date, base, symbols.as_deref(), amount)
+        .get_rates_for_date(
+            date,
+            base,
+            params.symbols.as_deref(),
+            params.amount,
+            params.options,
+        )
         .await?;
 
     tracing::debug!("Got {} rates", response.rates.len());
-    Ok(Json(serde_json::to_value(response)?))
+    Ok(Json(HistoricalResponse::Day(response)))
+}
+
+/// Query parameters for sync endpoints
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SyncQuery {
+    /// Wait for the job to finish instead of returning 202 right away
+    #[serde(default)]
+    pub wait: bool,
+    /// Backfill this range instead of syncing new data (single provider only)
+    pub start: Option<String>,
+    /// End of the backfill range (default: today)
+    pub end: Option<String>,
+    /// Backfill mode: fill-missing (default) or overwrite
+    #[param(value_type = Option<BackfillMode>)]
+    pub mode: Option<String>,
 }
 
-/// POST /sync
-/// Trigger a manual sync (admin endpoint)
-pub async fn trigger_sync(State(state): State<Arc<AppState>>) -> Result<Json<serde_json::Value>> {
-    state.service.sync_all_providers().await?;
+impl SyncQuery {
+    /// Backfill requested by `start`/`end`/`mode`, if any
+    fn backfill(&self) -> Result<Option<BackfillRequest>> {
+        if self.start.is_none() && self.end.is_none() && self.mode.is_none() {
+            return Ok(None);
+        }
+
+        let start = self
+            .start
+            .as_deref()
+            .map(parse_date)
+            .transpose()?
+            .ok_or_else(|| AppError::Validation("Backfill requires a start date".to_string()))?;
+        let end = self
+            .end
+            .as_deref()
+            .map(parse_date)
+            .transpose()?
+            .unwrap_or_else(|| chrono::Utc::now().date_naive());
+        let mode = self
+            .mode
+            .as_deref()
+            .map(str::parse::<BackfillMode>)
+            .transpose()
+            .map_err(AppError::Validation)?
+            .unwrap_or_default();
 
-    Ok(Json(serde_json::json!({
-        "status": "ok",
-        "message": "Sync completed"
-    })))
+        Ok(Some(BackfillRequest { start, end, mode }))
+    }
 }
 
-/// POST /sync/{provider}
-/// Trigger sync for a specific provider
+/// Run a queued job in the background (202 + Location), or wait for it when
+/// `wait` is set. The job runs in its own task either way, so a client that
+/// disconnects while waiting doesn't cancel it.
+async fn start_sync_job(state: Arc<AppState>, job: SyncJob, wait: bool) -> Result<Response> {
+    let location = format!("{}/sync/jobs/{}", super::v1::PREFIX, job.id);
+    let id = job.id.clone();
+    // The job stays in the request's span, so its logs carry the request id
+    let handle = tokio::spawn(
+        async move {
+            let result = state.service.run_sync_job(&id).await;
+            if let Err(e) = &result {
+                tracing::error!("Sync job {} failed: {}", id, e);
+            }
+            result
+        }
+        .in_current_span(),
+    );
+
+    if wait {
+        let job = handle
+            .await
+            .map_err(|e| AppError::Internal(format!("Sync job {} panicked: {}", job.id, e)))??;
+        return Ok(Json(job).into_response());
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(header::LOCATION, location)],
+        Json(job),
+    )
+        .into_response())
+}
+
+/// POST /sync?wait=false
+/// Start a sync of all enabled providers (admin endpoint)
+#[utoipa::path(
+    post,
+    path = "/sync",
+    tag = "sync",
+    params(("wait" = Option<bool>, Query, description = "Wait for the job to finish instead of returning 202 right away")),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 202, description = "Job queued, see the `Location` header", body = SyncJob),
+        (status = 200, description = "Finished job (`wait=true`)", body = SyncJob),
+        (status = 409, description = "A provider is being synced by another job", body = Problem),
+    )
+)]
+pub async fn trigger_sync(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SyncQuery>,
+) -> Result<Response> {
+    let names = state.service.enabled_provider_names();
+    let job = state.service.create_sync_job(&names, SyncTrigger::Api)?;
+
+    start_sync_job(state, job, query.wait).await
+}
+
+/// POST /sync/{provider}?wait=false
+/// Start a sync of a specific provider.
+/// With `start`, `end` and `mode` (admin only): refetch that range instead,
+/// either only the missing (date, currency) cells or overwriting everything.
+#[utoipa::path(
+    post,
+    path = "/sync/{provider}",
+    tag = "sync",
+    params(("provider" = String, Path, description = "Provider name", example = "nbu"), SyncQuery),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 202, description = "Job queued, see the `Location` header", body = SyncJob),
+        (status = 200, description = "Finished job (`wait=true`)", body = SyncJob),
+        (status = 400, description = "Disabled provider or invalid backfill range", body = Problem),
+        (status = 404, description = "Unknown provider", body = Problem),
+        (status = 409, description = "The provider is being synced by another job", body = Problem),
+    )
+)]
 pub async fn trigger_provider_sync(
+    admin: std::result::Result<AdminUser, AppError>,
     State(state): State<Arc<AppState>>,
     Path(provider): Path<String>,
-) -> Result<Json<serde_json::Value>> {
-    let count = state.service.sync_provider(&provider).await?;
+    Query(query): Query<SyncQuery>,
+) -> Result<Response> {
+    let job = match query.backfill()? {
+        Some(request) => {
+            let AdminUser(actor) = admin?;
+            tracing::info!(
+                "{} requested a {:?} backfill of {} from {} to {}",
+                actor,
+                request.mode,
+                provider,
+                request.start,
+                request.end
+            );
+            state
+                .service
+                .create_backfill_job(&provider, request, SyncTrigger::Api)?
+        }
+        None => state
+            .service
+            .create_sync_job(&[provider], SyncTrigger::Api)?,
+    };
+
+    start_sync_job(state, job, query.wait).await
+}
+
+/// GET /sync/jobs/{id}
+/// Progress of a sync job
+#[utoipa::path(
+    get,
+    path = "/sync/jobs/{id}",
+    tag = "sync",
+    params(("id" = String, Path, description = "Job id")),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Job progress", body = SyncJob),
+        (status = 404, description = "Unknown job", body = Problem),
+    )
+)]
+pub async fn get_sync_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<SyncJob>> {
+    state
+        .service
+        .get_sync_job(&id)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Sync job {}", id)))
+}
+
+/// Query parameters for the sync history
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SyncHistoryQuery {
+    pub provider: Option<String>,
+    /// success, partial, retry_later, error, skipped or seeded
+    pub status: Option<String>,
+    pub job_id: Option<String>,
+    /// Page size (default: 50, at most 500)
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// GET /sync/history?provider=nbu&status=error&limit=50&offset=0
+/// Past provider syncs, newest first
+#[utoipa::path(
+    get,
+    path = "/sync/history",
+    tag = "sync",
+    params(SyncHistoryQuery),
+    security(("bearer" = []), ("api_key" = [])),
+    responses((status = 200, description = "A page of the sync log", body = SyncHistoryResponse))
+)]
+pub async fn sync_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SyncHistoryQuery>,
+) -> Result<Json<SyncHistoryResponse>> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let offset = query.offset.unwrap_or(0).max(0);
+    let filter = SyncHistoryFilter {
+        provider: query.provider,
+        status: query.status,
+        job_id: query.job_id,
+    };
+
+    Ok(Json(
+        state
+            .service
+            .get_sync_history(&filter, limit, offset)
+            .await?,
+    ))
+}
+
+/// Query parameters for rate revisions
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RevisionsQuery {
+    /// Single date, shorthand for `start` = `end`
+    pub date: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// Currency pair; revisions of either USD-based leg are listed
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub provider: Option<String>,
+    /// Also list weekend/holiday estimates replaced by the published rate
+    pub include_estimates: Option<bool>,
+    /// Maximum number of entries (default: 100)
+    pub limit: Option<i64>,
+}
+
+/// GET /revisions?date=2025-03-03&from=EUR&to=UAH
+/// Stored rates that a provider later published with a different value
+#[utoipa::path(
+    get,
+    path = "/revisions",
+    tag = "rates",
+    params(RevisionsQuery),
+    security((), ("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Revisions, newest first", body = Vec<RateRevision>),
+        (status = 400, description = "Invalid date", body = Problem),
+    )
+)]
+pub async fn list_revisions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RevisionsQuery>,
+) -> Result<Json<Vec<RateRevision>>> {
+    let parse = |s: &Option<String>| s.as_deref().map(parse_date).transpose();
+    let (start, end) = match query.date {
+        Some(_) => (parse(&query.date)?, parse(&query.date)?),
+        None => (parse(&query.start)?, parse(&query.end)?),
+    };
+    let currencies = [&query.from, &query.to]
+        .into_iter()
+        .flatten()
+        .map(|c| c.trim().to_uppercase())
+        .collect();
+    let filter = RevisionFilter {
+        start,
+        end,
+        currencies,
+        provider: query.provider,
+        include_estimates: query.include_estimates.unwrap_or(false),
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    Ok(Json(state.service.get_revisions(filter, limit).await?))
+}
+
+/// Query parameters for the reconciliation report
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReconciliationQuery {
+    /// Relative difference reported as a divergence (default: `RECONCILIATION_TOLERANCE`)
+    pub tolerance: Option<f64>,
+    /// Only this currency
+    pub currency: Option<String>,
+}
+
+/// GET /reconciliation/{start}..{end}?tolerance=0.005&currency=EUR
+/// Pairs and days on which providers publishing the same rate diverge
+#[utoipa::path(
+    get,
+    path = "/reconciliation/{range}",
+    tag = "reconciliation",
+    params(
+        ("range" = String, Path, description = "`YYYY-MM-DD..YYYY-MM-DD`", example = "2025-03-01..2025-03-31"),
+        ReconciliationQuery,
+    ),
+    security((), ("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Divergent pairs, largest first", body = ReconciliationReport),
+        (status = 400, description = "Invalid range", body = Problem),
+    )
+)]
+pub async fn get_reconciliation(
+    State(state): State<Arc<AppState>>,
+    Path(range): Path<String>,
+    Query(query): Query<ReconciliationQuery>,
+) -> Result<Json<ReconciliationReport>> {
+    let (start, end) = parse_date_range(&range).map_err(|e| e.with_param("range"))?;
+    let currency = query.currency.map(|c| c.trim().to_uppercase());
+
+    Ok(Json(
+        state
+            .service
+            .get_reconciliation(start, end, query.tolerance, currency.as_deref())
+            .await?,
+    ))
+}
+
+/// POST /reconciliation/{start}..{end}
+/// Compare the providers again over a range (admin only), then report
+#[utoipa::path(
+    post,
+    path = "/reconciliation/{range}",
+    tag = "reconciliation",
+    params(
+        ("range" = String, Path, description = "`YYYY-MM-DD..YYYY-MM-DD`", example = "2025-03-01..2025-03-31"),
+        ReconciliationQuery,
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Divergent pairs, largest first", body = ReconciliationReport),
+        (status = 400, description = "Invalid range", body = Problem),
+        (status = 403, description = "The key lacks the admin scope", body = Problem),
+    )
+)]
+pub async fn run_reconciliation(
+    AdminUser(actor): AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(range): Path<String>,
+    Query(query): Query<ReconciliationQuery>,
+) -> Result<Json<ReconciliationReport>> {
+    let (start, end) = parse_date_range(&range).map_err(|e| e.with_param("range"))?;
+    tracing::info!(
+        "Reconciliation of {} to {} started by {}",
+        start,
+        end,
+        actor
+    );
+    state.service.reconcile(start, end).await?;
+
+    let currency = query.currency.map(|c| c.trim().to_uppercase());
+    Ok(Json(
+        state
+            .service
+            .get_reconciliation(start, end, query.tolerance, currency.as_deref())
+            .await?,
+    ))
+}
+
+/// Parse a `YYYY-MM-DD..YYYY-MM-DD` range
+pub(super) fn parse_date_range(s: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let invalid = |detail: &str| -> AppError {
+        ParamError::new(ErrorCode::InvalidDate, detail)
+            .param("date", s)
+            .into()
+    };
+
+    let Some((start, end)) = s.split_once("..") else {
+        return Err(invalid(
+            "Invalid date range format. Use YYYY-MM-DD..YYYY-MM-DD",
+        ));
+    };
+    if end.contains("..") {
+        return Err(invalid(
+            "Invalid date range format. Use YYYY-MM-DD..YYYY-MM-DD",
+        ));
+    }
+
+    let start = parse_date(start)?;
+    let end = parse_date(end)?;
+
+    if start > end {
+        return Err(invalid("Start date must be before or equal to end date"));
+    }
 
-    Ok(Json(serde_json::json!({
-        "status": "ok",
-        "provider": provider,
-        "records_synced": count
-    })))
+    Ok((start, end))
 }
 
 /// Parse date from string, supporting multiple formats
-fn parse_date(s: &str) -> Result<NaiveDate> {
+pub(super) fn parse_date(s: &str) -> Result<NaiveDate> {
     // Try ISO format first (YYYY-MM-DD)
     if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
         return Ok(date);
@@ -185,8 +824,10 @@ fn parse_date(s: &str) -> Result<NaiveDate> {
         return Ok(date);
     }
 
-    Err(AppError::InvalidDate(format!(
-        "Invalid date format: {}. Use YYYY-MM-DD",
-        s
-    )))
+    Err(ParamError::new(
+        ErrorCode::InvalidDate,
+        format!("Invalid date format: {}. Use YYYY-MM-DD", s),
+    )
+    .param("date", s)
+    .into())
 }
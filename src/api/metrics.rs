@@ -0,0 +1,51 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::handlers::AppState;
+use crate::error::Result;
+use crate::metrics;
+
+/// Layer counting requests and their latency per route and status
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+    metrics::observe_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+
+    response
+}
+
+/// GET /metrics
+/// Metrics in the Prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "meta",
+    responses((status = 200, description = "Prometheus exposition format", content_type = "text/plain", body = String))
+)]
+pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> Result<Response> {
+    state.service.refresh_metrics().await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, metrics::CONTENT_TYPE)],
+        metrics::render(),
+    )
+        .into_response())
+}
@@ -1,5 +1,14 @@
+mod admin;
+mod auth;
+mod extract;
 mod handlers;
+mod metrics;
+mod openapi;
+mod rate_limit;
 mod routes;
+mod v1;
 
 pub use handlers::AppState;
+pub use openapi::ApiDoc;
+pub use rate_limit::flush_usage;
 pub use routes::create_router;
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::{OpenApi as OpenApiDoc, RefOr, path::Operation};
+use utoipa::{Modify, OpenApi};
+
+use super::{handlers, metrics, v1};
+use crate::error::Problem;
+
+/// OpenAPI document of the API, served at `/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Currency Rates API",
+        description = "Currency exchange rates API with multiple providers"
+    ),
+    paths(
+        handlers::root,
+        handlers::health_check,
+        handlers::health_live,
+        handlers::health_ready,
+        metrics::metrics_handler,
+    ),
+    nest((path = v1::PREFIX, api = v1::V1Api)),
+    components(schemas(Problem)),
+    tags(
+        (name = "rates", description = "Exchange rates"),
+        (name = "providers", description = "Data providers"),
+        (name = "reconciliation", description = "Cross-provider comparison"),
+        (name = "sync", description = "Fetching from providers (`sync` scope)"),
+        (name = "admin", description = "Manual rates, quarantine and usage (`admin` scope)"),
+        (name = "health", description = "Health and readiness probes"),
+        (name = "meta", description = "API info and metrics"),
+    ),
+    modifiers(&ApiKeyAuth, &RouteLineDocs, &ProblemResponses)
+)]
+pub struct ApiDoc;
+
+/// The document, built once
+pub(crate) static OPENAPI: LazyLock<OpenApiDoc> = LazyLock::new(ApiDoc::openapi);
+
+/// API keys are sent as `Authorization: Bearer <key>` or `X-API-Key: <key>`
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
+/// Handler docs start with the route (`/// GET /latest`), which the
+/// document already has: drop it from the summary, and move lines after
+/// the first one into the description
+struct RouteLineDocs;
+
+impl Modify for RouteLineDocs {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        for_each_operation(openapi, split_summary);
+    }
+}
+
+/// Error responses are `application/problem+json`
+struct ProblemResponses;
+
+impl Modify for ProblemResponses {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        for_each_operation(openapi, |operation| {
+            for (status, response) in operation.responses.responses.iter_mut() {
+                let RefOr::T(response) = response else {
+                    continue;
+                };
+                if !status.starts_with(['4', '5']) {
+                    continue;
+                }
+                if let Some(content) = response.content.shift_remove("application/json") {
+                    response
+                        .content
+                        .insert("application/problem+json".to_string(), content);
+                }
+            }
+        });
+    }
+}
+
+fn for_each_operation(openapi: &mut OpenApiDoc, mut f: impl FnMut(&mut Operation)) {
+    for item in openapi.paths.paths.values_mut() {
+        let operations = [
+            &mut item.get,
+            &mut item.put,
+            &mut item.post,
+            &mut item.delete,
+        ];
+        for operation in operations.into_iter().flatten() {
+            f(operation);
+        }
+    }
+}
+
+fn split_summary(operation: &mut Operation) {
+    let Some(summary) = operation.summary.take() else {
+        return;
+    };
+    let mut lines = summary
+        .lines()
+        .map(str::trim)
+        .filter(|line| !is_route_line(line));
+    operation.summary = lines.next().map(str::to_string);
+
+    let rest: Vec<&str> = lines.collect();
+    if !rest.is_empty() {
+        let mut description = rest.join("\n");
+        if let Some(existing) = operation.description.take() {
+            description = format!("{}\n\n{}", description, existing);
+        }
+        operation.description = Some(description);
+    }
+}
+
+fn is_route_line(line: &str) -> bool {
+    line.split_once(' ').is_some_and(|(method, path)| {
+        ["GET", "POST", "PUT", "DELETE"].contains(&method) && path.starts_with('/')
+    })
+}
+
+/// `"GET /latest" -> summary` for every documented endpoint
+pub(crate) fn endpoint_summaries() -> BTreeMap<String, String> {
+    let mut endpoints = BTreeMap::new();
+    for (path, item) in &OPENAPI.paths.paths {
+        let operations = [
+            ("GET", &item.get),
+            ("PUT", &item.put),
+            ("POST", &item.post),
+            ("DELETE", &item.delete),
+        ];
+        for (method, operation) in operations {
+            if let Some(operation) = operation {
+                let summary = operation.summary.clone().unwrap_or_default();
+                endpoints.insert(format!("{} {}", method, path), summary);
+            }
+        }
+    }
+    endpoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_document() {
+        let doc = serde_json::to_value(&*OPENAPI).unwrap();
+
+        let latest = &doc["paths"]["/v1/latest"]["get"];
+        assert_eq!(latest["summary"], "Get the latest exchange rates");
+        let params: Vec<&str> = latest["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(params, ["amount", "from", "to", "overrides", "as_of"]);
+
+        let provider_sync = &doc["paths"]["/v1/sync/{provider}"]["post"];
+        assert_eq!(
+            provider_sync["summary"],
+            "Start a sync of a specific provider."
+        );
+        assert!(
+            provider_sync["description"]
+                .as_str()
+                .unwrap()
+                .starts_with("With `start`, `end` and `mode`")
+        );
+
+        for schema in [
+            "RatesResponse",
+            "TimeSeriesResponse",
+            "CurrencyInfo",
+            "HistoricalResponse",
+            "Problem",
+            "ProviderStatus",
+        ] {
+            assert!(
+                doc["components"]["schemas"][schema].is_object(),
+                "missing schema {}",
+                schema
+            );
+        }
+        assert!(doc["components"]["securitySchemes"]["api_key"].is_object());
+        assert!(
+            latest["responses"]["400"]["content"]["application/problem+json"]["schema"].is_object()
+        );
+
+        let endpoints = endpoint_summaries();
+        assert_eq!(
+            endpoints["GET /v1/{date}"],
+            "Get rates for a specific date or date range"
+        );
+        assert!(endpoints.contains_key("POST /v1/admin/quarantine/{id}/release"));
+    }
+}
@@ -0,0 +1,115 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::auth::Identity;
+use super::handlers::AppState;
+use crate::error::{AppError, Result};
+use crate::usage::{Client, ClientLimits, Verdict, Window};
+
+/// Address of the client, unspecified when the server runs without connect info
+pub(super) fn client_ip(request: &Request) -> IpAddr {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+}
+
+/// Seconds, rounded up so clients never retry too early
+pub(super) fn seconds(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+fn set_window(headers: &mut HeaderMap, prefix: &str, window: &Window) {
+    for (name, value) in [
+        ("Limit", window.limit),
+        ("Remaining", window.remaining),
+        ("Reset", seconds(window.reset)),
+    ] {
+        let name = HeaderName::try_from(format!("{}{}", prefix, name));
+        if let Ok(name) = name {
+            headers.insert(name, HeaderValue::from(value));
+        }
+    }
+}
+
+/// `X-RateLimit-*` for the burst window, `X-RateLimit-Quota-*` for the daily quota
+fn set_headers(headers: &mut HeaderMap, verdict: &Verdict) {
+    if let Some(rate) = &verdict.rate {
+        set_window(headers, "X-RateLimit-", rate);
+    }
+    if let Some(quota) = &verdict.quota {
+        set_window(headers, "X-RateLimit-Quota-", quota);
+    }
+    if let Some(retry_after) = verdict.retry_after {
+        headers.insert(RETRY_AFTER, HeaderValue::from(seconds(retry_after)));
+    }
+}
+
+/// Route layer limiting requests per API key, or per IP for requests without
+/// a valid key. Rejects with `429 Too Many Requests` and `Retry-After`.
+pub async fn rate_limit(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    let defaults = state.client_limits;
+    let (client, limits) = match request.extensions().get::<Identity>() {
+        Some(Identity::Caller(caller)) => (
+            Client::Key(caller.name.clone()),
+            ClientLimits {
+                requests_per_second: caller.rate_limit.unwrap_or(defaults.requests_per_second),
+                daily_quota: caller.daily_quota.unwrap_or(defaults.daily_quota),
+                ..defaults
+            },
+        ),
+        _ => (Client::Ip(client_ip(&request)), defaults),
+    };
+
+    // Daily counts of keys survive restarts
+    let now = Utc::now();
+    let today = now.date_naive();
+    let mut used_today = 0;
+    if let Client::Key(name) = &client {
+        if limits.daily_quota > 0 && !state.limiter.is_tracked(&client, today) {
+            used_today = state
+                .service
+                .get_api_usage(today, today, Some(name))
+                .await?
+                .iter()
+                .map(|day| day.requests)
+                .sum();
+        }
+    }
+
+    let verdict = state.limiter.check(&client, limits, used_today, now);
+    let mut response = if verdict.allowed {
+        next.run(request).await
+    } else {
+        let message = if verdict.quota.is_some_and(|q| q.remaining == 0) {
+            "Daily quota exceeded"
+        } else {
+            "Rate limit exceeded"
+        };
+        AppError::TooManyRequests(message.to_string()).into_response()
+    };
+    set_headers(response.headers_mut(), &verdict);
+
+    Ok(response)
+}
+
+/// Write API key usage counted since the last flush
+pub async fn flush_usage(state: &AppState) -> Result<()> {
+    let today = Utc::now().date_naive();
+    state.failed_keys.prune(today);
+    let usage = state.limiter.take_usage(today);
+    state.service.record_api_usage(&usage).await
+}
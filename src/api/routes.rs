@@ -1,35 +1,302 @@
 use axum::{
     Router,
-    routing::{get, post},
+    extract::Request,
+    http::{HeaderValue, header::LINK},
+    middleware::{Next, from_fn, from_fn_with_state},
+    response::Response,
+    routing::get,
 };
 use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
+use tracing::Span;
+use utoipa_swagger_ui::SwaggerUi;
 
-use super::handlers::{
-    AppState, get_currencies, get_historical, get_latest, health_check, root,
-    trigger_provider_sync, trigger_sync,
-};
+use super::auth::identify;
+use super::handlers::{AppState, health_check, health_live, health_ready, root};
+use super::metrics::{metrics_handler, track_requests};
+use super::openapi::OPENAPI;
+use super::v1;
+
+/// Span of a request, carrying the `X-Request-Id` set by `SetRequestIdLayer`
+fn request_span(request: &Request) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id,
+    )
+}
+
+/// Layer of the unversioned aliases of v1 routes: marks responses as
+/// deprecated and links the `/v1` equivalent
+async fn deprecated_alias(request: Request, next: Next) -> Response {
+    let successor = request
+        .uri()
+        .path_and_query()
+        .map(|p| format!("<{}{}>; rel=\"successor-version\"", v1::PREFIX, p))
+        .and_then(|link| HeaderValue::from_str(&link).ok());
 
-/// Create the API router with all routes
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Some(successor) = successor {
+        headers.insert(LINK, successor);
+    }
+    response
+}
+
+/// Create the API router with all routes.
+///
+/// Versioned routes live under `/v1` (see [`v1::routes`]); the same routes
+/// at the root are deprecated aliases kept for existing clients. `/` (also
+/// served at `/v1`), `/health*`, `/metrics`, `/openapi.json` and `/docs` are
+/// unversioned, open and not rate limited.
 pub fn create_router(state: Arc<AppState>) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    Router::new()
+    let open = Router::new()
         .route("/", get(root))
-        .route("/latest", get(get_latest))
-        .route("/currencies", get(get_currencies))
+        // Without it the `/:date_path` alias would take `/v1` for a date
+        .route(v1::PREFIX, get(root))
         .route("/health", get(health_check))
-        // Historical/time series endpoint
-        .route("/:date_path", get(get_historical))
-        // Admin endpoints
-        .route("/sync", post(trigger_sync))
-        .route("/sync/:provider", post(trigger_provider_sync))
+        .route("/health/live", get(health_live))
+        .route("/health/ready", get(health_ready))
+        .route("/metrics", get(metrics_handler))
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", OPENAPI.clone()));
+
+    let aliases = v1::routes(state.clone()).route_layer(from_fn(deprecated_alias));
+
+    Router::new()
+        .merge(open)
+        .nest(v1::PREFIX, v1::routes(state.clone()))
+        .merge(aliases)
         // Middleware
-        .layer(TraceLayer::new_for_http())
+        .layer(from_fn_with_state(state.clone(), identify))
+        .layer(from_fn(track_requests))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        // Keep the caller's X-Request-Id or generate one, and echo it back
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .layer(cors)
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_repository;
+    use crate::models::{DailyRates, Scope};
+    use crate::providers::ProviderRegistry;
+    use crate::service::RatesService;
+    use crate::usage::{ClientLimiter, ClientLimits};
+    use axum::body::{Body, to_bytes};
+    use axum::http::{
+        StatusCode,
+        header::{AUTHORIZATION, RETRY_AFTER},
+    };
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicBool;
+    use tower::ServiceExt;
+
+    async fn state(public_read: bool, client_limits: ClientLimits) -> Arc<AppState> {
+        let repository = test_repository().await;
+        repository
+            .store_daily_rates(&DailyRates {
+                date: Utc::now().date_naive(),
+                base_currency: "USD".to_string(),
+                rates: HashMap::from([("EUR".to_string(), 0.9)]),
+                provider: "ecb".to_string(),
+                estimated: false,
+            })
+            .await
+            .unwrap();
+
+        let service = RatesService::new(
+            repository,
+            Arc::new(ProviderRegistry::new()),
+            "USD".to_string(),
+        );
+        Arc::new(AppState {
+            service,
+            default_api_base: "USD".to_string(),
+            admin_tokens: HashMap::from([("admin-token".to_string(), "ops".to_string())]),
+            public_read,
+            client_limits,
+            limiter: ClientLimiter::new(),
+            failed_keys: ClientLimiter::new(),
+            startup_complete: AtomicBool::new(true),
+        })
+    }
+
+    async fn router() -> Router {
+        create_router(state(true, ClientLimits::default()).await)
+    }
+
+    /// Send a GET request, with `key` as a bearer token
+    async fn get(router: Router, uri: &str, key: Option<&str>) -> (Response, serde_json::Value) {
+        let mut request = Request::get(uri);
+        if let Some(key) = key {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", key));
+        }
+        let response = router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let (parts, body) = response.into_parts();
+        let body = to_bytes(body, usize::MAX).await.unwrap();
+        let json = serde_json::from_slice(&body).unwrap_or_default();
+        (Response::from_parts(parts, Body::empty()), json)
+    }
+
+    #[tokio::test]
+    async fn test_v1_routes() {
+        let router = router().await;
+
+        let (response, body) = get(router.clone(), "/v1/latest?to=EUR", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body["rates"]["EUR"], 0.9);
+        assert!(response.headers().get("deprecation").is_none());
+        assert!(response.headers().get(LINK).is_none());
+
+        let (response, body) = get(router.clone(), "/v1", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body["docs"], "/docs");
+
+        let (response, _) = get(router, "/v1/unknown/route", None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_aliases() {
+        let (response, body) = get(router().await, "/latest?to=EUR", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body["rates"]["EUR"], 0.9);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(
+            response.headers()[LINK],
+            "</v1/latest?to=EUR>; rel=\"successor-version\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_key_scopes() {
+        let state = state(false, ClientLimits::default()).await;
+        let (_, read_key) = state
+            .service
+            .create_api_key("reader", &[Scope::Read], None, None)
+            .await
+            .unwrap();
+        let router = create_router(state);
+
+        let (response, body) = get(router.clone(), "/v1/latest", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(body["detail"], "Unauthorized: Missing API key");
+
+        let (response, body) = get(router.clone(), "/v1/latest", Some("cr_unknown")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(body["detail"], "Unauthorized: Invalid API key");
+
+        let (response, _) = get(router.clone(), "/v1/latest", Some(&read_key)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let (response, body) = get(router.clone(), "/v1/sync/history", Some(&read_key)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            body["detail"],
+            "Forbidden: API key reader does not have the sync scope"
+        );
+
+        // Admin tokens hold every scope
+        let (response, _) = get(router, "/v1/sync/history", Some("admin-token")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_public_read() {
+        let router = router().await;
+
+        let (response, _) = get(router.clone(), "/v1/latest", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // A key that is sent must still be valid
+        let (response, _) = get(router.clone(), "/v1/latest", Some("cr_unknown")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let (response, _) = get(router, "/v1/sync/history", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let limits = ClientLimits {
+            requests_per_second: 0.01,
+            burst: 2,
+            daily_quota: 0,
+        };
+        let state = state(true, limits).await;
+        let (_, key) = state
+            .service
+            .create_api_key("metered", &[Scope::Read], None, Some(1))
+            .await
+            .unwrap();
+        let router = create_router(state);
+
+        for remaining in ["1", "0"] {
+            let (response, _) = get(router.clone(), "/v1/latest", None).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()["x-ratelimit-remaining"], remaining);
+        }
+        let (response, body) = get(router.clone(), "/v1/latest", None).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "100");
+        assert_eq!(body["detail"], "Too many requests: Rate limit exceeded");
+
+        // Keys have their own budget, and here a daily quota of one request
+        let (response, _) = get(router.clone(), "/v1/latest", Some(&key)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let (response, body) = get(router, "/v1/latest", Some(&key)).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(RETRY_AFTER));
+        assert_eq!(body["detail"], "Too many requests: Daily quota exceeded");
+    }
+
+    #[tokio::test]
+    async fn test_failed_keys_throttled() {
+        let limits = ClientLimits {
+            requests_per_second: 0.01,
+            burst: 2,
+            daily_quota: 0,
+        };
+        let router = create_router(state(true, limits).await);
+
+        // Keys are looked up on every route, including unlimited ones
+        for _ in 0..2 {
+            let (response, _) = get(router.clone(), "/health/live", Some("cr_unknown")).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let (response, body) = get(router.clone(), "/v1/latest", Some("cr_unknown")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "100");
+        assert_eq!(
+            body["detail"],
+            "Too many requests: Too many invalid API keys"
+        );
+
+        // Requests without a key are not affected
+        let (response, _) = get(router, "/v1/latest", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,114 @@
+//! Version 1 of the API, served under `/v1`.
+//!
+//! Within a version, paths, parameters and response fields are only ever
+//! added: nothing is removed, renamed or changes meaning. A breaking change
+//! goes into a new version module next to this one (`v2`) with its own
+//! router, OpenAPI paths and, where a response changes, its own response
+//! types; handlers whose behavior is unchanged are shared, so v1 keeps
+//! serving the `crate::models` types it was released with.
+
+use axum::{
+    Router,
+    middleware::from_fn_with_state,
+    routing::{delete, get, post, put},
+};
+use std::sync::Arc;
+use utoipa::OpenApi;
+
+use super::admin::{
+    self, create_manual_rate, delete_manual_rate, discard_quarantined, list_manual_rates,
+    list_quarantined, list_usage, manual_rates_audit, release_quarantined, update_manual_rate,
+};
+use super::auth::require_scope;
+use super::handlers::{
+    self, AppState, get_currencies, get_historical, get_latest, get_provider, get_reconciliation,
+    get_sync_job, list_providers, list_revisions, run_reconciliation, sync_history,
+    trigger_provider_sync, trigger_sync,
+};
+use super::rate_limit::rate_limit;
+use crate::models::Scope;
+
+/// Path prefix of this version
+pub const PREFIX: &str = "/v1";
+
+/// OpenAPI paths of this version, relative to [`PREFIX`]
+#[derive(OpenApi)]
+#[openapi(paths(
+    handlers::get_latest,
+    handlers::get_currencies,
+    handlers::get_historical,
+    handlers::list_providers,
+    handlers::get_provider,
+    handlers::list_revisions,
+    handlers::get_reconciliation,
+    handlers::run_reconciliation,
+    handlers::trigger_sync,
+    handlers::trigger_provider_sync,
+    handlers::get_sync_job,
+    handlers::sync_history,
+    admin::list_manual_rates,
+    admin::create_manual_rate,
+    admin::update_manual_rate,
+    admin::delete_manual_rate,
+    admin::manual_rates_audit,
+    admin::list_quarantined,
+    admin::release_quarantined,
+    admin::discard_quarantined,
+    admin::list_usage,
+))]
+pub struct V1Api;
+
+/// Routes of this version, relative to [`PREFIX`].
+///
+/// Routes are grouped by the API key scope they need: `read` (waived when
+/// the API is public-read), `sync` and `admin`. All of them are rate limited.
+pub fn routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    let scope = |scope: Scope| from_fn_with_state((state.clone(), scope), require_scope);
+
+    let read = Router::new()
+        .route("/latest", get(get_latest))
+        .route("/currencies", get(get_currencies))
+        .route("/providers", get(list_providers))
+        .route("/providers/:name", get(get_provider))
+        .route("/revisions", get(list_revisions))
+        // Running a reconciliation needs the admin scope (checked by the handler)
+        .route(
+            "/reconciliation/:range",
+            get(get_reconciliation).post(run_reconciliation),
+        )
+        // Historical/time series endpoint
+        .route("/:date_path", get(get_historical))
+        .route_layer(scope(Scope::Read));
+
+    let sync = Router::new()
+        .route("/sync", post(trigger_sync))
+        .route("/sync/:provider", post(trigger_provider_sync))
+        .route("/sync/jobs/:id", get(get_sync_job))
+        .route("/sync/history", get(sync_history))
+        .route_layer(scope(Scope::Sync));
+
+    let admin = Router::new()
+        // Manual override rates
+        .route(
+            "/admin/rates",
+            get(list_manual_rates).post(create_manual_rate),
+        )
+        .route("/admin/rates/audit", get(manual_rates_audit))
+        .route(
+            "/admin/rates/:date/:from/:to",
+            put(update_manual_rate).delete(delete_manual_rate),
+        )
+        // Rates held back by data quality checks
+        .route("/admin/quarantine", get(list_quarantined))
+        .route("/admin/quarantine/:id", delete(discard_quarantined))
+        .route("/admin/quarantine/:id/release", post(release_quarantined))
+        // Requests per API key
+        .route("/admin/usage", get(list_usage))
+        .route_layer(scope(Scope::Admin));
+
+    Router::new()
+        .merge(read)
+        .merge(sync)
+        .merge(admin)
+        .route_layer(from_fn_with_state(state, rate_limit))
+}
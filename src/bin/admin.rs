@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+use currency_rates::{Config, ProviderRegistry, RatesRepository, RatesService, models::Scope};
+
+const USAGE: &str = "\
+Usage: admin keys create <name> [--scopes read,sync,admin]
+                               [--rate-limit <per second>] [--daily-quota <requests>]
+       admin keys list
+       admin keys revoke <name>
+       admin keys usage [name] [--days <n>]
+
+Manage API keys. `admin` includes `sync`, which includes `read`; new keys
+get `read` unless --scopes is given. --rate-limit and --daily-quota override
+RATE_LIMIT_RPS and DAILY_QUOTA for the key. A created key is printed once and
+only its hash is stored. `usage` lists requests per key and day (last 30 days).";
+
+/// Parsed command line
+enum Command {
+    Create {
+        name: String,
+        scopes: Vec<Scope>,
+        rate_limit: Option<f64>,
+        daily_quota: Option<u64>,
+    },
+    List,
+    Revoke {
+        name: String,
+    },
+    Usage {
+        name: Option<String>,
+        days: i64,
+    },
+}
+
+fn parse_args() -> Result<Command> {
+    let mut positional = Vec::new();
+    let mut scopes = vec![Scope::Read];
+    let mut rate_limit = None;
+    let mut daily_quota = None;
+    let mut days = 30;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--scopes" => {
+                let value = args.next().context("--scopes needs a value")?;
+                scopes = value
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(anyhow::Error::msg)?;
+            }
+            "--rate-limit" => {
+                let value = args.next().context("--rate-limit needs a value")?;
+                rate_limit = Some(value.parse().context("Invalid --rate-limit")?);
+            }
+            "--daily-quota" => {
+                let value = args.next().context("--daily-quota needs a value")?;
+                daily_quota = Some(value.parse().context("Invalid --daily-quota")?);
+            }
+            "--days" => {
+                let value = args.next().context("--days needs a value")?;
+                days = value.parse().context("Invalid --days")?;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let positional: Vec<&str> = positional.iter().map(String::as_str).collect();
+    match positional.as_slice() {
+        ["keys", "create", name] => Ok(Command::Create {
+            name: name.to_string(),
+            scopes,
+            rate_limit,
+            daily_quota,
+        }),
+        ["keys", "list"] => Ok(Command::List),
+        ["keys", "revoke", name] => Ok(Command::Revoke {
+            name: name.to_string(),
+        }),
+        ["keys", "usage"] => Ok(Command::Usage { name: None, days }),
+        ["keys", "usage", name] => Ok(Command::Usage {
+            name: Some(name.to_string()),
+            days,
+        }),
+        _ => anyhow::bail!("{}", USAGE),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let command = parse_args()?;
+    let config = Config::from_env();
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect(&config.database_url)
+        .await?;
+    let repository = RatesRepository::new(pool);
+    repository.init().await?;
+
+    // Key management does not touch providers
+    let service = RatesService::new(
+        repository,
+        Arc::new(ProviderRegistry::new()),
+        config.default_api_base.clone(),
+    );
+
+    match command {
+        Command::Create {
+            name,
+            scopes,
+            rate_limit,
+            daily_quota,
+        } => {
+            let (api_key, key) = service
+                .create_api_key(&name, &scopes, rate_limit, daily_quota)
+                .await?;
+            let scopes: Vec<&str> = api_key.scopes.iter().map(Scope::as_str).collect();
+            println!("Created API key {} ({})", api_key.name, scopes.join(","));
+            println!("{}", key);
+            eprintln!("Store this key now, it cannot be shown again.");
+        }
+        Command::List => {
+            for api_key in service.list_api_keys().await? {
+                let scopes: Vec<&str> = api_key.scopes.iter().map(Scope::as_str).collect();
+                let status = match &api_key.revoked_at {
+                    Some(at) => format!("revoked {}", at),
+                    None => format!(
+                        "last used {}",
+                        api_key.last_used_at.as_deref().unwrap_or("never")
+                    ),
+                };
+                println!(
+                    "{}\t{}…\t{}\t{}",
+                    api_key.name,
+                    api_key.prefix,
+                    scopes.join(","),
+                    status
+                );
+            }
+        }
+        Command::Revoke { name } => {
+            service.revoke_api_key(&name).await?;
+            println!("Revoked API key {}", name);
+        }
+        Command::Usage { name, days } => {
+            let end = chrono::Utc::now().date_naive();
+            let start = end - chrono::Duration::days(days.max(1) - 1);
+            for day in service.get_api_usage(start, end, name.as_deref()).await? {
+                println!(
+                    "{}\t{}\t{} requests\t{} rejected",
+                    day.date, day.key, day.requests, day.rejected
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+use currency_rates::{
+    Config, RatesRepository, RatesService,
+    models::{BackfillMode, BackfillRequest, SyncTrigger},
+    providers::{build_registry, limiter},
+    telemetry,
+};
+
+const USAGE: &str = "\
+Usage: backfill <provider> <start> [end] [--mode fill-missing|overwrite] [--dry-run]
+
+Refetch a date range of a provider. fill-missing (default) only requests the
+(date, currency) cells that are not stored; overwrite replaces the whole range.
+--dry-run lists the missing cells without fetching anything.";
+
+/// Parsed command line
+struct Args {
+    provider: String,
+    start: NaiveDate,
+    end: NaiveDate,
+    mode: BackfillMode,
+    dry_run: bool,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut positional = Vec::new();
+    let mut mode = BackfillMode::default();
+    let mut dry_run = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mode" => {
+                let value = args.next().context("--mode needs a value")?;
+                mode = value.parse().map_err(anyhow::Error::msg)?;
+            }
+            "--dry-run" => dry_run = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let date = |s: &String| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").with_context(|| format!("Invalid date: {}", s))
+    };
+    let (provider, start) = match positional.as_slice() {
+        [provider, start, ..] => (provider.clone(), date(start)?),
+        _ => anyhow::bail!("{}", USAGE),
+    };
+    let end = match positional.get(2) {
+        Some(end) => date(end)?,
+        None => chrono::Utc::now().date_naive(),
+    };
+
+    Ok(Args {
+        provider,
+        start,
+        end,
+        mode,
+        dry_run,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    let _telemetry = telemetry::init("currency-rates-backfill", "currency_rates=info");
+
+    let args = parse_args()?;
+    let config = Config::from_env();
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect(&config.database_url)
+        .await?;
+    let repository = RatesRepository::new(pool);
+    repository.init().await?;
+
+    limiter::configure(config.fetch_limits);
+    let providers = Arc::new(build_registry(&config, &repository)?);
+    let service = RatesService::new(repository, providers, config.default_api_base.clone())
+        .with_quality_rules(config.quality.clone())
+        .with_reconciliation_tolerance(config.reconciliation_tolerance);
+
+    if args.dry_run {
+        let cells = service
+            .find_missing_cells(&args.provider, args.start, args.end)
+            .await?;
+        for (date, codes) in &cells {
+            println!("{} {}", date, codes.join(","));
+        }
+        println!(
+            "{} missing cells on {} dates",
+            cells.values().map(Vec::len).sum::<usize>(),
+            cells.len()
+        );
+        return Ok(());
+    }
+
+    let request = BackfillRequest {
+        start: args.start,
+        end: args.end,
+        mode: args.mode,
+    };
+    let job = service.create_backfill_job(&args.provider, request, SyncTrigger::Cli)?;
+    let job = service.run_sync_job(&job.id).await?;
+
+    println!("{}", serde_json::to_string_pretty(&job)?);
+    Ok(())
+}
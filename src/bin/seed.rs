@@ -1,20 +1,13 @@
 use anyhow::Result;
 use sqlx::sqlite::SqlitePoolOptions;
 use std::path::PathBuf;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use currency_rates::{Config, RatesRepository, seed};
+use currency_rates::{Config, RatesRepository, seed, telemetry};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "currency_rates=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let _telemetry = telemetry::init("currency-rates-seed", "currency_rates=info");
 
     tracing::info!("Currency Rates Database Seeder");
     tracing::info!("================================");
@@ -1,5 +1,16 @@
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use std::collections::HashMap;
 use std::env;
 
+use crate::providers::limiter::FetchLimits;
+use crate::quality::{DEFAULT_MAX_CHANGE, QualityRules};
+use crate::reconciliation::DEFAULT_TOLERANCE;
+use crate::usage::{ClientLimits, DEFAULT_BURST, DEFAULT_RATE};
+
+/// Default staleness threshold: covers a weekend plus a holiday of daily publishers
+pub const DEFAULT_STALE_AFTER_HOURS: u32 = 96;
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
@@ -12,6 +23,36 @@ pub struct Config {
     pub seed_on_startup: bool,
     pub sync_on_startup: bool,
     pub sync_cron: String,
+    /// Provider request parallelism and per-host rate limit
+    /// (`FETCH_CONCURRENCY`, `HTTP_RATE_LIMIT`, `HTTP_RATE_BURST`)
+    pub fetch_limits: FetchLimits,
+    /// Only fetch these NBU currencies (all discovered currencies when empty)
+    pub nbu_include: Vec<String>,
+    /// NBU currencies to skip. Defaults to the currencies published by ECB.
+    pub nbu_exclude: Option<Vec<String>>,
+    /// Excluded NBU currencies still fetched for reconciliation.
+    /// Defaults to EUR, GBP, CHF and JPY.
+    pub nbu_reference: Option<Vec<String>>,
+    /// Directory for the last downloaded ECB files (`ECB_CACHE_DIR`, empty to disable)
+    pub ecb_cache_dir: Option<String>,
+    /// Path to a TOML/YAML file with declarative provider definitions
+    pub providers_config: Option<String>,
+    /// Path to a TOML/YAML file with additional peg definitions
+    pub pegs_config: Option<String>,
+    /// Static tokens with the `admin` scope mapped to the name recorded in audit
+    /// trails (`ADMIN_TOKENS=alice:token1,bob:token2`). Prefer API keys created
+    /// with the `admin` binary.
+    pub admin_tokens: HashMap<String, String>,
+    /// Serve read endpoints without an API key (`PUBLIC_READ`, default true).
+    /// Sync and admin endpoints always need a key with the matching scope.
+    pub public_read: bool,
+    /// Limits for fetched rates (`QUALITY_MAX_CHANGE`, `QUALITY_MAX_CHANGE_BY_CURRENCY`)
+    pub quality: QualityRules,
+    /// Relative difference reported as a cross-provider divergence (`RECONCILIATION_TOLERANCE`)
+    pub reconciliation_tolerance: f64,
+    /// Request limits per API key, or per IP without a key
+    /// (`RATE_LIMIT_RPS`, `RATE_LIMIT_BURST`, `DAILY_QUOTA`)
+    pub client_limits: ClientLimits,
 }
 
 impl Config {
@@ -40,10 +81,249 @@ impl Config {
                 .unwrap_or(true),
 
             sync_cron: env::var("SYNC_CRON").unwrap_or_else(|_| "0 0 16 * * *".to_string()), // 4 PM UTC daily (after ECB publishes)
+
+            fetch_limits: {
+                let defaults = FetchLimits::default();
+                FetchLimits {
+                    concurrency: env::var("FETCH_CONCURRENCY")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.concurrency),
+                    requests_per_second: env::var("HTTP_RATE_LIMIT")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.requests_per_second),
+                    burst: env::var("HTTP_RATE_BURST")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.burst),
+                }
+            },
+
+            nbu_include: env::var("NBU_INCLUDE")
+                .map(|v| parse_list(&v))
+                .unwrap_or_default(),
+
+            nbu_exclude: env::var("NBU_EXCLUDE").ok().map(|v| parse_list(&v)),
+
+            nbu_reference: env::var("NBU_REFERENCE").ok().map(|v| parse_list(&v)),
+
+            ecb_cache_dir: match env::var("ECB_CACHE_DIR") {
+                Ok(v) if v.is_empty() => None,
+                Ok(v) => Some(v),
+                Err(_) => Some("cache/ecb".to_string()),
+            },
+
+            providers_config: env::var("PROVIDERS_CONFIG").ok().filter(|v| !v.is_empty()),
+
+            pegs_config: env::var("PEGS_CONFIG").ok().filter(|v| !v.is_empty()),
+
+            admin_tokens: env::var("ADMIN_TOKENS")
+                .map(|v| parse_admin_tokens(&v))
+                .unwrap_or_default(),
+
+            public_read: env::var("PUBLIC_READ")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+
+            quality: QualityRules {
+                max_change: env::var("QUALITY_MAX_CHANGE")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_MAX_CHANGE),
+                max_change_by_currency: env::var("QUALITY_MAX_CHANGE_BY_CURRENCY")
+                    .map(|v| parse_currency_limits(&v))
+                    .unwrap_or_default(),
+            },
+
+            reconciliation_tolerance: env::var("RECONCILIATION_TOLERANCE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_TOLERANCE),
+
+            client_limits: ClientLimits {
+                requests_per_second: env::var("RATE_LIMIT_RPS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_RATE),
+                burst: env::var("RATE_LIMIT_BURST")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_BURST),
+                daily_quota: env::var("DAILY_QUOTA")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0),
+            },
         }
     }
 }
 
+/// What to do when a provider could only fetch part of the requested data
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PartialDataPolicy {
+    /// Store nothing and report the sync as failed
+    Fail,
+    /// Store what was fetched and flag the sync as partial
+    #[default]
+    StorePartial,
+    /// Store nothing and sync the provider again after a delay
+    RetryLater,
+}
+
+impl std::str::FromStr for PartialDataPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fail" => Ok(Self::Fail),
+            "store" | "store_partial" => Ok(Self::StorePartial),
+            "retry" | "retry_later" => Ok(Self::RetryLater),
+            other => Err(format!("unknown partial data policy: {}", other)),
+        }
+    }
+}
+
+/// Per-provider sync settings, read from `<NAME>_*` environment variables
+/// (e.g. `NBU_ENABLED`, `NBU_SYNC_CRON`)
+#[derive(Debug, Clone)]
+pub struct ProviderSettings {
+    /// Synced by the scheduler and `/sync`. Stored data stays queryable either way.
+    pub enabled: bool,
+    /// Own cron expression (`<NAME>_SYNC_CRON`); `SYNC_CRON` when not set
+    pub sync_cron: Option<String>,
+    /// Days before the last stored date to re-fetch on every sync (`<NAME>_LOOKBACK_DAYS`)
+    pub lookback_days: u32,
+    /// Local publication time (`<NAME>_PUBLISH_TIME`, e.g. `16:00`).
+    /// Overrides the schedule reported by the provider.
+    pub publish_time: Option<NaiveTime>,
+    /// Time zone of the publication time (`<NAME>_TIMEZONE`, e.g. `Europe/Berlin`)
+    pub timezone: Option<Tz>,
+    /// Handling of partially fetched data (`<NAME>_PARTIAL_DATA`: fail, store, retry_later)
+    pub partial_data: PartialDataPolicy,
+    /// Delay before a `retry_later` sync runs again (`<NAME>_RETRY_LATER_MINUTES`)
+    pub retry_later_minutes: u32,
+    /// Hours without a successful sync or a newer rate date after which the
+    /// provider is reported stale (`<NAME>_STALE_AFTER_HOURS`, default `STALE_AFTER_HOURS`)
+    pub stale_after_hours: u32,
+}
+
+impl ProviderSettings {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            sync_cron: None,
+            lookback_days: 0,
+            publish_time: None,
+            timezone: None,
+            partial_data: PartialDataPolicy::default(),
+            retry_later_minutes: 30,
+            stale_after_hours: DEFAULT_STALE_AFTER_HOURS,
+        }
+    }
+
+    /// Read settings for a provider, falling back to `enabled_default`
+    pub fn from_env(provider: &str, enabled_default: bool) -> Self {
+        let prefix = env_prefix(provider);
+        let var = |key: &str| {
+            env::var(format!("{}_{}", prefix, key))
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let publish_time = var("PUBLISH_TIME").and_then(|v| {
+            NaiveTime::parse_from_str(&v, "%H:%M")
+                .or_else(|_| NaiveTime::parse_from_str(&v, "%H:%M:%S"))
+                .map_err(|_| tracing::warn!("Ignoring invalid {}_PUBLISH_TIME: {}", prefix, v))
+                .ok()
+        });
+        let timezone = var("TIMEZONE").and_then(|v| {
+            v.parse::<Tz>()
+                .map_err(|_| tracing::warn!("Ignoring invalid {}_TIMEZONE: {}", prefix, v))
+                .ok()
+        });
+
+        let partial_data = var("PARTIAL_DATA")
+            .and_then(|v| {
+                v.parse()
+                    .map_err(|e| tracing::warn!("Ignoring {}_PARTIAL_DATA: {}", prefix, e))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        Self {
+            enabled: var("ENABLED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(enabled_default),
+            sync_cron: var("SYNC_CRON"),
+            lookback_days: var("LOOKBACK_DAYS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            publish_time,
+            timezone,
+            partial_data,
+            retry_later_minutes: var("RETRY_LATER_MINUTES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+            stale_after_hours: var("STALE_AFTER_HOURS")
+                .or_else(|| env::var("STALE_AFTER_HOURS").ok())
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_STALE_AFTER_HOURS),
+        }
+    }
+}
+
+/// Environment variable prefix for a provider name ("my-bank" -> "MY_BANK")
+fn env_prefix(provider: &str) -> String {
+    provider
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Parse a comma-separated list of currency codes
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|c| c.trim().to_uppercase())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+/// Parse `CODE:limit` pairs into a currency -> limit map
+fn parse_currency_limits(value: &str) -> HashMap<String, f64> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let (code, limit) = entry.trim().split_once(':')?;
+            let limit = limit.trim().parse().ok()?;
+            Some((code.trim().to_uppercase(), limit))
+        })
+        .collect()
+}
+
+/// Parse `name:token` pairs into a token -> name map
+fn parse_admin_tokens(value: &str) -> HashMap<String, String> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let (name, token) = entry.trim().split_once(':')?;
+            let (name, token) = (name.trim(), token.trim());
+            if name.is_empty() || token.is_empty() {
+                return None;
+            }
+            Some((token.to_string(), name.to_string()))
+        })
+        .collect()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self::from_env()
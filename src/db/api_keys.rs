@@ -0,0 +1,324 @@
+use chrono::NaiveDate;
+use sqlx::FromRow;
+
+use super::{RatesRepository, fetch_returning};
+use crate::error::{AppError, Result};
+use crate::models::{ApiKey, ApiUsage, Scope};
+
+/// Columns added to `api_keys` after the first release
+const API_KEY_COLUMNS: &[(&str, &str)] = &[("rate_limit", "REAL"), ("daily_quota", "INTEGER")];
+
+/// Columns selected for an `ApiKey`
+const API_KEY_SELECT: &str =
+    "id, name, prefix, scopes, rate_limit, daily_quota, created_at, last_used_at, revoked_at";
+
+/// Database row for a day of API key usage
+#[derive(Debug, FromRow)]
+struct UsageRow {
+    name: String,
+    date: String,
+    requests: i64,
+    rejected: i64,
+}
+
+impl TryFrom<UsageRow> for ApiUsage {
+    type Error = AppError;
+
+    fn try_from(row: UsageRow) -> Result<Self> {
+        Ok(ApiUsage {
+            key: row.name,
+            date: NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?,
+            requests: row.requests as u64,
+            rejected: row.rejected as u64,
+        })
+    }
+}
+
+/// Database row for an API key
+#[derive(Debug, FromRow)]
+struct ApiKeyRow {
+    id: i64,
+    name: String,
+    prefix: String,
+    scopes: String,
+    rate_limit: Option<f64>,
+    daily_quota: Option<i64>,
+    created_at: String,
+    last_used_at: Option<String>,
+    revoked_at: Option<String>,
+}
+
+impl From<ApiKeyRow> for ApiKey {
+    fn from(row: ApiKeyRow) -> Self {
+        ApiKey {
+            id: row.id,
+            name: row.name,
+            prefix: row.prefix,
+            scopes: row
+                .scopes
+                .split(',')
+                .filter_map(|s| s.parse::<Scope>().ok())
+                .collect(),
+            rate_limit: row.rate_limit,
+            daily_quota: row.daily_quota.map(|q| q.max(0) as u64),
+            created_at: row.created_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+        }
+    }
+}
+
+impl RatesRepository {
+    /// Create the API key and usage tables. Only SHA-256 hashes of the keys are stored.
+    pub(super) async fn init_api_keys(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS api_keys (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                key_hash TEXT NOT NULL UNIQUE,
+                prefix TEXT NOT NULL,
+                scopes TEXT NOT NULL,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                last_used_at TEXT,
+                revoked_at TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let existing: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info('api_keys')")
+                .fetch_all(&self.pool)
+                .await?;
+        for (column, kind) in API_KEY_COLUMNS {
+            if !existing.iter().any(|c| c == column) {
+                sqlx::query(&format!(
+                    "ALTER TABLE api_keys ADD COLUMN {} {}",
+                    column, kind
+                ))
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS api_key_usage (
+                name TEXT NOT NULL,
+                date TEXT NOT NULL,
+                requests INTEGER NOT NULL DEFAULT 0,
+                rejected INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (name, date)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store a new API key
+    pub async fn insert_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        prefix: &str,
+        scopes: &[Scope],
+        rate_limit: Option<f64>,
+        daily_quota: Option<u64>,
+    ) -> Result<ApiKey> {
+        let scopes: Vec<&str> = scopes.iter().map(Scope::as_str).collect();
+        let sql = format!(
+            r#"
+            INSERT INTO api_keys (name, key_hash, prefix, scopes, rate_limit, daily_quota)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING {}
+            "#,
+            API_KEY_SELECT
+        );
+        let row: ApiKeyRow = fetch_returning(
+            sqlx::query_as(&sql)
+                .bind(name)
+                .bind(key_hash)
+                .bind(prefix)
+                .bind(scopes.join(","))
+                .bind(rate_limit)
+                .bind(daily_quota.map(|q| q as i64)),
+            &self.pool,
+        )
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                AppError::Conflict(format!("API key {} already exists", name))
+            }
+            e => e.into(),
+        })?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+        Ok(row.into())
+    }
+
+    /// Active (not revoked) key with the given hash
+    pub async fn find_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let row: Option<ApiKeyRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM api_keys WHERE key_hash = ? AND revoked_at IS NULL",
+            API_KEY_SELECT
+        ))
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(ApiKey::from))
+    }
+
+    /// All keys, including revoked ones
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKey>> {
+        let rows: Vec<ApiKeyRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM api_keys ORDER BY name",
+            API_KEY_SELECT
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(ApiKey::from).collect())
+    }
+
+    /// Revoke a key by name. Returns false if there is no active key with that name.
+    pub async fn revoke_api_key(&self, name: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET revoked_at = CURRENT_TIMESTAMP WHERE name = ? AND revoked_at IS NULL",
+        )
+        .bind(name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record that a key was used
+    pub async fn touch_api_key(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Add request counts to the stored usage
+    pub async fn add_api_usage(&self, usage: &[ApiUsage]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for day in usage {
+            sqlx::query(
+                r#"
+                INSERT INTO api_key_usage (name, date, requests, rejected)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(name, date) DO UPDATE SET
+                    requests = requests + excluded.requests,
+                    rejected = rejected + excluded.rejected
+                "#,
+            )
+            .bind(&day.key)
+            .bind(day.date.to_string())
+            .bind(day.requests as i64)
+            .bind(day.rejected as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stored usage of `start..=end`, optionally of one key
+    pub async fn get_api_usage(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        name: Option<&str>,
+    ) -> Result<Vec<ApiUsage>> {
+        let rows: Vec<UsageRow> = sqlx::query_as(
+            r#"
+            SELECT name, date, requests, rejected
+            FROM api_key_usage
+            WHERE date >= ? AND date <= ? AND (? IS NULL OR name = ?)
+            ORDER BY date DESC, name
+            "#,
+        )
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .bind(name)
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ApiUsage::try_from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_repository;
+
+    #[tokio::test]
+    async fn test_api_keys() {
+        let repository = test_repository().await;
+
+        let key = repository
+            .insert_api_key(
+                "ops",
+                "hash1",
+                "cr_0123",
+                &[Scope::Sync, Scope::Read],
+                None,
+                Some(1000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(key.scopes, vec![Scope::Sync, Scope::Read]);
+        assert_eq!(key.daily_quota, Some(1000));
+        assert!(matches!(
+            repository
+                .insert_api_key("ops", "hash2", "cr_4567", &[Scope::Read], None, None)
+                .await,
+            Err(AppError::Conflict(_))
+        ));
+
+        let found = repository.find_api_key("hash1").await.unwrap().unwrap();
+        assert_eq!(found.name, "ops");
+        assert!(repository.find_api_key("hash2").await.unwrap().is_none());
+
+        assert!(repository.revoke_api_key("ops").await.unwrap());
+        assert!(!repository.revoke_api_key("ops").await.unwrap());
+        assert!(repository.find_api_key("hash1").await.unwrap().is_none());
+        assert!(
+            repository.list_api_keys().await.unwrap()[0]
+                .revoked_at
+                .is_some()
+        );
+
+        let date = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let usage = ApiUsage {
+            key: "ops".to_string(),
+            date,
+            requests: 10,
+            rejected: 1,
+        };
+        for _ in 0..2 {
+            repository
+                .add_api_usage(std::slice::from_ref(&usage))
+                .await
+                .unwrap();
+        }
+        let stored = repository
+            .get_api_usage(date, date, Some("ops"))
+            .await
+            .unwrap();
+        assert_eq!((stored[0].requests, stored[0].rejected), (20, 2));
+    }
+}
@@ -0,0 +1,243 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{FromRow, Row};
+use std::collections::{HashMap, HashSet};
+
+use super::RatesRepository;
+use super::repository::{PREFERENCE_ORDER, priority_json};
+use crate::error::Result;
+use crate::models::ManualRate;
+use crate::providers::DERIVED_PROVIDER;
+
+/// Format used by SQLite's `CURRENT_TIMESTAMP` (UTC)
+pub(super) fn sql_timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Database row for a rate resolved at a point in time
+#[derive(Debug, FromRow)]
+struct RateAsOfRow {
+    date: String,
+    target_currency: String,
+    rate: f64,
+}
+
+/// Database row for a manual rate rebuilt from its audit trail
+#[derive(Debug, FromRow)]
+struct ManualAsOfRow {
+    date: String,
+    base_currency: String,
+    target_currency: String,
+    new_rate: f64,
+    note: Option<String>,
+    actor: String,
+    changed_at: String,
+}
+
+impl RatesRepository {
+    /// Latest rate date stored by `as_of`
+    pub async fn get_latest_date_as_of(&self, as_of: DateTime<Utc>) -> Result<Option<NaiveDate>> {
+        let max_date: Option<String> =
+            sqlx::query_scalar("SELECT MAX(date) FROM exchange_rates WHERE created_at <= ?")
+                .bind(sql_timestamp(as_of))
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(max_date
+            .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+            .transpose()?)
+    }
+
+    /// Rates for a date range as they were stored at `as_of`. A rate is visible
+    /// once created; its value then is the `old_rate` of the first revision
+    /// made after `as_of`, or the current value if it was not revised since.
+    /// Providers are preferred in `priority` order, like in `get_rates_for_date`.
+    pub async fn get_rates_for_range_as_of(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        base_currency: &str,
+        as_of: DateTime<Utc>,
+        priority: &[String],
+    ) -> Result<HashMap<NaiveDate, HashMap<String, f64>>> {
+        let as_of = sql_timestamp(as_of);
+        let priority = priority_json(priority);
+        let rows: Vec<RateAsOfRow> = sqlx::query_as(&format!(
+            r#"
+            SELECT er.date, er.target_currency,
+                   COALESCE((
+                       SELECT rv.old_rate FROM rate_revisions rv
+                       WHERE rv.date = er.date
+                         AND rv.base_currency = er.base_currency
+                         AND rv.target_currency = er.target_currency
+                         AND rv.provider = er.provider
+                         AND rv.revised_at > ?
+                       ORDER BY rv.id
+                       LIMIT 1
+                   ), er.rate) AS rate
+            FROM exchange_rates er
+            WHERE er.date >= ? AND er.date <= ? AND er.base_currency = ?
+              AND er.created_at <= ?
+            ORDER BY er.date, {}
+            "#,
+            PREFERENCE_ORDER
+        ))
+        .bind(&as_of)
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .bind(base_currency)
+        .bind(&as_of)
+        .bind(DERIVED_PROVIDER)
+        .bind(&priority)
+        .bind(&priority)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // The preferred row of each currency comes last
+        let mut results: HashMap<NaiveDate, HashMap<String, f64>> = HashMap::new();
+        for row in rows {
+            let date = NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?;
+            results
+                .entry(date)
+                .or_default()
+                .insert(row.target_currency, row.rate);
+        }
+
+        Ok(results)
+    }
+
+    /// Currencies whose only rate stored by `as_of` came from derived pegs
+    pub async fn get_derived_currencies_as_of(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        as_of: DateTime<Utc>,
+    ) -> Result<HashSet<String>> {
+        let as_of = sql_timestamp(as_of);
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT er.target_currency
+            FROM exchange_rates er
+            WHERE er.provider = ? AND er.date >= ? AND er.date <= ?
+              AND er.created_at <= ?
+              AND NOT EXISTS (
+                  SELECT 1 FROM exchange_rates o
+                  WHERE o.date = er.date
+                    AND o.base_currency = er.base_currency
+                    AND o.target_currency = er.target_currency
+                    AND o.provider != er.provider
+                    AND o.created_at <= ?
+              )
+            "#,
+        )
+        .bind(DERIVED_PROVIDER)
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .bind(&as_of)
+        .bind(&as_of)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.get("target_currency")).collect())
+    }
+
+    /// Manual rates in effect at `as_of`, rebuilt from the audit trail
+    pub async fn get_manual_rates_as_of(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        as_of: DateTime<Utc>,
+    ) -> Result<Vec<ManualRate>> {
+        let rows: Vec<ManualAsOfRow> = sqlx::query_as(
+            r#"
+            SELECT a.date, a.base_currency, a.target_currency, a.new_rate,
+                   a.note, a.actor, a.changed_at
+            FROM manual_rates_audit a
+            WHERE a.id = (
+                SELECT MAX(l.id) FROM manual_rates_audit l
+                WHERE l.date = a.date
+                  AND l.base_currency = a.base_currency
+                  AND l.target_currency = a.target_currency
+                  AND l.changed_at <= ?
+            )
+              AND a.action != 'delete' AND a.new_rate IS NOT NULL
+              AND a.date >= ? AND a.date <= ?
+            ORDER BY a.date, a.base_currency, a.target_currency
+            "#,
+        )
+        .bind(sql_timestamp(as_of))
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(ManualRate {
+                    date: NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?,
+                    base_currency: row.base_currency,
+                    target_currency: row.target_currency,
+                    rate: row.new_rate,
+                    note: row.note,
+                    updated_by: row.actor,
+                    updated_at: row.changed_at,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_repository;
+    use crate::models::DailyRates;
+    use chrono::{Duration, NaiveDate, Utc};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_rates_as_of() {
+        let repository = test_repository().await;
+
+        let date = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let daily = |rate: f64| DailyRates {
+            date,
+            base_currency: "USD".to_string(),
+            rates: HashMap::from([("EUR".to_string(), rate)]),
+            provider: "ecb".to_string(),
+            estimated: false,
+        };
+        repository
+            .store_daily_rates_batch(&[daily(0.91)])
+            .await
+            .unwrap();
+        repository
+            .store_daily_rates_batch(&[daily(0.92)])
+            .await
+            .unwrap();
+
+        // Pretend the rate was stored a day ago and revised an hour ago
+        sqlx::query("UPDATE exchange_rates SET created_at = datetime('now', '-1 day')")
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE rate_revisions SET revised_at = datetime('now', '-1 hour')")
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+
+        let rate_at = |hours: i64| {
+            let repository = repository.clone();
+            async move {
+                let as_of = Utc::now() - Duration::hours(hours);
+                repository
+                    .get_rates_for_range_as_of(date, date, "USD", as_of, &[])
+                    .await
+                    .unwrap()
+                    .get(&date)
+                    .and_then(|rates| rates.get("EUR").copied())
+            }
+        };
+        assert_eq!(rate_at(48).await, None);
+        assert_eq!(rate_at(2).await, Some(0.91));
+        assert_eq!(rate_at(0).await, Some(0.92));
+    }
+}
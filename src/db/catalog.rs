@@ -0,0 +1,109 @@
+use chrono::NaiveDate;
+use sqlx::FromRow;
+
+use super::RatesRepository;
+use crate::error::Result;
+use crate::models::ProviderCurrency;
+
+/// Database row for a provider's currency catalog
+#[derive(Debug, FromRow)]
+struct ProviderCurrencyRow {
+    code: String,
+    name: String,
+    first_date: Option<String>,
+    last_date: Option<String>,
+}
+
+impl RatesRepository {
+    /// Create the table tracking which currencies each provider publishes
+    pub(super) async fn init_catalog(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS provider_currencies (
+                provider TEXT NOT NULL,
+                code TEXT NOT NULL,
+                name TEXT NOT NULL,
+                first_date TEXT,
+                last_date TEXT,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY(provider, code)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get the currency catalog of a provider
+    pub async fn get_provider_currencies(&self, provider: &str) -> Result<Vec<ProviderCurrency>> {
+        let rows: Vec<ProviderCurrencyRow> = sqlx::query_as(
+            r#"
+            SELECT code, name, first_date, last_date
+            FROM provider_currencies
+            WHERE provider = ?
+            ORDER BY code
+            "#,
+        )
+        .bind(provider)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let parse =
+            |d: Option<String>| d.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ProviderCurrency {
+                code: row.code,
+                name: row.name,
+                first_date: parse(row.first_date),
+                last_date: parse(row.last_date),
+            })
+            .collect())
+    }
+
+    /// Insert or widen catalog entries. Known dates are never narrowed:
+    /// the earliest first date and the latest last date win.
+    pub async fn upsert_provider_currencies(
+        &self,
+        provider: &str,
+        currencies: &[ProviderCurrency],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for currency in currencies {
+            sqlx::query(
+                r#"
+                INSERT INTO provider_currencies (provider, code, name, first_date, last_date)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(provider, code) DO UPDATE SET
+                    name = excluded.name,
+                    first_date = CASE
+                        WHEN provider_currencies.first_date IS NULL THEN excluded.first_date
+                        WHEN excluded.first_date IS NULL THEN provider_currencies.first_date
+                        ELSE MIN(provider_currencies.first_date, excluded.first_date)
+                    END,
+                    last_date = CASE
+                        WHEN provider_currencies.last_date IS NULL THEN excluded.last_date
+                        WHEN excluded.last_date IS NULL THEN provider_currencies.last_date
+                        ELSE MAX(provider_currencies.last_date, excluded.last_date)
+                    END,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(provider)
+            .bind(&currency.code)
+            .bind(&currency.name)
+            .bind(currency.first_date.map(|d| d.to_string()))
+            .bind(currency.last_date.map(|d| d.to_string()))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::NaiveDate;
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+
+use super::RatesRepository;
+use crate::error::Result;
+
+impl RatesRepository {
+    /// Currencies stored for a provider on each date of `start..=end`
+    pub async fn get_stored_cells(
+        &self,
+        provider: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<HashMap<NaiveDate, HashSet<String>>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT date, target_currency FROM exchange_rates
+            WHERE provider = ? AND date >= ? AND date <= ?
+            "#,
+        )
+        .bind(provider)
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut cells: HashMap<NaiveDate, HashSet<String>> = HashMap::new();
+        for row in rows {
+            let date: String = row.get("date");
+            let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") else {
+                continue;
+            };
+            cells
+                .entry(date)
+                .or_default()
+                .insert(row.get("target_currency"));
+        }
+
+        Ok(cells)
+    }
+
+    /// First and last stored date of every currency of a provider
+    pub async fn get_currency_spans(
+        &self,
+        provider: &str,
+    ) -> Result<HashMap<String, (NaiveDate, NaiveDate)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT target_currency, MIN(date) AS first_date, MAX(date) AS last_date
+            FROM exchange_rates
+            WHERE provider = ?
+            GROUP BY target_currency
+            "#,
+        )
+        .bind(provider)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let parse = |d: String| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok();
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let first = parse(row.get("first_date"))?;
+                let last = parse(row.get("last_date"))?;
+                Some((row.get("target_currency"), (first, last)))
+            })
+            .collect())
+    }
+}
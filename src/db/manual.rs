@@ -0,0 +1,360 @@
+use chrono::NaiveDate;
+use sqlx::{FromRow, Row};
+
+use super::{RateStats, RatesRepository, fetch_returning};
+use crate::error::{AppError, Result};
+use crate::models::{ManualRate, ManualRateAudit};
+
+/// Database row for manual rates
+#[derive(Debug, FromRow)]
+struct ManualRateRow {
+    date: String,
+    base_currency: String,
+    target_currency: String,
+    rate: f64,
+    note: Option<String>,
+    updated_by: String,
+    updated_at: String,
+}
+
+impl TryFrom<ManualRateRow> for ManualRate {
+    type Error = AppError;
+
+    fn try_from(row: ManualRateRow) -> Result<Self> {
+        Ok(ManualRate {
+            date: NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?,
+            base_currency: row.base_currency,
+            target_currency: row.target_currency,
+            rate: row.rate,
+            note: row.note,
+            updated_by: row.updated_by,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+/// Database row for the manual rates audit trail
+#[derive(Debug, FromRow)]
+struct ManualAuditRow {
+    id: i64,
+    action: String,
+    date: String,
+    base_currency: String,
+    target_currency: String,
+    old_rate: Option<f64>,
+    new_rate: Option<f64>,
+    note: Option<String>,
+    actor: String,
+    changed_at: String,
+}
+
+impl RatesRepository {
+    /// Create tables for manually entered rates and their audit trail
+    pub(super) async fn init_manual(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS manual_rates (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date TEXT NOT NULL,
+                base_currency TEXT NOT NULL,
+                target_currency TEXT NOT NULL,
+                rate REAL NOT NULL,
+                note TEXT,
+                updated_by TEXT NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(date, base_currency, target_currency)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS manual_rates_audit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                action TEXT NOT NULL,
+                date TEXT NOT NULL,
+                base_currency TEXT NOT NULL,
+                target_currency TEXT NOT NULL,
+                old_rate REAL,
+                new_rate REAL,
+                note TEXT,
+                actor TEXT NOT NULL,
+                changed_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_manual_audit_pair
+            ON manual_rates_audit(date, base_currency, target_currency)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a single manual rate
+    pub async fn get_manual_rate(
+        &self,
+        date: NaiveDate,
+        base_currency: &str,
+        target_currency: &str,
+    ) -> Result<Option<ManualRate>> {
+        let row: Option<ManualRateRow> = sqlx::query_as(
+            r#"
+            SELECT date, base_currency, target_currency, rate, note, updated_by, updated_at
+            FROM manual_rates
+            WHERE date = ? AND base_currency = ? AND target_currency = ?
+            "#,
+        )
+        .bind(date.to_string())
+        .bind(base_currency)
+        .bind(target_currency)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(ManualRate::try_from).transpose()
+    }
+
+    /// Currencies of all manual rates, sorted
+    pub async fn get_manual_currencies(&self) -> Result<Vec<String>> {
+        let codes = sqlx::query_scalar(
+            r#"
+            SELECT base_currency FROM manual_rates
+            UNION
+            SELECT target_currency FROM manual_rates
+            ORDER BY 1
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(codes)
+    }
+
+    /// Summary of the manual rates table, counted like a provider's rates
+    pub async fn get_manual_stats(&self) -> Result<RateStats> {
+        let row = sqlx::query(
+            r#"
+            SELECT (SELECT COUNT(*) FROM manual_rates) AS rows_count,
+                   (SELECT COUNT(*) FROM (
+                       SELECT base_currency FROM manual_rates
+                       UNION
+                       SELECT target_currency FROM manual_rates
+                   )) AS currencies_count,
+                   (SELECT MIN(date) FROM manual_rates) AS min_date,
+                   (SELECT MAX(date) FROM manual_rates) AS max_date
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let date = |column: &str| -> Result<Option<NaiveDate>> {
+            let value: Option<String> = row.get(column);
+            Ok(value
+                .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+                .transpose()?)
+        };
+
+        Ok(RateStats {
+            rows: row.get("rows_count"),
+            currencies: row.get("currencies_count"),
+            first_date: date("min_date")?,
+            last_date: date("max_date")?,
+        })
+    }
+
+    /// Get manual rates for a date range
+    pub async fn get_manual_rates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<ManualRate>> {
+        let rows: Vec<ManualRateRow> = sqlx::query_as(
+            r#"
+            SELECT date, base_currency, target_currency, rate, note, updated_by, updated_at
+            FROM manual_rates
+            WHERE date >= ? AND date <= ?
+            ORDER BY date, base_currency, target_currency
+            "#,
+        )
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ManualRate::try_from).collect()
+    }
+
+    /// Insert or update a manual rate and record the change in the audit trail.
+    /// Returns the previous rate, if any.
+    pub async fn upsert_manual_rate(
+        &self,
+        date: NaiveDate,
+        base_currency: &str,
+        target_currency: &str,
+        rate: f64,
+        note: Option<&str>,
+        actor: &str,
+    ) -> Result<Option<f64>> {
+        let date_str = date.to_string();
+        let mut tx = self.pool.begin().await?;
+
+        let old_rate: Option<f64> = sqlx::query_scalar(
+            r#"
+            SELECT rate FROM manual_rates
+            WHERE date = ? AND base_currency = ? AND target_currency = ?
+            "#,
+        )
+        .bind(&date_str)
+        .bind(base_currency)
+        .bind(target_currency)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO manual_rates (date, base_currency, target_currency, rate, note, updated_by)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(date, base_currency, target_currency) DO UPDATE SET
+                rate = excluded.rate,
+                note = excluded.note,
+                updated_by = excluded.updated_by,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(&date_str)
+        .bind(base_currency)
+        .bind(target_currency)
+        .bind(rate)
+        .bind(note)
+        .bind(actor)
+        .execute(&mut *tx)
+        .await?;
+
+        let action = if old_rate.is_some() {
+            "update"
+        } else {
+            "create"
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO manual_rates_audit
+                (action, date, base_currency, target_currency, old_rate, new_rate, note, actor)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(action)
+        .bind(&date_str)
+        .bind(base_currency)
+        .bind(target_currency)
+        .bind(old_rate)
+        .bind(rate)
+        .bind(note)
+        .bind(actor)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(old_rate)
+    }
+
+    /// Delete a manual rate and record the deletion in the audit trail.
+    /// Returns the deleted rate, if it existed.
+    pub async fn delete_manual_rate(
+        &self,
+        date: NaiveDate,
+        base_currency: &str,
+        target_currency: &str,
+        actor: &str,
+    ) -> Result<Option<f64>> {
+        let date_str = date.to_string();
+        let mut tx = self.pool.begin().await?;
+
+        let old_rate: Option<f64> = fetch_returning(
+            sqlx::query_as(
+                r#"
+                DELETE FROM manual_rates
+                WHERE date = ? AND base_currency = ? AND target_currency = ?
+                RETURNING rate
+                "#,
+            )
+            .bind(&date_str)
+            .bind(base_currency)
+            .bind(target_currency),
+            &mut *tx,
+        )
+        .await?
+        .map(|(rate,)| rate);
+
+        if old_rate.is_some() {
+            sqlx::query(
+                r#"
+                INSERT INTO manual_rates_audit
+                    (action, date, base_currency, target_currency, old_rate, actor)
+                VALUES ('delete', ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&date_str)
+            .bind(base_currency)
+            .bind(target_currency)
+            .bind(old_rate)
+            .bind(actor)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(old_rate)
+    }
+
+    /// Get the audit trail for manual rates in a date range, newest first
+    pub async fn get_manual_audit(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        limit: i64,
+    ) -> Result<Vec<ManualRateAudit>> {
+        let rows: Vec<ManualAuditRow> = sqlx::query_as(
+            r#"
+            SELECT id, action, date, base_currency, target_currency,
+                   old_rate, new_rate, note, actor, changed_at
+            FROM manual_rates_audit
+            WHERE date >= ? AND date <= ?
+            ORDER BY id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(ManualRateAudit {
+                    id: row.id,
+                    action: row.action,
+                    date: NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?,
+                    base_currency: row.base_currency,
+                    target_currency: row.target_currency,
+                    old_rate: row.old_rate,
+                    new_rate: row.new_rate,
+                    note: row.note,
+                    actor: row.actor,
+                    changed_at: row.changed_at,
+                })
+            })
+            .collect()
+    }
+}
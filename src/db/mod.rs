@@ -1,3 +1,130 @@
+mod api_keys;
+mod as_of;
+mod catalog;
+mod coverage;
+mod manual;
+mod quality;
+mod reconciliation;
 mod repository;
+mod revisions;
+mod sync;
 
-pub use repository::RatesRepository;
+pub use repository::{NearbyDates, RateStats, RatesRepository};
+pub use revisions::RevisionFilter;
+pub use sync::SyncHistoryFilter;
+
+use sqlx::query::QueryAs;
+use sqlx::sqlite::{Sqlite, SqliteArguments, SqliteRow};
+use sqlx::{Executor, FromRow};
+
+/// First row of an `INSERT`/`DELETE ... RETURNING` statement.
+///
+/// The statement is stepped to completion: `fetch_one` and `fetch_optional`
+/// stop after the first row, and an unfinished RETURNING statement keeps the
+/// write transaction open, blocking every other writer.
+async fn fetch_returning<'q, 'e, 'c: 'e, O, E>(
+    query: QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    executor: E,
+) -> sqlx::Result<Option<O>>
+where
+    'q: 'e,
+    O: Send + Unpin + for<'r> FromRow<'r, SqliteRow> + 'e,
+    E: 'e + Executor<'c, Database = Sqlite>,
+{
+    Ok(query.fetch_all(executor).await?.into_iter().next())
+}
+
+/// In-memory database for tests. A single connection, since every
+/// connection to `sqlite::memory:` opens its own empty database.
+#[cfg(test)]
+pub(crate) async fn test_pool() -> sqlx::SqlitePool {
+    sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
+
+/// Repository over an initialized [`test_pool`]
+#[cfg(test)]
+pub(crate) async fn test_repository() -> RatesRepository {
+    let repository = RatesRepository::new(test_pool().await);
+    repository.init().await.unwrap();
+    repository
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{QualityIssue, QualityReason, Scope};
+    use chrono::NaiveDate;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    /// Every connection of the pool can write at once: no statement left a
+    /// write transaction open (no busy timeout, a held lock fails at once)
+    async fn assert_writable(pool: &sqlx::SqlitePool) {
+        let mut a = pool.acquire().await.unwrap();
+        let mut b = pool.acquire().await.unwrap();
+        for conn in [&mut a, &mut b] {
+            sqlx::query("INSERT INTO sync_log (provider, records_count, status) VALUES ('test', 0, 'success')")
+                .execute(&mut **conn)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_returning_releases_write_lock() {
+        let path = std::env::temp_dir().join(format!("currency-rates-{}.db", uuid::Uuid::new_v4()));
+        let options =
+            SqliteConnectOptions::from_str(&format!("sqlite:{}?mode=rwc", path.display()))
+                .unwrap()
+                .busy_timeout(Duration::ZERO);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(2)
+            .connect_with(options)
+            .await
+            .unwrap();
+        let repository = RatesRepository::new(pool.clone());
+        repository.init().await.unwrap();
+
+        let issue = QualityIssue {
+            date: NaiveDate::from_ymd_opt(2025, 3, 5).unwrap(),
+            currency: "UAH".to_string(),
+            rate: 4160.0,
+            reason: QualityReason::Jump,
+            previous_rate: Some(41.6),
+            change: Some(99.0),
+        };
+        repository
+            .quarantine_rates("nbu", "USD", &[issue])
+            .await
+            .unwrap();
+        let id = repository.get_quarantined(None, 1).await.unwrap()[0].id;
+        assert!(repository.take_quarantined(id).await.unwrap().is_some());
+        assert_writable(&pool).await;
+
+        repository
+            .insert_api_key("partner", "hash", "cr_12345678", &[Scope::Read], None, None)
+            .await
+            .unwrap();
+        assert_writable(&pool).await;
+
+        let date = NaiveDate::from_ymd_opt(2025, 3, 5).unwrap();
+        repository
+            .upsert_manual_rate(date, "USD", "UAH", 41.7, None, "ops")
+            .await
+            .unwrap();
+        let deleted = repository
+            .delete_manual_rate(date, "USD", "UAH", "ops")
+            .await
+            .unwrap();
+        assert_eq!(deleted, Some(41.7));
+        assert_writable(&pool).await;
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&path);
+    }
+}
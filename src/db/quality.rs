@@ -0,0 +1,342 @@
+use chrono::{Duration, NaiveDate};
+use sqlx::{FromRow, Row};
+use std::collections::HashMap;
+
+use super::{RatesRepository, fetch_returning};
+use crate::error::{AppError, Result};
+use crate::models::{DailyRates, QualityIssue, QualityReason, QuarantinedRate};
+
+/// How far back the last accepted rate of a currency is looked up
+const PREVIOUS_RATE_LOOKBACK_DAYS: i64 = 31;
+
+/// Database row for a quarantined rate
+#[derive(Debug, FromRow)]
+struct QuarantineRow {
+    id: i64,
+    date: String,
+    base_currency: String,
+    target_currency: String,
+    provider: String,
+    rate: f64,
+    previous_rate: Option<f64>,
+    reason: String,
+    detected_at: String,
+}
+
+impl TryFrom<QuarantineRow> for QuarantinedRate {
+    type Error = AppError;
+
+    fn try_from(row: QuarantineRow) -> Result<Self> {
+        Ok(QuarantinedRate {
+            id: row.id,
+            date: NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?,
+            base_currency: row.base_currency,
+            target_currency: row.target_currency,
+            provider: row.provider,
+            rate: row.rate,
+            previous_rate: row.previous_rate,
+            reason: row.reason,
+            detected_at: row.detected_at,
+        })
+    }
+}
+
+impl RatesRepository {
+    /// Create the table holding suspicious provider rates
+    pub(super) async fn init_quarantine(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS quarantined_rates (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date TEXT NOT NULL,
+                base_currency TEXT NOT NULL,
+                target_currency TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                rate REAL NOT NULL,
+                previous_rate REAL,
+                reason TEXT NOT NULL,
+                detected_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(date, base_currency, target_currency, provider)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Last published rate of each currency of a provider before `before`,
+    /// looking back at most `PREVIOUS_RATE_LOOKBACK_DAYS`
+    pub async fn get_previous_rates(
+        &self,
+        provider: &str,
+        base_currency: &str,
+        before: NaiveDate,
+    ) -> Result<HashMap<String, f64>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT e.target_currency, e.rate
+            FROM exchange_rates e
+            WHERE e.provider = ? AND e.base_currency = ?
+              AND e.date = (
+                  SELECT MAX(p.date) FROM exchange_rates p
+                  WHERE p.provider = e.provider
+                    AND p.base_currency = e.base_currency
+                    AND p.target_currency = e.target_currency
+                    AND p.estimated = 0
+                    AND p.date < ? AND p.date >= ?
+              )
+            "#,
+        )
+        .bind(provider)
+        .bind(base_currency)
+        .bind(before.to_string())
+        .bind((before - Duration::days(PREVIOUS_RATE_LOOKBACK_DAYS)).to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get("target_currency"), row.get("rate")))
+            .collect())
+    }
+
+    /// Rates of a provider quarantined as jumps before `before` and after the
+    /// last published rate of their currency, oldest first: the start of a
+    /// level that later fetched rates may confirm
+    pub async fn get_pending_jumps(
+        &self,
+        provider: &str,
+        base_currency: &str,
+        before: NaiveDate,
+    ) -> Result<Vec<QuarantinedRate>> {
+        let rows: Vec<QuarantineRow> = sqlx::query_as(
+            r#"
+            SELECT q.id, q.date, q.base_currency, q.target_currency, q.provider,
+                   q.rate, q.previous_rate, q.reason, q.detected_at
+            FROM quarantined_rates q
+            WHERE q.provider = ?1 AND q.base_currency = ?2 AND q.reason = ?3
+              AND q.date < ?4 AND q.date >= ?5
+              AND q.date > COALESCE((
+                  SELECT MAX(e.date) FROM exchange_rates e
+                  WHERE e.provider = q.provider
+                    AND e.base_currency = q.base_currency
+                    AND e.target_currency = q.target_currency
+                    AND e.estimated = 0
+                    AND e.date < ?4
+              ), '')
+            ORDER BY q.date, q.target_currency
+            "#,
+        )
+        .bind(provider)
+        .bind(base_currency)
+        .bind(QualityReason::Jump.as_str())
+        .bind(before.to_string())
+        .bind((before - Duration::days(PREVIOUS_RATE_LOOKBACK_DAYS)).to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(QuarantinedRate::try_from).collect()
+    }
+
+    /// Quarantine rates that jumped. A cell quarantined again keeps one row
+    /// with the latest value.
+    pub async fn quarantine_rates(
+        &self,
+        provider: &str,
+        base_currency: &str,
+        issues: &[QualityIssue],
+    ) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let mut count = 0;
+
+        for issue in issues.iter().filter(|i| i.reason == QualityReason::Jump) {
+            sqlx::query(
+                r#"
+                INSERT INTO quarantined_rates
+                    (date, base_currency, target_currency, provider, rate, previous_rate, reason)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(date, base_currency, target_currency, provider) DO UPDATE SET
+                    rate = excluded.rate,
+                    previous_rate = excluded.previous_rate,
+                    reason = excluded.reason,
+                    detected_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(issue.date.to_string())
+            .bind(base_currency)
+            .bind(&issue.currency)
+            .bind(provider)
+            .bind(issue.rate)
+            .bind(issue.previous_rate)
+            .bind(issue.reason.as_str())
+            .execute(&mut *tx)
+            .await?;
+            count += 1;
+        }
+
+        tx.commit().await?;
+        Ok(count)
+    }
+
+    /// Drop quarantined rates of a provider superseded by an accepted rate
+    /// for the same cell
+    pub async fn resolve_quarantined(&self, provider: &str, rates: &[DailyRates]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query(
+            "SELECT id, date, base_currency, target_currency FROM quarantined_rates WHERE provider = ?",
+        )
+        .bind(provider)
+        .fetch_all(&mut *tx)
+        .await?;
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let accepted: HashMap<String, &DailyRates> =
+            rates.iter().map(|d| (d.date.to_string(), d)).collect();
+        let mut resolved = 0;
+        for row in rows {
+            let date: String = row.get("date");
+            let base: String = row.get("base_currency");
+            let target: String = row.get("target_currency");
+            let superseded = accepted
+                .get(&date)
+                .is_some_and(|d| d.base_currency == base && d.rates.contains_key(&target));
+            if superseded {
+                let id: i64 = row.get("id");
+                resolved += sqlx::query("DELETE FROM quarantined_rates WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            }
+        }
+
+        tx.commit().await?;
+        Ok(resolved)
+    }
+
+    /// Quarantined rates, optionally of one provider, newest date first
+    pub async fn get_quarantined(
+        &self,
+        provider: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<QuarantinedRate>> {
+        let rows: Vec<QuarantineRow> = sqlx::query_as(
+            r#"
+            SELECT id, date, base_currency, target_currency, provider,
+                   rate, previous_rate, reason, detected_at
+            FROM quarantined_rates
+            WHERE (? IS NULL OR provider = ?)
+            ORDER BY date DESC, target_currency
+            LIMIT ?
+            "#,
+        )
+        .bind(provider)
+        .bind(provider)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(QuarantinedRate::try_from).collect()
+    }
+
+    /// Number of quarantined rates per provider
+    pub async fn count_quarantined(&self) -> Result<HashMap<String, usize>> {
+        let rows = sqlx::query(
+            "SELECT provider, COUNT(*) AS count FROM quarantined_rates GROUP BY provider",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let count: i64 = row.get("count");
+                (row.get("provider"), count as usize)
+            })
+            .collect())
+    }
+
+    /// Remove a quarantined rate, returning it
+    pub async fn take_quarantined(&self, id: i64) -> Result<Option<QuarantinedRate>> {
+        let row: Option<QuarantineRow> = fetch_returning(
+            sqlx::query_as(
+                r#"
+                DELETE FROM quarantined_rates WHERE id = ?
+                RETURNING id, date, base_currency, target_currency, provider,
+                          rate, previous_rate, reason, detected_at
+                "#,
+            )
+            .bind(id),
+            &self.pool,
+        )
+        .await?;
+
+        row.map(QuarantinedRate::try_from).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_repository;
+
+    fn daily(day: u32, rate: f64) -> DailyRates {
+        DailyRates {
+            date: NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+            base_currency: "USD".to_string(),
+            rates: HashMap::from([("UAH".to_string(), rate)]),
+            provider: "nbu".to_string(),
+            estimated: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quarantine() {
+        let repository = test_repository().await;
+
+        repository
+            .store_daily_rates_batch(&[daily(3, 41.5), daily(4, 41.6)])
+            .await
+            .unwrap();
+        let previous = repository
+            .get_previous_rates("nbu", "USD", NaiveDate::from_ymd_opt(2025, 3, 5).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(previous["UAH"], 41.6);
+
+        let issue = QualityIssue {
+            date: NaiveDate::from_ymd_opt(2025, 3, 5).unwrap(),
+            currency: "UAH".to_string(),
+            rate: 4160.0,
+            reason: QualityReason::Jump,
+            previous_rate: Some(41.6),
+            change: Some(99.0),
+        };
+        for _ in 0..2 {
+            repository
+                .quarantine_rates("nbu", "USD", std::slice::from_ref(&issue))
+                .await
+                .unwrap();
+        }
+        assert_eq!(repository.count_quarantined().await.unwrap()["nbu"], 1);
+
+        // A later accepted rate for the same cell supersedes the quarantined one
+        let resolved = repository
+            .resolve_quarantined("nbu", &[daily(5, 41.7)])
+            .await
+            .unwrap();
+        assert_eq!(resolved, 1);
+        assert!(
+            repository
+                .get_quarantined(None, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::NaiveDate;
+use sqlx::FromRow;
+
+use super::RatesRepository;
+use crate::error::{AppError, Result};
+use crate::models::{DailyRates, ExchangeRate, RateDiscrepancy};
+use crate::providers::DERIVED_PROVIDER;
+
+/// Database row for a rate published by several providers
+#[derive(Debug, FromRow)]
+struct OverlapRow {
+    date: String,
+    base_currency: String,
+    target_currency: String,
+    rate: f64,
+    provider: String,
+}
+
+/// Database row for a stored provider comparison
+#[derive(Debug, FromRow)]
+struct DiscrepancyRow {
+    date: String,
+    currency: String,
+    provider_a: String,
+    rate_a: f64,
+    provider_b: String,
+    rate_b: f64,
+    diff: f64,
+}
+
+impl TryFrom<DiscrepancyRow> for RateDiscrepancy {
+    type Error = AppError;
+
+    fn try_from(row: DiscrepancyRow) -> Result<Self> {
+        Ok(RateDiscrepancy {
+            date: NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?,
+            currency: row.currency,
+            provider_a: row.provider_a,
+            rate_a: row.rate_a,
+            provider_b: row.provider_b,
+            rate_b: row.rate_b,
+            diff: row.diff,
+        })
+    }
+}
+
+impl RatesRepository {
+    /// Create the tables of reference rates and cross-provider comparisons
+    pub(super) async fn init_reconciliation(&self) -> Result<()> {
+        // Rates a provider fetches only to compare them with the provider
+        // serving the currency; never served
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS reference_rates (
+                date TEXT NOT NULL,
+                base_currency TEXT NOT NULL,
+                target_currency TEXT NOT NULL,
+                rate REAL NOT NULL,
+                provider TEXT NOT NULL,
+                PRIMARY KEY (date, base_currency, target_currency, provider)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS rate_discrepancies (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date TEXT NOT NULL,
+                currency TEXT NOT NULL,
+                provider_a TEXT NOT NULL,
+                rate_a REAL NOT NULL,
+                provider_b TEXT NOT NULL,
+                rate_b REAL NOT NULL,
+                diff REAL NOT NULL,
+                checked_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(date, currency, provider_a, provider_b)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store rates fetched for reconciliation only
+    pub async fn store_reference_rates(&self, rates: &[DailyRates]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for daily in rates {
+            for (currency, rate) in &daily.rates {
+                if currency == &daily.base_currency {
+                    continue;
+                }
+                sqlx::query(
+                    r#"
+                    INSERT OR REPLACE INTO reference_rates
+                        (date, base_currency, target_currency, rate, provider)
+                    VALUES (?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(daily.date.to_string())
+                .bind(&daily.base_currency)
+                .bind(currency)
+                .bind(rate)
+                .bind(&daily.provider)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Published and reference rates of `start..=end` for cells that more
+    /// than one provider publishes (derived and gap-filled rates are left out)
+    pub async fn get_overlapping_rates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<ExchangeRate>> {
+        let rows: Vec<OverlapRow> = sqlx::query_as(
+            r#"
+            WITH published AS (
+                SELECT date, base_currency, target_currency, rate, provider
+                FROM exchange_rates
+                WHERE date >= ?1 AND date <= ?2 AND provider != ?3 AND estimated = 0
+                UNION ALL
+                SELECT date, base_currency, target_currency, rate, provider
+                FROM reference_rates
+                WHERE date >= ?1 AND date <= ?2
+            )
+            SELECT e.date, e.base_currency, e.target_currency, e.rate, e.provider
+            FROM published e
+            WHERE EXISTS (
+                SELECT 1 FROM published o
+                WHERE o.date = e.date
+                  AND o.base_currency = e.base_currency
+                  AND o.target_currency = e.target_currency
+                  AND o.provider != e.provider
+            )
+            ORDER BY e.date, e.target_currency, e.provider
+            "#,
+        )
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .bind(DERIVED_PROVIDER)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(ExchangeRate {
+                    date: NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?,
+                    base_currency: row.base_currency,
+                    target_currency: row.target_currency,
+                    rate: row.rate,
+                    provider: row.provider,
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the stored comparisons of `start..=end`
+    pub async fn store_discrepancies(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        discrepancies: &[RateDiscrepancy],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM rate_discrepancies WHERE date >= ? AND date <= ?")
+            .bind(start.to_string())
+            .bind(end.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        for d in discrepancies {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO rate_discrepancies
+                    (date, currency, provider_a, rate_a, provider_b, rate_b, diff)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(d.date.to_string())
+            .bind(&d.currency)
+            .bind(&d.provider_a)
+            .bind(d.rate_a)
+            .bind(&d.provider_b)
+            .bind(d.rate_b)
+            .bind(d.diff)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stored comparisons of `start..=end`, optionally for one currency
+    pub async fn get_discrepancies(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        currency: Option<&str>,
+    ) -> Result<Vec<RateDiscrepancy>> {
+        let rows: Vec<DiscrepancyRow> = sqlx::query_as(
+            r#"
+            SELECT date, currency, provider_a, rate_a, provider_b, rate_b, diff
+            FROM rate_discrepancies
+            WHERE date >= ? AND date <= ? AND (? IS NULL OR currency = ?)
+            ORDER BY currency, provider_a, provider_b, date
+            "#,
+        )
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .bind(currency)
+        .bind(currency)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(RateDiscrepancy::try_from).collect()
+    }
+}
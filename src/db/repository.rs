@@ -1,9 +1,32 @@
 use chrono::NaiveDate;
 use sqlx::{FromRow, Row, sqlite::SqlitePool};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use super::revisions::UPSERT_RATE_SQL;
 use crate::error::Result;
 use crate::models::{CurrencyInfo, DailyRates, ExchangeRate};
+use crate::providers::DERIVED_PROVIDER;
+
+/// Summary of the rates stored for a provider
+#[derive(Debug, Clone, Default)]
+pub struct RateStats {
+    pub rows: i64,
+    pub currencies: i64,
+    /// First and last published dates (gap-filled rows are left out)
+    pub first_date: Option<NaiveDate>,
+    pub last_date: Option<NaiveDate>,
+}
+
+/// Dates with rates around a requested range
+#[derive(Debug, Clone, Default)]
+pub struct NearbyDates {
+    /// Last date with rates before the range
+    pub before: Option<NaiveDate>,
+    /// First date with rates after the range
+    pub after: Option<NaiveDate>,
+    pub first_date: Option<NaiveDate>,
+    pub last_date: Option<NaiveDate>,
+}
 
 /// Database row for exchange rates
 #[derive(Debug, FromRow)]
@@ -26,10 +49,22 @@ struct CurrencyWithDatesRow {
     max_date: Option<String>,
 }
 
+/// `ORDER BY` terms listing the rates of one cell from least to most
+/// preferred, so the last one wins: gap-filled rows, derived rows, providers
+/// missing from the priority list, then listed providers from last to first.
+/// Binds the derived provider name, then the list (see [`priority_json`]) twice.
+pub(super) const PREFERENCE_ORDER: &str = "estimated DESC, (provider = ?) DESC, \
+    COALESCE((SELECT json_array_length(?) - p.key FROM json_each(?) p WHERE p.value = provider), 0)";
+
+/// Provider priority list, most preferred first, as bound to [`PREFERENCE_ORDER`]
+pub(super) fn priority_json(priority: &[String]) -> String {
+    serde_json::json!(priority).to_string()
+}
+
 /// Repository for exchange rate data
 #[derive(Clone)]
 pub struct RatesRepository {
-    pool: SqlitePool,
+    pub(super) pool: SqlitePool,
 }
 
 impl RatesRepository {
@@ -93,38 +128,28 @@ impl RatesRepository {
         .execute(&self.pool)
         .await?;
 
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS sync_log (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                provider TEXT NOT NULL,
-                synced_at TEXT DEFAULT CURRENT_TIMESTAMP,
-                records_count INTEGER,
-                status TEXT
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
+        self.init_sync_log().await?;
+        self.init_catalog().await?;
+        self.init_manual().await?;
+        self.init_revisions().await?;
+        self.init_quarantine().await?;
+        self.init_reconciliation().await?;
+        self.init_api_keys().await?;
 
         Ok(())
     }
 
     /// Store a single exchange rate
     pub async fn store_rate(&self, rate: &ExchangeRate) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT OR REPLACE INTO exchange_rates (date, base_currency, target_currency, rate, provider)
-            VALUES (?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(rate.date.to_string())
-        .bind(&rate.base_currency)
-        .bind(&rate.target_currency)
-        .bind(rate.rate)
-        .bind(&rate.provider)
-        .execute(&self.pool)
-        .await?;
+        sqlx::query(UPSERT_RATE_SQL)
+            .bind(rate.date.to_string())
+            .bind(&rate.base_currency)
+            .bind(&rate.target_currency)
+            .bind(rate.rate)
+            .bind(&rate.provider)
+            .bind(false)
+            .execute(&self.pool)
+            .await?;
 
         Ok(())
     }
@@ -136,15 +161,15 @@ impl RatesRepository {
                 continue; // Skip base currency (rate would be 1.0)
             }
 
-            let exchange_rate = ExchangeRate {
-                date: daily.date,
-                base_currency: daily.base_currency.clone(),
-                target_currency: currency.clone(),
-                rate: *rate,
-                provider: daily.provider.clone(),
-            };
-
-            self.store_rate(&exchange_rate).await?;
+            sqlx::query(UPSERT_RATE_SQL)
+                .bind(daily.date.to_string())
+                .bind(&daily.base_currency)
+                .bind(currency)
+                .bind(rate)
+                .bind(&daily.provider)
+                .bind(daily.estimated)
+                .execute(&self.pool)
+                .await?;
         }
 
         Ok(())
@@ -163,19 +188,15 @@ impl RatesRepository {
                     continue; // Skip base currency (rate would be 1.0)
                 }
 
-                sqlx::query(
-                    r#"
-                    INSERT OR REPLACE INTO exchange_rates (date, base_currency, target_currency, rate, provider)
-                    VALUES (?, ?, ?, ?, ?)
-                    "#,
-                )
-                .bind(daily.date.to_string())
-                .bind(&daily.base_currency)
-                .bind(currency)
-                .bind(rate)
-                .bind(&daily.provider)
-                .execute(&mut *tx)
-                .await?;
+                sqlx::query(UPSERT_RATE_SQL)
+                    .bind(daily.date.to_string())
+                    .bind(&daily.base_currency)
+                    .bind(currency)
+                    .bind(rate)
+                    .bind(&daily.provider)
+                    .bind(daily.estimated)
+                    .execute(&mut *tx)
+                    .await?;
 
                 count += 1;
             }
@@ -215,12 +236,29 @@ impl RatesRepository {
         Ok(None)
     }
 
-    /// Get rates for a specific date
+    /// Latest date a provider published rates for (gap-filled rows are left out)
+    pub async fn get_latest_published_date(&self, provider: &str) -> Result<Option<NaiveDate>> {
+        let date: Option<String> = sqlx::query_scalar(
+            "SELECT MAX(date) FROM exchange_rates WHERE provider = ? AND estimated = 0",
+        )
+        .bind(provider)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(date
+            .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+            .transpose()?)
+    }
+
+    /// Get rates for a specific date, from one provider or from all of them.
+    /// A currency published by several providers gets the rate of the first
+    /// in `priority` (provider names, most preferred first).
     pub async fn get_rates_for_date(
         &self,
         date: NaiveDate,
         base_currency: &str,
         provider: Option<&str>,
+        priority: &[String],
     ) -> Result<HashMap<String, f64>> {
         let date_str = date.to_string();
 
@@ -240,20 +278,27 @@ impl RatesRepository {
                 .await?
             }
             None => {
-                sqlx::query_as(
+                let priority = priority_json(priority);
+                sqlx::query_as(&format!(
                     r#"
                     SELECT id, date, base_currency, target_currency, rate, provider
                     FROM exchange_rates
                     WHERE date = ? AND base_currency = ?
+                    ORDER BY {}
                     "#,
-                )
+                    PREFERENCE_ORDER
+                ))
                 .bind(&date_str)
                 .bind(base_currency)
+                .bind(DERIVED_PROVIDER)
+                .bind(&priority)
+                .bind(&priority)
                 .fetch_all(&self.pool)
                 .await?
             }
         };
 
+        // The preferred row of each currency comes last
         let mut rates: HashMap<String, f64> = HashMap::new();
         for row in rows {
             rates.insert(row.target_currency, row.rate);
@@ -262,13 +307,15 @@ impl RatesRepository {
         Ok(rates)
     }
 
-    /// Get rates for a date range
+    /// Get rates for a date range, preferring providers like
+    /// [`Self::get_rates_for_date`]
     pub async fn get_rates_for_range(
         &self,
         start: NaiveDate,
         end: NaiveDate,
         base_currency: &str,
         provider: Option<&str>,
+        priority: &[String],
     ) -> Result<HashMap<NaiveDate, HashMap<String, f64>>> {
         let start_str = start.to_string();
         let end_str = end.to_string();
@@ -291,17 +338,22 @@ impl RatesRepository {
                 .await?
             }
             None => {
-                sqlx::query_as(
+                let priority = priority_json(priority);
+                sqlx::query_as(&format!(
                     r#"
                     SELECT id, date, base_currency, target_currency, rate, provider
                     FROM exchange_rates
                     WHERE date >= ? AND date <= ? AND base_currency = ?
-                    ORDER BY date
+                    ORDER BY date, {}
                     "#,
-                )
+                    PREFERENCE_ORDER
+                ))
                 .bind(&start_str)
                 .bind(&end_str)
                 .bind(base_currency)
+                .bind(DERIVED_PROVIDER)
+                .bind(&priority)
+                .bind(&priority)
                 .fetch_all(&self.pool)
                 .await?
             }
@@ -320,6 +372,81 @@ impl RatesRepository {
         Ok(results)
     }
 
+    /// Get rates for a date range from every provider except one
+    pub async fn get_rates_for_range_excluding(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        base_currency: &str,
+        excluded_provider: &str,
+    ) -> Result<HashMap<NaiveDate, HashMap<String, f64>>> {
+        let rows: Vec<RateRow> = sqlx::query_as(
+            r#"
+            SELECT id, date, base_currency, target_currency, rate, provider
+            FROM exchange_rates
+            WHERE date >= ? AND date <= ? AND base_currency = ? AND provider != ?
+            ORDER BY date, estimated DESC
+            "#,
+        )
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .bind(base_currency)
+        .bind(excluded_provider)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut results: HashMap<NaiveDate, HashMap<String, f64>> = HashMap::new();
+
+        for row in rows {
+            let date = NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?;
+            results
+                .entry(date)
+                .or_default()
+                .insert(row.target_currency, row.rate);
+        }
+
+        Ok(results)
+    }
+
+    /// Get currencies whose only rate on a date comes from derived pegs
+    pub async fn get_derived_currencies(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<HashMap<NaiveDate, HashSet<String>>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT er.date, er.target_currency
+            FROM exchange_rates er
+            WHERE er.provider = ? AND er.date >= ? AND er.date <= ?
+              AND NOT EXISTS (
+                  SELECT 1 FROM exchange_rates o
+                  WHERE o.date = er.date
+                    AND o.base_currency = er.base_currency
+                    AND o.target_currency = er.target_currency
+                    AND o.provider != er.provider
+              )
+            "#,
+        )
+        .bind(DERIVED_PROVIDER)
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut results: HashMap<NaiveDate, HashSet<String>> = HashMap::new();
+        for row in rows {
+            let date: String = row.get("date");
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+            results
+                .entry(date)
+                .or_default()
+                .insert(row.get("target_currency"));
+        }
+
+        Ok(results)
+    }
+
     /// Get all available currencies from exchange_rates (source of truth)
     pub async fn get_currencies(
         &self,
@@ -405,38 +532,108 @@ impl RatesRepository {
         Ok(())
     }
 
-    /// Log a sync operation
-    pub async fn log_sync(&self, provider: &str, records_count: usize, status: &str) -> Result<()> {
-        sqlx::query(
+    /// Run a trivial query to check that the database answers
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Latest published rate date of each provider (gap-filled rows are left out)
+    pub async fn get_latest_dates_by_provider(&self) -> Result<HashMap<String, NaiveDate>> {
+        let rows = sqlx::query(
+            "SELECT provider, MAX(date) AS date FROM exchange_rates WHERE estimated = 0 GROUP BY provider",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let date: String = row.get("date");
+                Ok((
+                    row.get("provider"),
+                    NaiveDate::parse_from_str(&date, "%Y-%m-%d")?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Connections in the pool, idle connections and the pool's maximum size
+    pub fn pool_stats(&self) -> (usize, usize, u32) {
+        (
+            self.pool.size() as usize,
+            self.pool.num_idle(),
+            self.pool.options().get_max_connections(),
+        )
+    }
+
+    /// Stored rows, distinct currencies and date range of a provider
+    pub async fn get_rate_stats(&self, provider: &str) -> Result<RateStats> {
+        let row = sqlx::query(
             r#"
-            INSERT INTO sync_log (provider, records_count, status)
-            VALUES (?, ?, ?)
+            SELECT COUNT(*) AS rows_count,
+                   COUNT(DISTINCT target_currency) AS currencies_count,
+                   MIN(CASE WHEN estimated = 0 THEN date END) AS min_date,
+                   MAX(CASE WHEN estimated = 0 THEN date END) AS max_date
+            FROM exchange_rates
+            WHERE provider = ?
             "#,
         )
         .bind(provider)
-        .bind(records_count as i64)
-        .bind(status)
-        .execute(&self.pool)
+        .fetch_one(&self.pool)
         .await?;
 
-        Ok(())
+        let date = |column: &str| -> Result<Option<NaiveDate>> {
+            let value: Option<String> = row.get(column);
+            Ok(value
+                .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+                .transpose()?)
+        };
+
+        Ok(RateStats {
+            rows: row.get("rows_count"),
+            currencies: row.get("currencies_count"),
+            first_date: date("min_date")?,
+            last_date: date("max_date")?,
+        })
     }
 
-    /// Get last sync time for a provider
-    pub async fn get_last_sync(&self, provider: &str) -> Result<Option<String>> {
+    /// Dates with `base_currency` rates closest to `start..=end`, and the
+    /// first and last of all
+    pub async fn get_nearby_dates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        base_currency: &str,
+    ) -> Result<NearbyDates> {
         let row = sqlx::query(
             r#"
-            SELECT synced_at FROM sync_log
-            WHERE provider = ? AND status = 'success'
-            ORDER BY synced_at DESC
-            LIMIT 1
+            SELECT (SELECT MAX(date) FROM exchange_rates
+                    WHERE base_currency = ?1 AND date < ?2) AS before_date,
+                   (SELECT MIN(date) FROM exchange_rates
+                    WHERE base_currency = ?1 AND date > ?3) AS after_date,
+                   (SELECT MIN(date) FROM exchange_rates WHERE base_currency = ?1) AS min_date,
+                   (SELECT MAX(date) FROM exchange_rates WHERE base_currency = ?1) AS max_date
             "#,
         )
-        .bind(provider)
-        .fetch_optional(&self.pool)
+        .bind(base_currency)
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .fetch_one(&self.pool)
         .await?;
 
-        Ok(row.map(|r| r.get("synced_at")))
+        let date = |column: &str| -> Result<Option<NaiveDate>> {
+            let value: Option<String> = row.get(column);
+            Ok(value
+                .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+                .transpose()?)
+        };
+
+        Ok(NearbyDates {
+            before: date("before_date")?,
+            after: date("after_date")?,
+            first_date: date("min_date")?,
+            last_date: date("max_date")?,
+        })
     }
 
     /// Get count of rates per provider
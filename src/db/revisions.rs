@@ -0,0 +1,267 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite};
+use std::collections::HashSet;
+
+use super::RatesRepository;
+use super::as_of::sql_timestamp;
+use crate::error::{AppError, Result};
+use crate::models::RateRevision;
+
+/// Upsert of a single rate. Values within rounding noise of the stored one are
+/// left alone, and a gap-filled estimate never replaces a published rate.
+pub(super) const UPSERT_RATE_SQL: &str = r#"
+    INSERT INTO exchange_rates (date, base_currency, target_currency, rate, provider, estimated)
+    VALUES (?, ?, ?, ?, ?, ?)
+    ON CONFLICT(date, base_currency, target_currency, provider) DO UPDATE SET
+        rate = excluded.rate,
+        estimated = excluded.estimated,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE NOT (excluded.estimated = 1 AND exchange_rates.estimated = 0)
+      AND (abs(exchange_rates.rate - excluded.rate) > 1e-9 * abs(exchange_rates.rate)
+           OR exchange_rates.estimated != excluded.estimated)
+"#;
+
+/// Columns added to `exchange_rates` after the first release
+const RATE_COLUMNS: &[(&str, &str)] = &[
+    ("estimated", "INTEGER NOT NULL DEFAULT 0"),
+    ("updated_at", "TEXT"),
+];
+
+/// Database row for a rate revision
+#[derive(Debug, FromRow)]
+struct RevisionRow {
+    id: i64,
+    date: String,
+    base_currency: String,
+    target_currency: String,
+    provider: String,
+    old_rate: f64,
+    new_rate: f64,
+    old_estimated: bool,
+    revised_at: String,
+}
+
+impl TryFrom<RevisionRow> for RateRevision {
+    type Error = AppError;
+
+    fn try_from(row: RevisionRow) -> Result<Self> {
+        Ok(RateRevision {
+            id: row.id,
+            date: NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")?,
+            base_currency: row.base_currency,
+            target_currency: row.target_currency,
+            provider: row.provider,
+            old_rate: row.old_rate,
+            new_rate: row.new_rate,
+            replaced_estimate: row.old_estimated,
+            revised_at: row.revised_at,
+        })
+    }
+}
+
+/// Filters for listing revisions
+#[derive(Debug, Clone, Default)]
+pub struct RevisionFilter {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    /// Stored target currencies (USD-based legs) to include
+    pub currencies: Vec<String>,
+    pub provider: Option<String>,
+    /// Also list estimates replaced by the published rate
+    pub include_estimates: bool,
+}
+
+impl RatesRepository {
+    /// Create the revision history and the trigger recording every changed rate
+    pub(super) async fn init_revisions(&self) -> Result<()> {
+        let existing: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info('exchange_rates')")
+                .fetch_all(&self.pool)
+                .await?;
+        for (column, kind) in RATE_COLUMNS {
+            if !existing.iter().any(|c| c == column) {
+                sqlx::query(&format!(
+                    "ALTER TABLE exchange_rates ADD COLUMN {} {}",
+                    column, kind
+                ))
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS rate_revisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date TEXT NOT NULL,
+                base_currency TEXT NOT NULL,
+                target_currency TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                old_rate REAL NOT NULL,
+                new_rate REAL NOT NULL,
+                old_estimated INTEGER NOT NULL DEFAULT 0,
+                revised_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_revisions_cell
+            ON rate_revisions(date, target_currency, provider)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Estimates replaced by the published rate are kept too (flagged), so
+        // the history can reproduce what was served at any point in time
+        sqlx::query(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS trg_exchange_rates_revision
+            AFTER UPDATE OF rate ON exchange_rates
+            WHEN OLD.rate != NEW.rate
+            BEGIN
+                INSERT INTO rate_revisions
+                    (date, base_currency, target_currency, provider, old_rate, new_rate, old_estimated)
+                VALUES
+                    (OLD.date, OLD.base_currency, OLD.target_currency, OLD.provider,
+                     OLD.rate, NEW.rate, OLD.estimated);
+            END
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// List revisions, oldest first
+    pub async fn get_revisions(
+        &self,
+        filter: &RevisionFilter,
+        limit: i64,
+    ) -> Result<Vec<RateRevision>> {
+        let mut query: QueryBuilder<Sqlite> = QueryBuilder::new(
+            r#"
+            SELECT id, date, base_currency, target_currency, provider,
+                   old_rate, new_rate, old_estimated, revised_at
+            FROM rate_revisions
+            WHERE 1 = 1
+            "#,
+        );
+        if let Some(start) = filter.start {
+            query.push(" AND date >= ").push_bind(start.to_string());
+        }
+        if let Some(end) = filter.end {
+            query.push(" AND date <= ").push_bind(end.to_string());
+        }
+        if !filter.currencies.is_empty() {
+            query.push(" AND target_currency IN (");
+            let mut separated = query.separated(", ");
+            for code in &filter.currencies {
+                separated.push_bind(code);
+            }
+            query.push(")");
+        }
+        if let Some(provider) = &filter.provider {
+            query.push(" AND provider = ").push_bind(provider);
+        }
+        if !filter.include_estimates {
+            query.push(" AND old_estimated = 0");
+        }
+        query
+            .push(" ORDER BY date, target_currency, id LIMIT ")
+            .push_bind(limit);
+
+        let rows: Vec<RevisionRow> = query.build_query_as().fetch_all(&self.pool).await?;
+        rows.into_iter().map(RateRevision::try_from).collect()
+    }
+
+    /// Stored currencies with an upstream revision on any date of `start..=end`,
+    /// counting only revisions made by `as_of` when given
+    pub async fn get_revised_currencies(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        as_of: Option<DateTime<Utc>>,
+    ) -> Result<HashSet<String>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT target_currency FROM rate_revisions
+            WHERE date >= ? AND date <= ? AND old_estimated = 0
+              AND (? IS NULL OR revised_at <= ?)
+            "#,
+        )
+        .bind(start.to_string())
+        .bind(end.to_string())
+        .bind(as_of.map(sql_timestamp))
+        .bind(as_of.map(sql_timestamp))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.get("target_currency")).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_repository;
+    use crate::models::DailyRates;
+    use std::collections::HashMap;
+
+    fn daily(rate: f64, estimated: bool) -> DailyRates {
+        DailyRates {
+            date: NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            base_currency: "USD".to_string(),
+            rates: HashMap::from([("EUR".to_string(), rate)]),
+            provider: "ecb".to_string(),
+            estimated,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_revisions() {
+        let repository = test_repository().await;
+
+        // Estimate replaced by the published rate, then an upstream correction
+        for rates in [
+            daily(0.90, true),
+            daily(0.91, false),
+            daily(0.88, true),
+            daily(0.91 + 1e-12, false),
+            daily(0.92, false),
+        ] {
+            repository.store_daily_rates_batch(&[rates]).await.unwrap();
+        }
+
+        let stored = repository
+            .get_rates_for_date(
+                NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+                "USD",
+                None,
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(stored["EUR"], 0.92);
+
+        let revisions = repository
+            .get_revisions(&RevisionFilter::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!((revisions[0].old_rate, revisions[0].new_rate), (0.91, 0.92));
+
+        let all = RevisionFilter {
+            include_estimates: true,
+            ..Default::default()
+        };
+        let revisions = repository.get_revisions(&all, 10).await.unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert!(revisions[0].replaced_estimate);
+    }
+}
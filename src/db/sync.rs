@@ -0,0 +1,333 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite};
+
+use super::RatesRepository;
+use crate::error::Result;
+use crate::models::{ProviderSyncProgress, SyncFailure, SyncLogEntry};
+
+/// Parse a `CURRENT_TIMESTAMP` value (UTC)
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Database row for the sync log
+#[derive(Debug, FromRow)]
+struct SyncLogRow {
+    id: i64,
+    job_id: Option<String>,
+    provider: String,
+    status: Option<String>,
+    records_count: Option<i64>,
+    missing: Option<String>,
+    error: Option<String>,
+    started_at: Option<String>,
+    synced_at: String,
+    duration_ms: Option<i64>,
+}
+
+impl From<SyncLogRow> for SyncLogEntry {
+    fn from(row: SyncLogRow) -> Self {
+        SyncLogEntry {
+            id: row.id,
+            job_id: row.job_id,
+            provider: row.provider,
+            status: row.status.unwrap_or_default(),
+            records: row.records_count.unwrap_or_default(),
+            missing: row
+                .missing
+                .map(|m| {
+                    m.split(',')
+                        .filter(|c| !c.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            error: row.error,
+            started_at: row.started_at,
+            synced_at: row.synced_at,
+            duration_ms: row.duration_ms,
+        }
+    }
+}
+
+/// Filters for paging through the sync log
+#[derive(Debug, Clone, Default)]
+pub struct SyncHistoryFilter {
+    pub provider: Option<String>,
+    pub status: Option<String>,
+    pub job_id: Option<String>,
+}
+
+/// Columns added to `sync_log` after the first release
+const SYNC_LOG_COLUMNS: &[(&str, &str)] = &[
+    ("job_id", "TEXT"),
+    ("started_at", "TEXT"),
+    ("duration_ms", "INTEGER"),
+    ("missing", "TEXT"),
+    ("error", "TEXT"),
+];
+
+impl RatesRepository {
+    /// Create the sync log, upgrading older databases to the structured columns
+    pub(super) async fn init_sync_log(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sync_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                provider TEXT NOT NULL,
+                synced_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                records_count INTEGER,
+                status TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let existing: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info('sync_log')")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut upgraded = false;
+        for (column, kind) in SYNC_LOG_COLUMNS {
+            if !existing.iter().any(|c| c == column) {
+                sqlx::query(&format!(
+                    "ALTER TABLE sync_log ADD COLUMN {} {}",
+                    column, kind
+                ))
+                .execute(&self.pool)
+                .await?;
+                upgraded = true;
+            }
+        }
+
+        if upgraded {
+            // Older rows kept details in a free-text status ("error: ...", "partial: missing A,B")
+            sqlx::query(
+                r#"
+                UPDATE sync_log SET
+                    error = CASE
+                        WHEN status LIKE 'error: %' THEN substr(status, 8)
+                        WHEN status LIKE 'retry_later: %' THEN substr(status, 14)
+                        ELSE error END,
+                    missing = CASE
+                        WHEN status LIKE 'partial: missing %' THEN substr(status, 18)
+                        ELSE missing END,
+                    status = CASE
+                        WHEN status LIKE 'error: %' THEN 'error'
+                        WHEN status LIKE 'retry_later: %' THEN 'retry_later'
+                        WHEN status LIKE 'partial: %' THEN 'partial'
+                        ELSE status END
+                "#,
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_sync_log_provider ON sync_log(provider, synced_at)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Log a sync operation
+    pub async fn log_sync(&self, provider: &str, records_count: usize, status: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sync_log (provider, records_count, status)
+            VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(provider)
+        .bind(records_count as i64)
+        .bind(status)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Log a finished provider run of a sync job
+    pub async fn log_sync_run(&self, job_id: &str, run: &ProviderSyncProgress) -> Result<()> {
+        let timestamp =
+            |t: chrono::DateTime<chrono::Utc>| t.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO sync_log
+                (job_id, provider, status, records_count, missing, error, started_at, synced_at, duration_ms)
+            VALUES (?, ?, ?, ?, ?, ?, ?, COALESCE(?, CURRENT_TIMESTAMP), ?)
+            "#,
+        )
+        .bind(job_id)
+        .bind(&run.provider)
+        .bind(run.status.as_str())
+        .bind(run.records as i64)
+        .bind((!run.missing.is_empty()).then(|| run.missing.join(",")))
+        .bind(&run.error)
+        .bind(run.started_at.map(timestamp))
+        .bind(run.finished_at.map(timestamp))
+        .bind(run.duration_ms)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Page through the sync log, newest first. Returns the entries and the total count.
+    pub async fn get_sync_history(
+        &self,
+        filter: &SyncHistoryFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<SyncLogEntry>, i64)> {
+        fn push_filters<'a>(query: &mut QueryBuilder<'a, Sqlite>, filter: &'a SyncHistoryFilter) {
+            query.push(" WHERE 1 = 1");
+            if let Some(provider) = &filter.provider {
+                query.push(" AND provider = ").push_bind(provider);
+            }
+            if let Some(status) = &filter.status {
+                query.push(" AND status = ").push_bind(status);
+            }
+            if let Some(job_id) = &filter.job_id {
+                query.push(" AND job_id = ").push_bind(job_id);
+            }
+        }
+
+        let mut count = QueryBuilder::new("SELECT COUNT(*) AS count FROM sync_log");
+        push_filters(&mut count, filter);
+        let total: i64 = count.build().fetch_one(&self.pool).await?.get("count");
+
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT id, job_id, provider, status, records_count, missing, error,
+                   started_at, synced_at, duration_ms
+            FROM sync_log
+            "#,
+        );
+        push_filters(&mut query, filter);
+        query
+            .push(" ORDER BY synced_at DESC, id DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let rows: Vec<SyncLogRow> = query.build_query_as().fetch_all(&self.pool).await?;
+
+        Ok((rows.into_iter().map(SyncLogEntry::from).collect(), total))
+    }
+
+    /// Get last successful sync time for a provider
+    pub async fn get_last_sync(&self, provider: &str) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query(
+            r#"
+            SELECT synced_at FROM sync_log
+            WHERE provider = ? AND status = 'success'
+            ORDER BY synced_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(provider)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.and_then(|r| parse_timestamp(r.get("synced_at"))))
+    }
+
+    /// Get the last sync of a provider that did not succeed
+    pub async fn get_last_failure(&self, provider: &str) -> Result<Option<SyncFailure>> {
+        let row: Option<SyncLogRow> = sqlx::query_as(
+            r#"
+            SELECT id, job_id, provider, status, records_count, missing, error,
+                   started_at, synced_at, duration_ms
+            FROM sync_log
+            WHERE provider = ? AND status IN ('partial', 'retry_later', 'error')
+            ORDER BY synced_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(provider)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(SyncLogEntry::from).and_then(|entry| {
+            Some(SyncFailure {
+                at: parse_timestamp(&entry.synced_at)?,
+                status: entry.status,
+                error: entry.error,
+                missing: entry.missing,
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_pool;
+    use crate::models::SyncStatus;
+
+    #[tokio::test]
+    async fn test_upgrades_free_text_status() {
+        let repository = RatesRepository::new(test_pool().await);
+
+        // Sync log as created by older releases
+        sqlx::query(
+            "CREATE TABLE sync_log (id INTEGER PRIMARY KEY AUTOINCREMENT, provider TEXT NOT NULL, \
+             synced_at TEXT DEFAULT CURRENT_TIMESTAMP, records_count INTEGER, status TEXT)",
+        )
+        .execute(&repository.pool)
+        .await
+        .unwrap();
+        repository
+            .log_sync("nbu", 10, "partial: missing KZT,MDL")
+            .await
+            .unwrap();
+        repository
+            .log_sync("ecb", 0, "error: connection refused")
+            .await
+            .unwrap();
+
+        repository.init().await.unwrap();
+
+        let mut run = ProviderSyncProgress::queued("ecb");
+        run.status = SyncStatus::Success;
+        run.records = 5;
+        repository.log_sync_run("job-1", &run).await.unwrap();
+
+        let (entries, total) = repository
+            .get_sync_history(&SyncHistoryFilter::default(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(total, 3);
+
+        let partial = entries.iter().find(|e| e.provider == "nbu").unwrap();
+        assert_eq!(partial.status, "partial");
+        assert_eq!(partial.missing, vec!["KZT", "MDL"]);
+
+        let filter = SyncHistoryFilter {
+            provider: Some("ecb".to_string()),
+            status: Some("error".to_string()),
+            ..Default::default()
+        };
+        let (entries, total) = repository.get_sync_history(&filter, 10, 0).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(entries[0].error.as_deref(), Some("connection refused"));
+
+        assert!(repository.get_last_sync("ecb").await.unwrap().is_some());
+        assert!(repository.get_last_sync("nbu").await.unwrap().is_none());
+        let failure = repository.get_last_failure("ecb").await.unwrap().unwrap();
+        assert_eq!(failure.status, "error");
+        assert_eq!(failure.error.as_deref(), Some("connection refused"));
+        let failure = repository.get_last_failure("nbu").await.unwrap().unwrap();
+        assert_eq!(failure.missing, vec!["KZT", "MDL"]);
+    }
+}
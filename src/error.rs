@@ -1,9 +1,194 @@
 use axum::{
     Json,
-    http::StatusCode,
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::{HeaderValue, StatusCode, header::CONTENT_TYPE},
     response::{IntoResponse, Response},
 };
-use serde_json::json;
+use chrono::NaiveDate;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::models::DailyRates;
+
+/// Stable, machine-readable error code (`code` of problem responses)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A query or path parameter or the body is malformed
+    InvalidParameter,
+    /// A date or date range is malformed
+    InvalidDate,
+    /// The currency code is well-formed but no provider publishes it
+    UnknownCurrency,
+    /// The currency has no rates on the requested dates
+    CurrencyUnavailable,
+    /// No rates are stored for the requested dates
+    NoData,
+    NotFound,
+    Conflict,
+    Unauthorized,
+    Forbidden,
+    RateLimited,
+    ProviderError,
+    ProviderUnavailable,
+    PartialData,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidParameter => "invalid_parameter",
+            ErrorCode::InvalidDate => "invalid_date",
+            ErrorCode::UnknownCurrency => "unknown_currency",
+            ErrorCode::CurrencyUnavailable => "currency_unavailable",
+            ErrorCode::NoData => "no_data",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::Unauthorized => "unauthorized",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::ProviderError => "provider_error",
+            ErrorCode::ProviderUnavailable => "provider_unavailable",
+            ErrorCode::PartialData => "partial_data",
+            ErrorCode::InternalError => "internal_error",
+        }
+    }
+
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidParameter | ErrorCode::InvalidDate | ErrorCode::UnknownCurrency => {
+                StatusCode::BAD_REQUEST
+            }
+            ErrorCode::CurrencyUnavailable | ErrorCode::NoData | ErrorCode::NotFound => {
+                StatusCode::NOT_FOUND
+            }
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::ProviderError | ErrorCode::PartialData => StatusCode::BAD_GATEWAY,
+            ErrorCode::ProviderUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Short summary, the same for every occurrence (`title`)
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidParameter => "Invalid parameter",
+            ErrorCode::InvalidDate => "Invalid date",
+            ErrorCode::UnknownCurrency => "Unknown currency",
+            ErrorCode::CurrencyUnavailable => "Currency not available",
+            ErrorCode::NoData => "No data available",
+            ErrorCode::NotFound => "Not found",
+            ErrorCode::Conflict => "Conflict",
+            ErrorCode::Unauthorized => "Unauthorized",
+            ErrorCode::Forbidden => "Forbidden",
+            ErrorCode::RateLimited => "Too many requests",
+            ErrorCode::ProviderError => "Provider error",
+            ErrorCode::ProviderUnavailable => "Provider unavailable",
+            ErrorCode::PartialData => "Provider returned partial data",
+            ErrorCode::InternalError => "Internal server error",
+        }
+    }
+
+    /// Problem type URI (`type`)
+    pub fn type_uri(self) -> String {
+        format!("urn:currency-rates:problem:{}", self.as_str())
+    }
+}
+
+/// Dates with data, as a hint for requests outside them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct DateSpan {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+/// Hints on how to fix a rejected request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Suggestions {
+    /// Known currency codes close to an unknown one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub currencies: Vec<String>,
+    /// Dates the currency (or any rate, for `no_data`) is available for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available: Option<DateSpan>,
+    /// Closest dates with data before and after the requested one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nearest_dates: Vec<NaiveDate>,
+}
+
+impl Suggestions {
+    pub fn is_empty(&self) -> bool {
+        *self == Suggestions::default()
+    }
+}
+
+/// A rejected request parameter: what was wrong with which value
+#[derive(Debug, Clone)]
+pub struct ParamError {
+    pub code: ErrorCode,
+    pub param: Option<String>,
+    pub value: Option<String>,
+    pub detail: String,
+    pub suggestions: Suggestions,
+}
+
+impl ParamError {
+    pub fn new(code: ErrorCode, detail: impl Into<String>) -> Self {
+        Self {
+            code,
+            param: None,
+            value: None,
+            detail: detail.into(),
+            suggestions: Suggestions::default(),
+        }
+    }
+
+    /// Name and value of the offending parameter
+    pub fn param(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.param = Some(name.to_string());
+        self.value = Some(value.into());
+        self
+    }
+
+    pub fn with_suggestions(mut self, suggestions: Suggestions) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+}
+
+impl From<ParamError> for AppError {
+    fn from(e: ParamError) -> Self {
+        AppError::Param(Box::new(e))
+    }
+}
+
+/// Error body in the RFC 7807 `application/problem+json` format, with the
+/// stable `code`, the offending parameter and suggestions as extensions
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Problem {
+    /// URI identifying the problem type, `urn:currency-rates:problem:<code>`
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Summary of the problem type
+    pub title: String,
+    /// HTTP status code
+    pub status: u16,
+    /// What went wrong in this request
+    pub detail: String,
+    pub code: ErrorCode,
+    /// Offending query, path or body parameter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub param: Option<String>,
+    /// Offending value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestions: Option<Suggestions>,
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -25,15 +210,44 @@ pub enum AppError {
     #[error("Invalid date: {0}")]
     InvalidDate(String),
 
-    #[error("Invalid currency: {0}")]
-    InvalidCurrency(String),
+    /// Invalid request parameter, with details for the problem response
+    #[error("{}", .0.detail)]
+    Param(Box<ParamError>),
 
     #[error("No data available for the requested date")]
     NoDataAvailable,
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
     #[error("Provider error: {0}")]
     Provider(String),
 
+    #[error("Provider unavailable: {0}")]
+    Unavailable(String),
+
+    /// Some requests of a batch failed; `rates` holds what was fetched
+    #[error("Partial data, failed to fetch: {}", missing.join(", "))]
+    PartialData {
+        rates: Vec<DailyRates>,
+        missing: Vec<String>,
+    },
+
     #[error("Configuration error: {0}")]
     Config(String),
 
@@ -41,60 +255,159 @@ pub enum AppError {
     Internal(String),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, message) = match &self {
+impl AppError {
+    /// Name the offending parameter of a `Param` error, e.g. the path
+    /// segment a shared date parser was called for
+    pub fn with_param(self, name: &str) -> Self {
+        match self {
+            AppError::Param(mut e) => {
+                e.param = Some(name.to_string());
+                AppError::Param(e)
+            }
+            other => other,
+        }
+    }
+
+    /// Problem response body of this error
+    pub fn problem(&self) -> Problem {
+        let code = match self {
             AppError::Database(e) => {
                 tracing::error!("Database error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+                ErrorCode::InternalError
             }
             AppError::Request(e) => {
                 tracing::error!("HTTP request error: {}", e);
-                (StatusCode::BAD_GATEWAY, "Failed to fetch external data")
+                ErrorCode::ProviderError
             }
             AppError::XmlParse(e) => {
                 tracing::error!("XML parsing error: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Failed to parse provider data",
-                )
+                ErrorCode::InternalError
             }
             AppError::JsonParse(e) => {
                 tracing::error!("JSON parsing error: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Failed to parse provider data",
-                )
+                ErrorCode::InternalError
             }
-            AppError::DateParse(_) | AppError::InvalidDate(_) => {
-                (StatusCode::BAD_REQUEST, "Invalid date format")
-            }
-            AppError::InvalidCurrency(_) => (StatusCode::NOT_FOUND, "Currency not found"),
-            AppError::NoDataAvailable => (
-                StatusCode::NOT_FOUND,
-                "No data available for the requested parameters",
-            ),
+            AppError::DateParse(_) | AppError::InvalidDate(_) => ErrorCode::InvalidDate,
+            AppError::Param(e) => e.code,
+            AppError::NoDataAvailable => ErrorCode::NoData,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::Validation(_) => ErrorCode::InvalidParameter,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::TooManyRequests(_) => ErrorCode::RateLimited,
             AppError::Provider(e) => {
                 tracing::error!("Provider error: {}", e);
-                (StatusCode::BAD_GATEWAY, "Provider error")
+                ErrorCode::ProviderError
+            }
+            AppError::Unavailable(e) => {
+                tracing::warn!("Provider unavailable: {}", e);
+                ErrorCode::ProviderUnavailable
+            }
+            AppError::PartialData { missing, .. } => {
+                tracing::warn!("Partial provider data, missing: {:?}", missing);
+                ErrorCode::PartialData
             }
             AppError::Config(e) => {
                 tracing::error!("Configuration error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Configuration error")
+                ErrorCode::InternalError
             }
             AppError::Internal(e) => {
                 tracing::error!("Internal error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+                ErrorCode::InternalError
             }
         };
 
-        let body = Json(json!({
-            "error": message,
-            "message": self.to_string()
-        }));
+        let (param, value, suggestions) = match self {
+            AppError::Param(e) => (
+                e.param.clone(),
+                e.value.clone(),
+                (!e.suggestions.is_empty()).then(|| e.suggestions.clone()),
+            ),
+            _ => (None, None, None),
+        };
+
+        Problem {
+            problem_type: code.type_uri(),
+            title: code.title().to_string(),
+            status: code.status().as_u16(),
+            detail: self.to_string(),
+            code,
+            param,
+            value,
+            suggestions,
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let problem = self.problem();
+        let status =
+            StatusCode::from_u16(problem.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        let mut response = (status, Json(problem)).into_response();
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        response
+    }
+}
+
+// Extractor rejections, answered as problems through `api::extract`
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        ParamError::new(ErrorCode::InvalidParameter, rejection.body_text()).into()
+    }
+}
 
-        (status, body).into_response()
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        ParamError::new(ErrorCode::InvalidParameter, rejection.body_text()).into()
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        ParamError::new(ErrorCode::InvalidParameter, rejection.body_text()).into()
     }
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_response() {
+        let error: AppError = ParamError::new(ErrorCode::UnknownCurrency, "Unknown currency: EUT")
+            .param("to", "EUT")
+            .with_suggestions(Suggestions {
+                currencies: vec!["EUR".to_string()],
+                ..Default::default()
+            })
+            .into();
+
+        let body = serde_json::to_value(error.problem()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "type": "urn:currency-rates:problem:unknown_currency",
+                "title": "Unknown currency",
+                "status": 400,
+                "detail": "Unknown currency: EUT",
+                "code": "unknown_currency",
+                "param": "to",
+                "value": "EUT",
+                "suggestions": {"currencies": ["EUR"]}
+            })
+        );
+
+        let response = AppError::NotFound("Job abc".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/problem+json");
+    }
+}
@@ -2,13 +2,21 @@ pub mod api;
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod metrics;
 pub mod models;
 pub mod providers;
+pub mod quality;
+pub mod reconciliation;
 pub mod seed;
 pub mod service;
+pub mod telemetry;
+pub mod usage;
 
-pub use config::Config;
+pub use config::{Config, PartialDataPolicy, ProviderSettings};
 pub use db::RatesRepository;
 pub use error::{AppError, Result};
-pub use providers::{EcbProvider, NbuProvider, Provider, ProviderRegistry};
+pub use providers::{
+    BoeProvider, ConfigurableProvider, DerivedProvider, EcbProvider, NbuCurrencyFilter,
+    NbuProvider, Provider, ProviderRegistry, SeriesProvider, SnbProvider,
+};
 pub use service::RatesService;
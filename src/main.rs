@@ -1,27 +1,24 @@
 use sqlx::sqlite::SqlitePoolOptions;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::net::TcpListener;
 use tokio_cron_scheduler::{Job, JobScheduler};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use std::path::PathBuf;
 
 use currency_rates::{
-    Config, EcbProvider, NbuProvider, ProviderRegistry, RatesRepository, RatesService,
+    Config, RatesRepository, RatesService,
     api::{self, AppState},
-    seed,
+    models::SyncTrigger,
+    seed, telemetry,
+    usage::ClientLimiter,
 };
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "currency_rates=info,tower_http=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize logging and tracing
+    let _telemetry = telemetry::init("currency-rates", "currency_rates=info,tower_http=info");
 
     // Load configuration
     let config = Config::from_env();
@@ -40,68 +37,20 @@ async fn main() -> anyhow::Result<()> {
     repository.init().await?;
     tracing::info!("Database initialized");
 
-    // Seed database from bundled files if enabled and database is empty
-    if config.seed_on_startup {
-        let ecb_count = repository.get_rates_count("ecb").await?;
-        let nbu_count = repository.get_rates_count("nbu").await?;
-
-        if ecb_count == 0 && nbu_count == 0 {
-            tracing::info!("Database is empty, seeding from bundled files...");
-
-            let ecb_seed_path = std::env::var("ECB_SEED_PATH")
-                .ok()
-                .map(PathBuf::from)
-                .or_else(|| {
-                    let default = PathBuf::from("seed_data/ecb-full-hist.xml");
-                    if default.exists() {
-                        Some(default)
-                    } else {
-                        None
-                    }
-                });
-
-            let nbu_seed_path = std::env::var("NBU_SEED_PATH")
-                .ok()
-                .map(PathBuf::from)
-                .or_else(|| {
-                    let default = PathBuf::from("seed_data/nbu-full-hist.json");
-                    if default.exists() {
-                        Some(default)
-                    } else {
-                        None
-                    }
-                });
-
-            if ecb_seed_path.is_some() || nbu_seed_path.is_some() {
-                if let Err(e) = seed::seed_database(
-                    &repository,
-                    ecb_seed_path.as_deref(),
-                    nbu_seed_path.as_deref(),
-                )
-                .await
-                {
-                    tracing::error!("Seeding failed: {}", e);
-                } else {
-                    tracing::info!("Database seeding completed");
-                }
-            } else {
-                tracing::info!("No seed files found, skipping seeding");
-            }
-        } else {
-            tracing::info!(
-                "Database already contains data (ECB: {} records, NBU: {} records), skipping seeding",
-                ecb_count,
-                nbu_count
-            );
-        }
-    }
+    // Limits shared by all provider HTTP clients
+    currency_rates::providers::limiter::configure(config.fetch_limits);
+    tracing::info!(
+        "Provider fetching: {} parallel requests, {} req/s per host",
+        config.fetch_limits.concurrency,
+        config.fetch_limits.requests_per_second
+    );
 
-    // Register providers
-    // All providers store rates with USD as the internal base currency
-    let mut providers = ProviderRegistry::new();
-    providers.register(EcbProvider::new());
-    providers.register(NbuProvider::new());
-    tracing::info!("Registered providers: {:?}", providers.names());
+    let providers = currency_rates::providers::build_registry(&config, &repository)?;
+    tracing::info!(
+        "Registered providers: {:?} (enabled: {:?})",
+        providers.names(),
+        providers.enabled_names()
+    );
 
     let providers = Arc::new(providers);
 
@@ -110,68 +59,200 @@ async fn main() -> anyhow::Result<()> {
         repository.clone(),
         providers.clone(),
         config.default_api_base.clone(),
-    );
+    )
+    .with_quality_rules(config.quality.clone())
+    .with_reconciliation_tolerance(config.reconciliation_tolerance);
 
     // Create shared state
     let state = Arc::new(AppState {
         service,
         default_api_base: config.default_api_base.clone(),
+        admin_tokens: config.admin_tokens.clone(),
+        public_read: config.public_read,
+        client_limits: config.client_limits,
+        limiter: ClientLimiter::new(),
+        failed_keys: ClientLimiter::new(),
+        startup_complete: AtomicBool::new(false),
     });
 
-    // Initial sync if enabled (runs in background so server starts immediately)
-    if config.sync_on_startup {
-        let sync_state = state.clone();
-        tokio::spawn(async move {
+    // Seeding and the initial sync run in the background so the server starts
+    // immediately; `/health/ready` answers 503 until seeding is done
+    let startup_state = state.clone();
+    let (seed_on_startup, sync_on_startup) = (config.seed_on_startup, config.sync_on_startup);
+    tokio::spawn(async move {
+        if seed_on_startup {
+            if let Err(e) = seed_if_empty(&repository).await {
+                tracing::error!("Seeding failed: {}", e);
+            }
+        }
+        startup_state
+            .startup_complete
+            .store(true, Ordering::Release);
+
+        if sync_on_startup {
             tracing::info!("Running initial sync in background...");
-            match sync_state.service.sync_all_providers().await {
+            match startup_state
+                .service
+                .sync_all_providers(SyncTrigger::Startup)
+                .await
+            {
                 Err(e) => {
                     tracing::error!("Initial sync failed: {}", e);
                 }
-                _ => {
-                    tracing::info!("Initial sync completed");
+                Ok(job) => {
+                    tracing::info!("Initial sync completed: {:?}", job.status);
                 }
             }
-        });
-    }
+        }
+    });
 
     // Setup scheduled sync
     let scheduler = JobScheduler::new().await?;
 
-    // Clone state for the scheduler
-    let sync_state = state.clone();
-    let cron_expr = config.sync_cron.clone();
+    // Group enabled providers by cron expression, keeping sync order within a group
+    let mut schedules: Vec<(String, Vec<String>)> = Vec::new();
+    for name in providers.enabled_names() {
+        let cron_expr = providers
+            .settings(&name)
+            .sync_cron
+            .unwrap_or_else(|| config.sync_cron.clone());
+        match schedules.iter_mut().find(|(cron, _)| *cron == cron_expr) {
+            Some((_, names)) => names.push(name),
+            None => schedules.push((cron_expr, vec![name])),
+        }
+    }
 
     // Schedule periodic sync
-    let job = Job::new_async(cron_expr.as_str(), move |_uuid, _lock| {
-        let state = sync_state.clone();
+    for (cron_expr, names) in schedules {
+        let sync_state = state.clone();
+        let job_names = names.clone();
+        let job = Job::new_async(cron_expr.as_str(), move |_uuid, _lock| {
+            let state = sync_state.clone();
+            let names = job_names.clone();
+            Box::pin(async move {
+                tracing::info!("Running scheduled sync for {:?}...", names);
+                if let Err(e) = state
+                    .service
+                    .sync_providers(&names, SyncTrigger::Schedule)
+                    .await
+                {
+                    tracing::error!("Scheduled sync failed: {}", e);
+                }
+            })
+        })?;
+
+        scheduler.add(job).await?;
+        tracing::info!("Scheduled sync of {:?} with cron: {}", names, cron_expr);
+    }
+
+    // Run syncs postponed after partial data (`<NAME>_PARTIAL_DATA=retry_later`)
+    let retry_state = state.clone();
+    let retry_job = Job::new_async("0 * * * * *", move |_uuid, _lock| {
+        let state = retry_state.clone();
         Box::pin(async move {
-            tracing::info!("Running scheduled sync...");
-            if let Err(e) = state.service.sync_all_providers().await {
-                tracing::error!("Scheduled sync failed: {}", e);
+            if let Err(e) = state.service.run_due_retries().await {
+                tracing::error!("Postponed sync failed: {}", e);
             }
         })
     })?;
+    scheduler.add(retry_job).await?;
+
+    // Write API key usage counters
+    let usage_state = state.clone();
+    let usage_job = Job::new_async("30 * * * * *", move |_uuid, _lock| {
+        let state = usage_state.clone();
+        Box::pin(async move {
+            if let Err(e) = api::flush_usage(&state).await {
+                tracing::error!("Failed to record API usage: {}", e);
+            }
+        })
+    })?;
+    scheduler.add(usage_job).await?;
 
-    scheduler.add(job).await?;
     scheduler.start().await?;
-    tracing::info!("Scheduler started with cron: {}", config.sync_cron);
+    tracing::info!("Scheduler started");
 
     // Create router
-    let app = api::create_router(state);
+    let app = api::create_router(state.clone());
 
     // Start server
     let addr = format!("{}:{}", config.host, config.port);
     let listener = TcpListener::bind(&addr).await?;
     tracing::info!("Server listening on http://{}", addr);
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
+
+    if let Err(e) = api::flush_usage(&state).await {
+        tracing::error!("Failed to record API usage: {}", e);
+    }
 
     tracing::info!("Server shutdown complete");
     Ok(())
 }
 
+/// Seed the database from bundled files if it holds no ECB or NBU rates
+async fn seed_if_empty(repository: &RatesRepository) -> anyhow::Result<()> {
+    let ecb_count = repository.get_rates_count("ecb").await?;
+    let nbu_count = repository.get_rates_count("nbu").await?;
+
+    if ecb_count == 0 && nbu_count == 0 {
+        tracing::info!("Database is empty, seeding from bundled files...");
+
+        let ecb_seed_path = std::env::var("ECB_SEED_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| {
+                let default = PathBuf::from("seed_data/ecb-full-hist.xml");
+                if default.exists() {
+                    Some(default)
+                } else {
+                    None
+                }
+            });
+
+        let nbu_seed_path = std::env::var("NBU_SEED_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| {
+                let default = PathBuf::from("seed_data/nbu-full-hist.json");
+                if default.exists() {
+                    Some(default)
+                } else {
+                    None
+                }
+            });
+
+        if ecb_seed_path.is_some() || nbu_seed_path.is_some() {
+            if let Err(e) = seed::seed_database(
+                repository,
+                ecb_seed_path.as_deref(),
+                nbu_seed_path.as_deref(),
+            )
+            .await
+            {
+                tracing::error!("Seeding failed: {}", e);
+            } else {
+                tracing::info!("Database seeding completed");
+            }
+        } else {
+            tracing::info!("No seed files found, skipping seeding");
+        }
+    } else {
+        tracing::info!(
+            "Database already contains data (ECB: {} records, NBU: {} records), skipping seeding",
+            ecb_count,
+            nbu_count
+        );
+    }
+
+    Ok(())
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
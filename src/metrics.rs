@@ -0,0 +1,211 @@
+use prometheus::{
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use crate::models::ProviderSyncProgress;
+
+/// Content type of the Prometheus text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Sync duration buckets in seconds (full-history fetches take minutes)
+const SYNC_BUCKETS: &[f64] = &[0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+/// Process-wide metrics, exported by `GET /metrics`
+struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    sync_duration: HistogramVec,
+    sync_records: IntCounterVec,
+    sync_runs: IntCounterVec,
+    latest_rate_age: GaugeVec,
+    cache_requests: IntCounterVec,
+    db_connections: IntGaugeVec,
+    db_max_connections: IntGauge,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("currency_rates".to_string()), None)
+            .expect("valid metrics prefix");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency"),
+            &["method", "route"],
+        )
+        .unwrap();
+        let sync_duration = HistogramVec::new(
+            HistogramOpts::new("sync_duration_seconds", "Duration of provider syncs")
+                .buckets(SYNC_BUCKETS.to_vec()),
+            &["provider"],
+        )
+        .unwrap();
+        let sync_records = IntCounterVec::new(
+            Opts::new(
+                "sync_records_total",
+                "Rate records written by provider syncs",
+            ),
+            &["provider"],
+        )
+        .unwrap();
+        let sync_runs = IntCounterVec::new(
+            Opts::new("sync_runs_total", "Provider syncs by outcome"),
+            &["provider", "status"],
+        )
+        .unwrap();
+        let latest_rate_age = GaugeVec::new(
+            Opts::new(
+                "latest_rate_age_seconds",
+                "Time since the start (UTC) of the latest stored rate date",
+            ),
+            &["provider"],
+        )
+        .unwrap();
+        let cache_requests = IntCounterVec::new(
+            Opts::new(
+                "provider_cache_requests_total",
+                "Provider downloads answered from the local cache (hit) or fetched (miss)",
+            ),
+            &["provider", "result"],
+        )
+        .unwrap();
+        let db_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "SQLite pool connections by state"),
+            &["state"],
+        )
+        .unwrap();
+        let db_max_connections =
+            IntGauge::new("db_pool_max_connections", "Maximum size of the SQLite pool").unwrap();
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_duration.clone()),
+            Box::new(sync_duration.clone()),
+            Box::new(sync_records.clone()),
+            Box::new(sync_runs.clone()),
+            Box::new(latest_rate_age.clone()),
+            Box::new(cache_requests.clone()),
+            Box::new(db_connections.clone()),
+            Box::new(db_max_connections.clone()),
+        ] {
+            registry.register(collector).expect("unique metric names");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_duration,
+            sync_duration,
+            sync_records,
+            sync_runs,
+            latest_rate_age,
+            cache_requests,
+            db_connections,
+            db_max_connections,
+        }
+    }
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// Count a served HTTP request. `route` is the matched route pattern, not the
+/// raw path, to keep label cardinality bounded.
+pub fn observe_request(method: &str, route: &str, status: u16, elapsed: Duration) {
+    METRICS
+        .http_requests
+        .with_label_values(&[method, route, &status.to_string()])
+        .inc();
+    METRICS
+        .http_duration
+        .with_label_values(&[method, route])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Record a finished provider sync
+pub fn observe_sync(run: &ProviderSyncProgress) {
+    let provider = run.provider.as_str();
+
+    METRICS
+        .sync_runs
+        .with_label_values(&[provider, run.status.as_str()])
+        .inc();
+    METRICS
+        .sync_records
+        .with_label_values(&[provider])
+        .inc_by(run.records as u64);
+    if let Some(ms) = run.duration_ms {
+        METRICS
+            .sync_duration
+            .with_label_values(&[provider])
+            .observe(ms.max(0) as f64 / 1000.0);
+    }
+}
+
+/// Count a provider download served from (`hit`) or stored in the local cache
+pub fn observe_cache(provider: &str, hit: bool) {
+    METRICS
+        .cache_requests
+        .with_label_values(&[provider, if hit { "hit" } else { "miss" }])
+        .inc();
+}
+
+/// Set the age of the latest stored rate of a provider
+pub fn set_latest_rate_age(provider: &str, age: Duration) {
+    METRICS
+        .latest_rate_age
+        .with_label_values(&[provider])
+        .set(age.as_secs_f64());
+}
+
+/// Set SQLite pool utilization
+pub fn set_db_pool(active: usize, idle: usize, max: u32) {
+    for (state, value) in [("active", active), ("idle", idle)] {
+        METRICS
+            .db_connections
+            .with_label_values(&[state])
+            .set(value as i64);
+    }
+    METRICS.db_max_connections.set(max as i64);
+}
+
+/// All metrics in the Prometheus text format
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&METRICS.registry.gather(), &mut buffer) {
+        tracing::error!("Failed to encode metrics: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SyncStatus;
+
+    #[test]
+    fn test_render_metrics() {
+        let mut run = ProviderSyncProgress::queued("test-provider");
+        run.status = SyncStatus::Success;
+        run.records = 42;
+        run.duration_ms = Some(1500);
+        observe_sync(&run);
+        observe_request("GET", "/latest", 200, Duration::from_millis(3));
+
+        let text = render();
+        assert!(text.contains(r#"currency_rates_sync_records_total{provider="test-provider"} 42"#));
+        assert!(text.contains(
+            r#"currency_rates_sync_runs_total{provider="test-provider",status="success"} 1"#
+        ));
+        assert!(text.contains(
+            r#"currency_rates_http_requests_total{method="GET",route="/latest",status="200"}"#
+        ));
+    }
+}
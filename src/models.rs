@@ -1,6 +1,10 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+use crate::providers::ProviderCapabilities;
+use crate::providers::http::CircuitStatus;
 
 /// A single exchange rate record
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +23,8 @@ pub struct DailyRates {
     pub base_currency: String,
     pub rates: HashMap<String, f64>,
     pub provider: String,
+    /// Copied from the previous published day to fill a weekend/holiday gap
+    pub estimated: bool,
 }
 
 /// Currency metadata
@@ -29,26 +35,52 @@ pub struct Currency {
 }
 
 /// Response format for /latest and /YYYY-MM-DD endpoints
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RatesResponse {
     pub amount: f64,
     pub base: String,
     pub date: NaiveDate,
     pub rates: HashMap<String, f64>,
+    /// Currencies computed from a peg rather than published by a provider
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub derived: Vec<String>,
+    /// Currencies whose rate was revised by the provider after first being stored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revised: Vec<String>,
+    /// Point in time the response was reconstructed for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
 }
 
 /// Response format for time series endpoint
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TimeSeriesResponse {
     pub amount: f64,
     pub base: String,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
     pub rates: HashMap<NaiveDate, HashMap<String, f64>>,
+    /// Currencies computed from a peg on at least one day of the range
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub derived: Vec<String>,
+    /// Currencies revised by the provider on at least one day of the range
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revised: Vec<String>,
+    /// Point in time the response was reconstructed for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Response format for /{date}: rates of one day, or a time series for a range
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum HistoricalResponse {
+    Day(RatesResponse),
+    Range(TimeSeriesResponse),
 }
 
 /// Currency information including date range
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CurrencyInfo {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -60,19 +92,485 @@ pub struct CurrencyInfo {
 /// Response format for /currencies endpoint
 pub type CurrenciesResponse = HashMap<String, CurrencyInfo>;
 
+/// Currency published by a provider, with the dates it was first and last seen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCurrency {
+    pub code: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_date: Option<NaiveDate>,
+}
+
+/// Provider metadata for the /providers endpoint
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProviderDetails {
+    pub name: String,
+    pub description: String,
+    /// Disabled providers are not synced but their stored data is still served
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub capabilities: ProviderCapabilities,
+}
+
+/// Most recent sync of a provider that did not succeed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SyncFailure {
+    pub at: DateTime<Utc>,
+    /// partial, retry_later or error
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
 /// Provider info for health check
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ProviderInfo {
     pub name: String,
+    pub description: String,
     pub enabled: bool,
-    pub last_sync: Option<String>,
+    /// Last successful sync
+    pub last_sync: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure: Option<SyncFailure>,
+    /// Earliest stored rate date
+    pub first_date: Option<NaiveDate>,
+    /// Latest stored rate date
+    pub latest_date: Option<NaiveDate>,
+    /// Enabled, but no successful sync or no newer rate date within
+    /// `<NAME>_STALE_AFTER_HOURS`
+    pub stale: bool,
+    /// Distinct target currencies stored
     pub currencies_count: usize,
+    /// Stored rate rows
+    pub rows_count: usize,
+    /// Rates waiting in quarantine for review
+    pub quarantined: usize,
+    /// Circuit breaker of the provider's HTTP client
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit: Option<CircuitStatus>,
+}
+
+/// Status and capabilities of a single provider
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderStatus {
+    #[serde(flatten)]
+    pub info: ProviderInfo,
+    #[serde(flatten)]
+    pub capabilities: ProviderCapabilities,
+}
+
+/// Manually entered rate: 1 `base_currency` = `rate` `target_currency`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ManualRate {
+    pub date: NaiveDate,
+    pub base_currency: String,
+    pub target_currency: String,
+    pub rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub updated_by: String,
+    pub updated_at: String,
+}
+
+/// Audit trail entry for a manual rate change
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ManualRateAudit {
+    pub id: i64,
+    /// "create", "update" or "delete"
+    pub action: String,
+    pub date: NaiveDate,
+    pub base_currency: String,
+    pub target_currency: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub actor: String,
+    pub changed_at: String,
+}
+
+/// A stored rate replaced by a different value from the provider.
+/// Rates are USD-based: `1 base_currency = rate target_currency`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RateRevision {
+    pub id: i64,
+    pub date: NaiveDate,
+    pub base_currency: String,
+    pub target_currency: String,
+    pub provider: String,
+    pub old_rate: f64,
+    pub new_rate: f64,
+    /// The old value was a weekend/holiday estimate, not a published rate
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub replaced_estimate: bool,
+    pub revised_at: String,
+}
+
+/// Why a fetched rate was held back from storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityReason {
+    /// Zero, negative, NaN or infinite; dropped
+    InvalidRate,
+    /// Moved more than the allowed day-over-day change; quarantined
+    Jump,
+}
+
+impl QualityReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QualityReason::InvalidRate => "invalid_rate",
+            QualityReason::Jump => "jump",
+        }
+    }
+}
+
+/// A fetched rate that failed a data quality check
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QualityIssue {
+    pub date: NaiveDate,
+    pub currency: String,
+    pub rate: f64,
+    pub reason: QualityReason,
+    /// Last accepted rate the change is measured against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_rate: Option<f64>,
+    /// Relative change from `previous_rate` (0.5 = +50%)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<f64>,
+}
+
+/// A suspicious provider rate kept out of `exchange_rates` until reviewed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuarantinedRate {
+    pub id: i64,
+    pub date: NaiveDate,
+    pub base_currency: String,
+    pub target_currency: String,
+    pub provider: String,
+    pub rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_rate: Option<f64>,
+    pub reason: String,
+    pub detected_at: String,
+}
+
+/// Same USD-based rate as published by two providers. `diff` is the
+/// difference relative to their mean (0.01 = 1%).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateDiscrepancy {
+    pub date: NaiveDate,
+    pub currency: String,
+    pub provider_a: String,
+    pub rate_a: f64,
+    pub provider_b: String,
+    pub rate_b: f64,
+    pub diff: f64,
+}
+
+/// A day on which two providers diverged
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DivergentDay {
+    pub date: NaiveDate,
+    /// Rate per provider
+    pub rates: BTreeMap<String, f64>,
+    pub diff: f64,
 }
 
-/// Health check response
-#[derive(Debug, Serialize, Deserialize)]
+/// Comparison of one pair between two providers over a date range
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReconciliationPair {
+    /// e.g. "USD/EUR"
+    pub pair: String,
+    pub providers: Vec<String>,
+    pub days_compared: usize,
+    pub days_diverging: usize,
+    pub max_diff: f64,
+    pub mean_diff: f64,
+    /// Days beyond the tolerance
+    pub divergences: Vec<DivergentDay>,
+}
+
+/// Response format for /reconciliation/{start}..{end}
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReconciliationReport {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub tolerance: f64,
+    /// Pairs published by more than one provider in the range
+    pub compared_pairs: usize,
+    /// Pairs that diverged beyond the tolerance on at least one day, largest first
+    pub pairs: Vec<ReconciliationPair>,
+}
+
+/// State of a sync job, or of one provider within a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    Queued,
+    Running,
+    Success,
+    /// Some data could not be fetched (or some providers failed)
+    Partial,
+    /// Postponed after partial data, see `<NAME>_PARTIAL_DATA=retry_later`
+    RetryLater,
+    Error,
+    /// Not run because another job was already syncing the provider
+    Skipped,
+}
+
+impl SyncStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncStatus::Queued => "queued",
+            SyncStatus::Running => "running",
+            SyncStatus::Success => "success",
+            SyncStatus::Partial => "partial",
+            SyncStatus::RetryLater => "retry_later",
+            SyncStatus::Error => "error",
+            SyncStatus::Skipped => "skipped",
+        }
+    }
+
+    /// Job or provider has finished
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, SyncStatus::Queued | SyncStatus::Running)
+    }
+}
+
+/// What started a sync job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncTrigger {
+    Api,
+    Cli,
+    Startup,
+    Schedule,
+    Retry,
+}
+
+/// How a backfill treats rates that are already stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackfillMode {
+    /// Refetch and store only missing (date, currency) cells
+    #[default]
+    FillMissing,
+    /// Refetch the whole range and replace stored rates
+    Overwrite,
+}
+
+impl std::str::FromStr for BackfillMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "fill-missing" => Ok(Self::FillMissing),
+            "overwrite" => Ok(Self::Overwrite),
+            other => Err(format!("unknown backfill mode: {}", other)),
+        }
+    }
+}
+
+/// Date range a backfill job repairs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct BackfillRequest {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub mode: BackfillMode,
+}
+
+/// Progress of one provider within a sync job
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProviderSyncProgress {
+    pub provider: String,
+    pub status: SyncStatus,
+    /// Rate records written
+    pub records: usize,
+    /// Parts (e.g. currencies) that could not be fetched
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+    /// Missing (date, currency) cells found before a fill-missing backfill
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing_cells: Option<usize>,
+    /// Rates dropped as invalid
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rejected: usize,
+    /// Rates quarantined for review
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub quarantined: usize,
+    /// First quality findings of the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<QualityIssue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+}
+
+impl ProviderSyncProgress {
+    pub fn queued(provider: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            status: SyncStatus::Queued,
+            records: 0,
+            missing: Vec::new(),
+            missing_cells: None,
+            rejected: 0,
+            quarantined: 0,
+            issues: Vec::new(),
+            error: None,
+            started_at: None,
+            finished_at: None,
+            duration_ms: None,
+        }
+    }
+}
+
+/// A sync run over one or more providers
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SyncJob {
+    pub id: String,
+    pub status: SyncStatus,
+    pub trigger: SyncTrigger,
+    /// Range being repaired, for backfill jobs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backfill: Option<BackfillRequest>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    /// Rate records written by all providers so far
+    pub records: usize,
+    pub providers: Vec<ProviderSyncProgress>,
+}
+
+/// A row of the sync log
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SyncLogEntry {
+    pub id: i64,
+    /// Job the run belonged to (empty for runs logged before jobs existed, or seeding)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    pub provider: String,
+    /// success, partial, retry_later, error, skipped or seeded
+    pub status: String,
+    pub records: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// When the run finished
+    pub synced_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+}
+
+/// A page of the sync log, newest first
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SyncHistoryResponse {
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub entries: Vec<SyncLogEntry>,
+}
+
+/// Permission granted to an API key. `admin` includes `sync`, which includes `read`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    Read,
+    Sync,
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Sync => "sync",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "read" => Ok(Self::Read),
+            "sync" => Ok(Self::Sync),
+            "admin" => Ok(Self::Admin),
+            other => Err(format!("unknown scope: {}", other)),
+        }
+    }
+}
+
+/// API key metadata. The key itself is only shown once, when it is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: i64,
+    /// Caller name recorded in audit trails
+    pub name: String,
+    /// First characters of the key, to recognize it
+    pub prefix: String,
+    pub scopes: Vec<Scope>,
+    /// Sustained requests per second, overriding `RATE_LIMIT_RPS`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<f64>,
+    /// Requests per UTC day, overriding `DAILY_QUOTA`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_quota: Option<u64>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<String>,
+}
+
+/// Requests made with an API key on one UTC day
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiUsage {
+    pub key: String,
+    pub date: NaiveDate,
+    /// Requests admitted
+    pub requests: u64,
+    /// Requests rejected by the rate limit or the daily quota
+    pub rejected: u64,
+}
+
+/// Health check response. `status` is `ok`, `degraded` (a provider is stale
+/// or failing fast), `starting` or `unavailable` (not ready, see `ReadinessResponse`).
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
     pub providers: Vec<ProviderInfo>,
 }
+
+/// Readiness probe response. `status` is `ready`, `starting` (seeding still
+/// running) or `unavailable` (database unreachable).
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: String,
+    pub database: bool,
+    pub startup_complete: bool,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
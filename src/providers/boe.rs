@@ -0,0 +1,85 @@
+use chrono::NaiveDate;
+
+use crate::providers::PublicationSchedule;
+use crate::providers::sdmx::{
+    Quotation, SeriesBacked, SeriesFormat, SeriesMapping, SeriesProvider, SeriesSource,
+};
+
+const BOE_IADB_URL: &str = "https://www.bankofengland.co.uk/boeapps/database/_iadb-fromshowcolumns.asp?csv.x=yes&Datefrom={start}&Dateto={end}&SeriesCodes={series}&CSVF=TN&UsingCodes=Y&VPD=Y&VFD=N";
+
+/// IADB spot rate series ("into sterling": units of currency per 1 GBP)
+const BOE_SERIES: &[(&str, &str, &str)] = &[
+    ("XUDLUSS", "USD", "US Dollar"),
+    ("XUDLERS", "EUR", "Euro"),
+    ("XUDLJYS", "JPY", "Japanese Yen"),
+    ("XUDLSFS", "CHF", "Swiss Franc"),
+    ("XUDLCDS", "CAD", "Canadian Dollar"),
+    ("XUDLADS", "AUD", "Australian Dollar"),
+    ("XUDLNDS", "NZD", "New Zealand Dollar"),
+    ("XUDLNKS", "NOK", "Norwegian Krone"),
+    ("XUDLSKS", "SEK", "Swedish Krona"),
+    ("XUDLDKS", "DKK", "Danish Krone"),
+    ("XUDLHDS", "HKD", "Hong Kong Dollar"),
+    ("XUDLSGS", "SGD", "Singapore Dollar"),
+    ("XUDLZRS", "ZAR", "South African Rand"),
+    ("XUDLSRS", "SAR", "Saudi Riyal"),
+];
+
+/// Bank of England provider
+/// Fetches GBP-based spot rates from the Interactive Database (IADB)
+pub struct BoeProvider {
+    inner: SeriesProvider,
+}
+
+impl BoeProvider {
+    pub fn new() -> Self {
+        Self {
+            inner: SeriesProvider::new(Self::source()),
+        }
+    }
+
+    /// Series source definition for the BoE IADB CSV export
+    pub fn source() -> SeriesSource {
+        let series = BOE_SERIES
+            .iter()
+            .map(|(key, currency, name)| {
+                SeriesMapping::new(key, currency, name, Quotation::ForeignPerBase)
+            })
+            .collect();
+
+        SeriesSource {
+            name: "boe".to_string(),
+            description: "Bank of England - Daily GBP spot exchange rates".to_string(),
+            native_base: "GBP".to_string(),
+            native_base_name: "British Pound".to_string(),
+            url_template: BOE_IADB_URL.to_string(),
+            url_date_format: "%d/%b/%Y".to_string(),
+            series_separator: ",".to_string(),
+            format: SeriesFormat::CsvWide {
+                delimiter: b',',
+                date_column: "DATE".to_string(),
+            },
+            date_format: "%d %b %Y".to_string(),
+            history_start: NaiveDate::from_ymd_opt(1975, 1, 2).unwrap(),
+            fill_gaps: true,
+            // Spot rates are recorded at about 4pm London time
+            publication: PublicationSchedule::business_days(Some((16, 0)), "Europe/London"),
+            attribution: Some(
+                "Source: Bank of England Statistical Interactive Database".to_string(),
+            ),
+            series,
+        }
+    }
+}
+
+impl Default for BoeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SeriesBacked for BoeProvider {
+    fn series(&self) -> &SeriesProvider {
+        &self.inner
+    }
+}
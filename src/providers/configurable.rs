@@ -0,0 +1,1042 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::{AppError, Result};
+use crate::models::{Currency, DailyRates};
+use crate::providers::http::{CircuitStatus, HttpClient};
+use crate::providers::{Provider, ProviderCapabilities, PublicationSchedule};
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_units() -> f64 {
+    1.0
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+/// How values published by the source are quoted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotationDef {
+    /// Units of the currency per 1 native base (1 EUR = 1.08 USD)
+    #[default]
+    ForeignPerBase,
+    /// Native base per `units` of the currency (100 JPY = 26.5 UAH)
+    BasePerForeign,
+}
+
+/// How to extract records from a response body
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FormatDef {
+    /// JSON document.
+    /// `records` is a path (`$.data.rates`) to either an array of records or an
+    /// object of `{ "CCY": rate }`. Field paths are relative to each record, or
+    /// to the document root when they start with `$`.
+    Json {
+        records: String,
+        date: Option<String>,
+        currency: Option<String>,
+        rate: Option<String>,
+        units: Option<String>,
+        #[serde(default = "default_date_format")]
+        date_format: String,
+    },
+    /// CSV with a header row; fields are column names
+    Csv {
+        #[serde(default = "default_delimiter")]
+        delimiter: char,
+        date_column: Option<String>,
+        currency_column: String,
+        rate_column: String,
+        units_column: Option<String>,
+        #[serde(default = "default_date_format")]
+        date_format: String,
+    },
+    /// XML document.
+    /// `records` is an element path (`//Rate`, `/rates/rate`); field selectors are
+    /// `@attr`, `child`, `child/@attr` or `text()` relative to each record, or
+    /// absolute (`/rates/@date`) when they start with `/`.
+    Xml {
+        records: String,
+        date: Option<String>,
+        currency: String,
+        rate: String,
+        units: Option<String>,
+        #[serde(default = "default_date_format")]
+        date_format: String,
+    },
+}
+
+/// Declarative definition of a provider, loaded from a TOML/YAML file
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfigurableProviderDef {
+    /// Unique provider name
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Request URL; supports `{date}`, `{start}` and `{end}` placeholders
+    pub url: String,
+    /// Optional URL for the latest rates (defaults to `url` for today)
+    pub latest_url: Option<String>,
+    /// chrono format used for URL placeholders
+    #[serde(default = "default_date_format")]
+    pub url_date_format: String,
+    /// Extra request headers; `${VAR}` is replaced with the environment variable
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Currency the source quotes against
+    pub native_base: String,
+    #[serde(default)]
+    pub quotation: QuotationDef,
+    /// Units per rate when the format has no units field
+    #[serde(default = "default_units")]
+    pub units: f64,
+    /// First date the source has data for (used for full history)
+    pub history_start: Option<NaiveDate>,
+    /// Fill weekends/holidays with the previous available day's rates
+    #[serde(default)]
+    pub fill_gaps: bool,
+    /// Only keep these currencies (all if empty)
+    #[serde(default)]
+    pub currencies: Vec<String>,
+    /// Publication schedule (defaults to business days, time unknown, UTC)
+    pub publication: Option<PublicationSchedule>,
+    /// License or attribution text shown on /providers
+    pub attribution: Option<String>,
+    pub format: FormatDef,
+}
+
+/// File layout: a list of provider definitions
+#[derive(Debug, Deserialize)]
+struct ProvidersFile {
+    #[serde(default)]
+    providers: Vec<ConfigurableProviderDef>,
+}
+
+/// A parsed record: (date, currency, value, units)
+type Record = (Option<NaiveDate>, String, f64, f64);
+
+/// Provider defined entirely by configuration
+pub struct ConfigurableProvider {
+    def: ConfigurableProviderDef,
+    http: HttpClient,
+}
+
+impl ConfigurableProvider {
+    pub fn new(def: ConfigurableProviderDef) -> Result<Self> {
+        if def.name.trim().is_empty() {
+            return Err(AppError::Config(
+                "Provider name must not be empty".to_string(),
+            ));
+        }
+        if def.native_base.len() != 3 {
+            return Err(AppError::Config(format!(
+                "Provider {}: invalid native base currency '{}'",
+                def.name, def.native_base
+            )));
+        }
+        if def.units <= 0.0 {
+            return Err(AppError::Config(format!(
+                "Provider {}: units must be positive",
+                def.name
+            )));
+        }
+        if let FormatDef::Csv { delimiter, .. } = &def.format {
+            if !delimiter.is_ascii() {
+                return Err(AppError::Config(format!(
+                    "Provider {}: CSV delimiter '{}' is not an ASCII character",
+                    def.name, delimiter
+                )));
+            }
+        }
+
+        let mut def = def;
+        def.native_base = def.native_base.to_uppercase();
+        def.currencies = def.currencies.iter().map(|c| c.to_uppercase()).collect();
+
+        Ok(Self {
+            http: HttpClient::new(&def.name),
+            def,
+        })
+    }
+
+    /// Load provider definitions from a `.toml`, `.yaml` or `.yml` file
+    pub fn load_file(path: &Path) -> Result<Vec<Self>> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Config(format!(
+                "Failed to read providers file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        let file: ProvidersFile = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&content)
+                .map_err(|e| AppError::Config(format!("Invalid providers TOML: {}", e)))?,
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&content)
+                .map_err(|e| AppError::Config(format!("Invalid providers YAML: {}", e)))?,
+            _ => {
+                return Err(AppError::Config(format!(
+                    "Unsupported providers file format: {}",
+                    path.display()
+                )));
+            }
+        };
+
+        file.providers.into_iter().map(Self::new).collect()
+    }
+
+    fn supports_range(&self) -> bool {
+        self.def.url.contains("{start}") && self.def.url.contains("{end}")
+    }
+
+    fn build_url(&self, template: &str, start: NaiveDate, end: NaiveDate) -> String {
+        let format = |d: NaiveDate| d.format(&self.def.url_date_format).to_string();
+
+        template
+            .replace("{date}", &format(end))
+            .replace("{start}", &format(start))
+            .replace("{end}", &format(end))
+    }
+
+    async fn fetch_body(&self, url: &str) -> Result<String> {
+        tracing::info!("Fetching {} rates: {}", self.def.name, url);
+
+        let headers: Vec<(&String, String)> = self
+            .def
+            .headers
+            .iter()
+            .map(|(name, value)| (name, expand_env(value)))
+            .collect();
+
+        let response = self
+            .http
+            .send(|client| {
+                headers
+                    .iter()
+                    .fold(client.get(url), |request, (name, value)| {
+                        request.header(name.as_str(), value.as_str())
+                    })
+            })
+            .await?;
+        Ok(response.text().await?)
+    }
+
+    /// Extract records from a response body according to the configured format
+    #[tracing::instrument(name = "parse", skip_all, fields(provider = %self.def.name))]
+    fn parse_records(&self, body: &str) -> Result<Vec<Record>> {
+        match &self.def.format {
+            FormatDef::Json {
+                records,
+                date,
+                currency,
+                rate,
+                units,
+                date_format,
+            } => {
+                let root: serde_json::Value = serde_json::from_str(body)?;
+                let selected = json_path(&root, &root, records).ok_or_else(|| {
+                    AppError::Provider(format!("JSON path '{}' not found", records))
+                })?;
+
+                let field = |record: &serde_json::Value, path: &Option<String>| {
+                    path.as_deref()
+                        .and_then(|p| json_path(&root, record, p))
+                        .and_then(json_scalar)
+                };
+
+                let mut result = Vec::new();
+                match selected {
+                    serde_json::Value::Array(items) => {
+                        for item in items {
+                            let (Some(code), Some(value)) =
+                                (field(item, currency), field(item, rate))
+                            else {
+                                continue;
+                            };
+                            let Ok(value) = value.parse::<f64>() else {
+                                continue;
+                            };
+                            result.push((
+                                field(item, date).and_then(|d| parse_date(&d, date_format)),
+                                code,
+                                value,
+                                field(item, units)
+                                    .and_then(|u| u.parse().ok())
+                                    .unwrap_or(self.def.units),
+                            ));
+                        }
+                    }
+                    serde_json::Value::Object(map) => {
+                        // { "CCY": rate } or { "CCY": { ..record.. } }
+                        let doc_date = field(&root, date).and_then(|d| parse_date(&d, date_format));
+                        for (code, item) in map {
+                            let value = match rate {
+                                Some(_) => field(item, rate),
+                                None => json_scalar(item),
+                            };
+                            let Some(value) = value.and_then(|v| v.parse::<f64>().ok()) else {
+                                continue;
+                            };
+                            result.push((
+                                doc_date,
+                                code.clone(),
+                                value,
+                                field(item, units)
+                                    .and_then(|u| u.parse().ok())
+                                    .unwrap_or(self.def.units),
+                            ));
+                        }
+                    }
+                    _ => {
+                        return Err(AppError::Provider(format!(
+                            "JSON path '{}' is not an array or object",
+                            records
+                        )));
+                    }
+                }
+                Ok(result)
+            }
+            FormatDef::Csv {
+                delimiter,
+                date_column,
+                currency_column,
+                rate_column,
+                units_column,
+                date_format,
+            } => {
+                let mut reader = csv::ReaderBuilder::new()
+                    // ASCII, checked in `new`
+                    .delimiter(*delimiter as u8)
+                    .flexible(true)
+                    .trim(csv::Trim::All)
+                    .from_reader(body.as_bytes());
+
+                let headers = reader
+                    .headers()
+                    .map_err(|e| AppError::Provider(format!("Invalid CSV: {}", e)))?
+                    .clone();
+                let column = |name: &str| {
+                    headers
+                        .iter()
+                        .position(|h| h.eq_ignore_ascii_case(name))
+                        .ok_or_else(|| {
+                            AppError::Provider(format!("CSV column '{}' not found", name))
+                        })
+                };
+
+                let date_idx = date_column.as_deref().map(column).transpose()?;
+                let currency_idx = column(currency_column)?;
+                let rate_idx = column(rate_column)?;
+                let units_idx = units_column.as_deref().map(column).transpose()?;
+
+                let mut result = Vec::new();
+                for row in reader.records() {
+                    let row = row.map_err(|e| AppError::Provider(format!("Invalid CSV: {}", e)))?;
+                    let (Some(code), Some(value)) = (
+                        row.get(currency_idx),
+                        row.get(rate_idx).and_then(|v| v.parse::<f64>().ok()),
+                    ) else {
+                        continue;
+                    };
+                    result.push((
+                        date_idx
+                            .and_then(|i| row.get(i))
+                            .and_then(|d| parse_date(d, date_format)),
+                        code.to_string(),
+                        value,
+                        units_idx
+                            .and_then(|i| row.get(i))
+                            .and_then(|u| u.parse().ok())
+                            .unwrap_or(self.def.units),
+                    ));
+                }
+                Ok(result)
+            }
+            FormatDef::Xml {
+                records,
+                date,
+                currency,
+                rate,
+                units,
+                date_format,
+            } => {
+                let root = parse_xml_tree(body)?;
+
+                let mut result = Vec::new();
+                for node in select_nodes(&root, records) {
+                    let field = |selector: &str| select_value(&root, node, selector);
+
+                    let (Some(code), Some(value)) = (
+                        field(currency),
+                        field(rate).and_then(|v| v.trim().parse::<f64>().ok()),
+                    ) else {
+                        continue;
+                    };
+                    result.push((
+                        date.as_deref()
+                            .and_then(field)
+                            .and_then(|d| parse_date(&d, date_format)),
+                        code.trim().to_string(),
+                        value,
+                        units
+                            .as_deref()
+                            .and_then(field)
+                            .and_then(|u| u.trim().parse().ok())
+                            .unwrap_or(self.def.units),
+                    ));
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    /// Convert parsed records to USD-based daily rates.
+    /// Records without a date are assigned `default_date`.
+    fn to_daily_rates(&self, records: Vec<Record>, default_date: NaiveDate) -> Vec<DailyRates> {
+        let mut native_rates_by_date: HashMap<NaiveDate, HashMap<String, f64>> = HashMap::new();
+
+        for (date, code, value, units) in records {
+            let code = code.to_uppercase();
+            if value <= 0.0 || !value.is_finite() {
+                continue;
+            }
+            if !self.def.currencies.is_empty()
+                && !self.def.currencies.contains(&code)
+                && code != "USD"
+            {
+                continue;
+            }
+
+            // Normalize to "units of currency per 1 native base"
+            let foreign_per_base = match self.def.quotation {
+                QuotationDef::ForeignPerBase => value / units,
+                QuotationDef::BasePerForeign => units / value,
+            };
+
+            native_rates_by_date
+                .entry(date.unwrap_or(default_date))
+                .or_default()
+                .insert(code, foreign_per_base);
+        }
+
+        let mut results: Vec<DailyRates> = Vec::new();
+
+        for (date, native_rates) in native_rates_by_date {
+            let Some(mut usd_rates) = super::rebase_to_usd(&self.def.native_base, native_rates)
+            else {
+                tracing::warn!(
+                    "{}/USD rate not found for date {} in {}, skipping",
+                    self.def.native_base,
+                    date,
+                    self.def.name
+                );
+                continue;
+            };
+
+            if !self.def.currencies.is_empty() {
+                usd_rates.retain(|code, _| {
+                    code == "USD"
+                        || code == &self.def.native_base
+                        || self.def.currencies.contains(code)
+                });
+            }
+
+            results.push(DailyRates {
+                date,
+                base_currency: "USD".to_string(),
+                rates: usd_rates,
+                provider: self.def.name.clone(),
+                estimated: false,
+            });
+        }
+
+        results.sort_by_key(|r| r.date);
+        results
+    }
+
+    async fn fetch_url(&self, url: &str, default_date: NaiveDate) -> Result<Vec<DailyRates>> {
+        let body = self.fetch_body(url).await?;
+        let records = self.parse_records(&body)?;
+        Ok(self.to_daily_rates(records, default_date))
+    }
+}
+
+#[async_trait]
+impl Provider for ConfigurableProvider {
+    fn name(&self) -> &str {
+        &self.def.name
+    }
+
+    fn description(&self) -> &str {
+        &self.def.description
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_base: self.def.native_base.clone(),
+            history_start: self.def.history_start,
+            publication: self
+                .def
+                .publication
+                .clone()
+                .unwrap_or_else(|| PublicationSchedule::business_days(None, "UTC")),
+            supports_range: self.supports_range(),
+            attribution: self.def.attribution.clone(),
+        }
+    }
+
+    async fn supported_currencies(&self) -> Result<Vec<Currency>> {
+        let codes: Vec<String> = if self.def.currencies.is_empty() {
+            let mut codes: Vec<String> = self.fetch_latest().await?.rates.into_keys().collect();
+            codes.sort();
+            codes
+        } else {
+            let mut codes = self.def.currencies.clone();
+            if !codes.contains(&self.def.native_base) {
+                codes.insert(0, self.def.native_base.clone());
+            }
+            codes
+        };
+
+        // Definitions carry no names; use the code until another provider supplies one
+        Ok(codes
+            .into_iter()
+            .map(|code| Currency {
+                name: code.clone(),
+                code,
+            })
+            .collect())
+    }
+
+    async fn fetch_latest(&self) -> Result<DailyRates> {
+        let today = chrono::Utc::now().date_naive();
+
+        let rates = match &self.def.latest_url {
+            Some(url) => self.fetch_url(url, today).await?,
+            None if self.supports_range() => {
+                let start = today - chrono::Duration::days(14);
+                self.fetch_url(&self.build_url(&self.def.url, start, today), today)
+                    .await?
+            }
+            None => {
+                self.fetch_url(&self.build_url(&self.def.url, today, today), today)
+                    .await?
+            }
+        };
+
+        rates.into_iter().last().ok_or_else(|| {
+            AppError::Provider(format!("No rates found in {} response", self.def.name))
+        })
+    }
+
+    async fn fetch_date(&self, date: NaiveDate) -> Result<DailyRates> {
+        let url = self.build_url(&self.def.url, date, date);
+
+        self.fetch_url(&url, date)
+            .await?
+            .into_iter()
+            .find(|r| r.date == date)
+            .ok_or(AppError::NoDataAvailable)
+    }
+
+    async fn fetch_range(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyRates>> {
+        let (mut rates, missing) = if self.supports_range() || !self.def.url.contains("{date}") {
+            // One request covers the whole range (or the document holds everything)
+            let url = self.build_url(&self.def.url, start, end);
+            (self.fetch_url(&url, end).await?, Vec::new())
+        } else {
+            // Only per-day URLs available
+            match super::provider::fetch_each_day(self, start, end).await {
+                Ok(rates) => (rates, Vec::new()),
+                Err(AppError::PartialData { rates, missing }) => (rates, missing),
+                Err(e) => return Err(e),
+            }
+        };
+
+        if self.def.fill_gaps {
+            rates = super::fill_gaps(rates, self.name());
+        }
+
+        let rates = rates
+            .into_iter()
+            .filter(|r| r.date >= start && r.date <= end)
+            .collect();
+        if missing.is_empty() {
+            Ok(rates)
+        } else {
+            Err(AppError::PartialData { rates, missing })
+        }
+    }
+
+    async fn fetch_full_history(&self) -> Result<Vec<DailyRates>> {
+        let end = chrono::Utc::now().date_naive();
+        let start = self
+            .def
+            .history_start
+            .unwrap_or(end - chrono::Duration::days(30));
+
+        tracing::info!(
+            "Fetching {} history from {} to {}",
+            self.def.name,
+            start,
+            end
+        );
+        self.fetch_range(start, end).await
+    }
+
+    fn circuit_status(&self) -> Option<CircuitStatus> {
+        Some(self.http.circuit_status())
+    }
+}
+
+fn parse_date(value: &str, format: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), format).ok()
+}
+
+/// Replace `${VAR}` with the value of the environment variable
+fn expand_env(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let var = &rest[start + 2..start + len];
+        result.push_str(&std::env::var(var).unwrap_or_default());
+        rest = &rest[start + len + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Resolve a simple JSON path (`$.a.b[0].c`, `a.b`) against `root` (for `$...`)
+/// or the current `record`
+fn json_path<'a>(
+    root: &'a serde_json::Value,
+    record: &'a serde_json::Value,
+    path: &str,
+) -> Option<&'a serde_json::Value> {
+    let (mut current, path) = match path.strip_prefix('$') {
+        Some(rest) => (root, rest),
+        None => (record, path),
+    };
+
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        // Split "name[0][1]" into the key and its indexes
+        let (key, indexes) = match segment.find('[') {
+            Some(pos) => (&segment[..pos], &segment[pos..]),
+            None => (segment, ""),
+        };
+
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+
+        for index in indexes.split('[').filter(|s| !s.is_empty()) {
+            let index: usize = index.trim_end_matches(']').parse().ok()?;
+            current = current.get(index)?;
+        }
+    }
+
+    Some(current)
+}
+
+/// String form of a scalar JSON value
+fn json_scalar(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Minimal XML element tree used for path selection
+#[derive(Debug, Default)]
+struct XmlNode {
+    name: String,
+    attributes: HashMap<String, String>,
+    text: String,
+    children: Vec<XmlNode>,
+}
+
+/// Parse XML into a tree; the returned node is a synthetic document root
+fn parse_xml_tree(xml: &str) -> Result<XmlNode> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let invalid = |e: quick_xml::Error| AppError::Provider(format!("Invalid XML: {}", e));
+
+    let element = |e: &quick_xml::events::BytesStart| XmlNode {
+        name: String::from_utf8_lossy(e.local_name().as_ref()).to_string(),
+        attributes: e
+            .attributes()
+            .flatten()
+            .filter_map(|attr| {
+                let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+                Some((key, attr.unescape_value().ok()?.to_string()))
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut stack: Vec<XmlNode> = vec![XmlNode::default()];
+
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) => stack.push(element(&e)),
+            Event::Empty(e) => {
+                let node = element(&e);
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+            Event::Text(e) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&e.unescape().map_err(invalid)?);
+                }
+            }
+            Event::CData(e) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&String::from_utf8_lossy(&e));
+                }
+            }
+            Event::End(_) if stack.len() > 1 => {
+                let node = stack.pop().unwrap_or_default();
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(stack.into_iter().next().unwrap_or_default())
+}
+
+fn collect_descendants<'a>(node: &'a XmlNode, name: &str, out: &mut Vec<&'a XmlNode>) {
+    for child in &node.children {
+        if name == "*" || child.name == name {
+            out.push(child);
+        }
+        collect_descendants(child, name, out);
+    }
+}
+
+/// Select elements with a simple path (`//a`, `/a/b`, `a/b`, `*`)
+fn select_nodes<'a>(context: &'a XmlNode, path: &str) -> Vec<&'a XmlNode> {
+    let mut current = vec![context];
+    let mut descendant = false;
+
+    for (i, step) in path.split('/').enumerate() {
+        if step.is_empty() {
+            // Leading "/" selects from the context, "//" switches to the descendant axis
+            if i > 0 {
+                descendant = true;
+            }
+            continue;
+        }
+        if step == "." {
+            continue;
+        }
+
+        let mut next = Vec::new();
+        for node in &current {
+            if descendant {
+                collect_descendants(node, step, &mut next);
+            } else {
+                next.extend(
+                    node.children
+                        .iter()
+                        .filter(|c| step == "*" || c.name == step),
+                );
+            }
+        }
+        current = next;
+        descendant = false;
+    }
+
+    current
+}
+
+/// Evaluate a value selector (`@attr`, `child`, `child/@attr`, `text()`) against
+/// `node`, or against the document `root` when it starts with `/`
+fn select_value(root: &XmlNode, node: &XmlNode, selector: &str) -> Option<String> {
+    let context = if selector.starts_with('/') {
+        root
+    } else {
+        node
+    };
+
+    let (path, attribute) = match selector.rfind('@') {
+        Some(pos) => (
+            selector[..pos].trim_end_matches('/'),
+            Some(&selector[pos + 1..]),
+        ),
+        None => (selector, None),
+    };
+    let (path, text) = match path.strip_suffix("text()") {
+        Some(p) => (p.trim_end_matches('/'), true),
+        None => (path, attribute.is_none()),
+    };
+
+    let target = if path.is_empty() || path == "." {
+        context
+    } else {
+        *select_nodes(context, path).first()?
+    };
+
+    match attribute {
+        Some(attr) => target.attributes.get(attr).cloned(),
+        None if text => Some(target.text.clone()),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider_from_toml(toml: &str) -> ConfigurableProvider {
+        let file: ProvidersFile = toml::from_str(toml).unwrap();
+        ConfigurableProvider::new(file.providers.into_iter().next().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_json_records() {
+        let provider = provider_from_toml(
+            r#"
+            [[providers]]
+            name = "treasury"
+            url = "https://example.com/rates?date={date}"
+            native_base = "EUR"
+
+            [providers.format]
+            type = "json"
+            records = "$.data.rates"
+            date = "$.data.date"
+            currency = "ccy"
+            rate = "value"
+            "#,
+        );
+
+        let body = r#"{"data": {"date": "2024-03-01", "rates": [
+            {"ccy": "USD", "value": 1.08},
+            {"ccy": "JPY", "value": "162.0"}
+        ]}}"#;
+
+        let records = provider.parse_records(body).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let default = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let rates = provider.to_daily_rates(records, default);
+        assert_eq!(rates.len(), 1);
+        assert_eq!(rates[0].date, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        let jpy = rates[0].rates.get("JPY").unwrap();
+        assert!((jpy - 162.0 / 1.08).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_json_object_map() {
+        let provider = provider_from_toml(
+            r#"
+            [[providers]]
+            name = "partner"
+            url = "https://example.com/latest"
+            native_base = "USD"
+
+            [providers.format]
+            type = "json"
+            records = "$.rates"
+            "#,
+        );
+
+        let records = provider
+            .parse_records(r#"{"rates": {"EUR": 0.92, "GBP": 0.79}}"#)
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let rates = provider.to_daily_rates(records, date);
+
+        assert_eq!(rates[0].date, date);
+        assert_eq!(rates[0].rates.get("EUR"), Some(&0.92));
+        assert_eq!(rates[0].rates.get("USD"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_csv_with_units() {
+        let provider = provider_from_toml(
+            r#"
+            [[providers]]
+            name = "partner_csv"
+            url = "https://example.com/rates.csv?from={start}&to={end}"
+            native_base = "UAH"
+            quotation = "base_per_foreign"
+            currencies = ["JPY"]
+
+            [providers.format]
+            type = "csv"
+            delimiter = ";"
+            date_column = "date"
+            currency_column = "code"
+            rate_column = "rate"
+            units_column = "units"
+            date_format = "%d.%m.%Y"
+            "#,
+        );
+
+        let body = "date;code;rate;units\n\
+                    01.03.2024;USD;38.0;1\n\
+                    01.03.2024;JPY;25.5;100\n\
+                    01.03.2024;EUR;41.0;1\n";
+
+        let records = provider.parse_records(body).unwrap();
+        let rates = provider.to_daily_rates(records, NaiveDate::from_ymd_opt(2000, 1, 1).unwrap());
+
+        // 1 USD = 38 UAH, 100 JPY = 25.5 UAH -> 1 USD = 38 / 0.255 JPY
+        let jpy = rates[0].rates.get("JPY").unwrap();
+        assert!((jpy - 38.0 / 0.255).abs() < 0.0001);
+        let uah = rates[0].rates.get("UAH").unwrap();
+        assert!((uah - 38.0).abs() < 0.0001);
+        // Filtered out by the currency list
+        assert!(!rates[0].rates.contains_key("EUR"));
+    }
+
+    #[test]
+    fn test_csv_delimiter_must_be_ascii() {
+        let file: ProvidersFile = toml::from_str(
+            r#"
+            [[providers]]
+            name = "partner_csv"
+            url = "https://example.com/rates.csv"
+            native_base = "UAH"
+
+            [providers.format]
+            type = "csv"
+            delimiter = "§"
+            currency_column = "code"
+            rate_column = "rate"
+            "#,
+        )
+        .unwrap();
+        let def = file.providers.into_iter().next().unwrap();
+
+        assert!(matches!(
+            ConfigurableProvider::new(def),
+            Err(AppError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_xml_attributes_and_children() {
+        let provider = provider_from_toml(
+            r#"
+            [[providers]]
+            name = "partner_xml"
+            url = "https://example.com/rates.xml"
+            native_base = "EUR"
+
+            [providers.format]
+            type = "xml"
+            records = "//Rate"
+            date = "/Rates/@date"
+            currency = "@currency"
+            rate = "Value"
+            "#,
+        );
+
+        let body = r#"<?xml version="1.0"?>
+            <Rates date="2024-03-01">
+                <Rate currency="USD"><Value>1.08</Value></Rate>
+                <Rate currency="GBP"><Value>0.85</Value></Rate>
+            </Rates>"#;
+
+        let records = provider.parse_records(body).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let rates = provider.to_daily_rates(records, NaiveDate::from_ymd_opt(2000, 1, 1).unwrap());
+        assert_eq!(rates[0].date, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        let gbp = rates[0].rates.get("GBP").unwrap();
+        assert!((gbp - 0.85 / 1.08).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_build_url_and_expand_env() {
+        let provider = provider_from_toml(
+            r#"
+            [[providers]]
+            name = "treasury"
+            url = "https://example.com/rates?from={start}&to={end}"
+            url_date_format = "%Y%m%d"
+            native_base = "eur"
+
+            [providers.format]
+            type = "json"
+            records = "$"
+            "#,
+        );
+
+        let url = provider.build_url(
+            &provider.def.url,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        assert_eq!(url, "https://example.com/rates?from=20240101&to=20240131");
+        assert!(provider.supports_range());
+        assert_eq!(provider.def.native_base, "EUR");
+
+        assert_eq!(expand_env("Bearer ${__UNSET_TEST_VAR__}"), "Bearer ");
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let provider = provider_from_toml(
+            r#"
+            [[providers]]
+            name = "treasury"
+            url = "https://example.com/rates/{date}"
+            native_base = "EUR"
+            attribution = "Internal use only"
+
+            [providers.publication]
+            weekdays = ["Mon", "Wed", "Fri"]
+            time = "10:30:00"
+            timezone = "Europe/Warsaw"
+
+            [providers.format]
+            type = "json"
+            records = "$"
+            "#,
+        );
+
+        let capabilities = provider.capabilities();
+        assert!(!capabilities.supports_range);
+        assert_eq!(capabilities.native_base, "EUR");
+        assert_eq!(
+            capabilities.attribution.as_deref(),
+            Some("Internal use only")
+        );
+        assert_eq!(
+            capabilities.publication.weekdays,
+            vec![
+                chrono::Weekday::Mon,
+                chrono::Weekday::Wed,
+                chrono::Weekday::Fri
+            ]
+        );
+        assert_eq!(capabilities.publication.timezone, "Europe/Warsaw");
+    }
+}
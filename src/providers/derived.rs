@@ -0,0 +1,341 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::db::RatesRepository;
+use crate::error::{AppError, Result};
+use crate::models::{Currency, DailyRates};
+use crate::providers::{Provider, ProviderCapabilities, PublicationSchedule};
+
+/// Provider name used for rates computed from pegs
+pub const DERIVED_PROVIDER: &str = "derived";
+
+/// Internal base currency for storage (all providers convert to this)
+const INTERNAL_BASE: &str = "USD";
+
+/// A currency defined as a fixed ratio to an anchor currency
+#[derive(Debug, Clone, Deserialize)]
+pub struct PegDefinition {
+    /// Pegged currency (e.g. "XOF")
+    pub currency: String,
+    /// Human-readable currency name
+    pub name: String,
+    /// Anchor currency (e.g. "EUR")
+    pub anchor: String,
+    /// Units of `currency` per 1 unit of `anchor` (e.g. 655.957 XOF per EUR)
+    pub ratio: f64,
+    /// First day the peg applies
+    pub valid_from: NaiveDate,
+    /// Last day the peg applies (open-ended if not set)
+    pub valid_to: Option<NaiveDate>,
+}
+
+impl PegDefinition {
+    fn new(
+        currency: &str,
+        name: &str,
+        anchor: &str,
+        ratio: f64,
+        valid_from: (i32, u32, u32),
+        valid_to: Option<(i32, u32, u32)>,
+    ) -> Self {
+        let date = |(y, m, d): (i32, u32, u32)| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        Self {
+            currency: currency.to_string(),
+            name: name.to_string(),
+            anchor: anchor.to_string(),
+            ratio,
+            valid_from: date(valid_from),
+            valid_to: valid_to.map(date),
+        }
+    }
+
+    fn is_valid_on(&self, date: NaiveDate) -> bool {
+        date >= self.valid_from && self.valid_to.is_none_or(|to| date <= to)
+    }
+}
+
+/// File layout: a list of peg definitions
+#[derive(Debug, Deserialize)]
+struct PegsFile {
+    #[serde(default)]
+    pegs: Vec<PegDefinition>,
+}
+
+/// Built-in hard pegs. Band pegs (DKK, HKD) are published by ECB and can be
+/// added through a pegs file if needed.
+pub fn default_pegs() -> Vec<PegDefinition> {
+    vec![
+        PegDefinition::new(
+            "XOF",
+            "West African CFA Franc",
+            "EUR",
+            655.957,
+            (1999, 1, 1),
+            None,
+        ),
+        PegDefinition::new(
+            "XAF",
+            "Central African CFA Franc",
+            "EUR",
+            655.957,
+            (1999, 1, 1),
+            None,
+        ),
+        PegDefinition::new(
+            "KMF",
+            "Comorian Franc",
+            "EUR",
+            491.96775,
+            (1999, 1, 1),
+            None,
+        ),
+        PegDefinition::new(
+            "BGN",
+            "Bulgarian Lev",
+            "EUR",
+            1.95583,
+            (1999, 1, 1),
+            Some((2025, 12, 31)),
+        ),
+        PegDefinition::new("AED", "UAE Dirham", "USD", 3.6725, (1997, 11, 2), None),
+        PegDefinition::new("SAR", "Saudi Riyal", "USD", 3.75, (1986, 6, 1), None),
+    ]
+}
+
+/// Provider that computes pegged currencies from anchor rates already stored
+/// by other providers. Must be synced after the providers supplying the anchors.
+pub struct DerivedProvider {
+    repository: RatesRepository,
+    pegs: Vec<PegDefinition>,
+}
+
+impl DerivedProvider {
+    pub fn new(repository: RatesRepository, pegs: Vec<PegDefinition>) -> Self {
+        Self { repository, pegs }
+    }
+
+    /// Built-in pegs, with entries from an optional TOML/YAML file replacing
+    /// the defaults for the same currency
+    pub fn load_pegs(path: Option<&Path>) -> Result<Vec<PegDefinition>> {
+        let mut pegs = default_pegs();
+
+        let Some(path) = path else {
+            return Ok(pegs);
+        };
+
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Config(format!(
+                "Failed to read pegs file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let file: PegsFile = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&content)
+                .map_err(|e| AppError::Config(format!("Invalid pegs TOML: {}", e)))?,
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&content)
+                .map_err(|e| AppError::Config(format!("Invalid pegs YAML: {}", e)))?,
+            _ => {
+                return Err(AppError::Config(format!(
+                    "Unsupported pegs file format: {}",
+                    path.display()
+                )));
+            }
+        };
+
+        for peg in &file.pegs {
+            if peg.ratio <= 0.0 || !peg.ratio.is_finite() {
+                return Err(AppError::Config(format!(
+                    "Peg {}/{}: ratio must be positive",
+                    peg.currency, peg.anchor
+                )));
+            }
+        }
+
+        let overridden: Vec<String> = file.pegs.iter().map(|p| p.currency.clone()).collect();
+        pegs.retain(|p| !overridden.contains(&p.currency));
+        pegs.extend(file.pegs);
+
+        Ok(pegs)
+    }
+
+    /// Compute pegged rates from USD-based anchor rates.
+    /// Currencies that the anchor data already contains are left to their provider.
+    fn derive(&self, anchor_rates: HashMap<NaiveDate, HashMap<String, f64>>) -> Vec<DailyRates> {
+        let mut results: Vec<DailyRates> = Vec::new();
+
+        for (date, rates) in anchor_rates {
+            let mut derived: HashMap<String, f64> = HashMap::new();
+
+            for peg in self.pegs.iter().filter(|p| p.is_valid_on(date)) {
+                if rates.contains_key(&peg.currency) {
+                    continue;
+                }
+
+                let usd_anchor = if peg.anchor == INTERNAL_BASE {
+                    1.0
+                } else {
+                    match rates.get(&peg.anchor) {
+                        Some(&rate) => rate,
+                        None => continue,
+                    }
+                };
+
+                // USD/XXX = USD/ANCHOR * ANCHOR/XXX
+                derived.insert(peg.currency.clone(), usd_anchor * peg.ratio);
+            }
+
+            if !derived.is_empty() {
+                results.push(DailyRates {
+                    date,
+                    base_currency: INTERNAL_BASE.to_string(),
+                    rates: derived,
+                    provider: DERIVED_PROVIDER.to_string(),
+                    estimated: false,
+                });
+            }
+        }
+
+        results.sort_by_key(|r| r.date);
+        results
+    }
+}
+
+#[async_trait]
+impl Provider for DerivedProvider {
+    fn name(&self) -> &str {
+        DERIVED_PROVIDER
+    }
+
+    fn description(&self) -> &str {
+        "Pegged currencies derived from anchor rates"
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_base: INTERNAL_BASE.to_string(),
+            history_start: self.pegs.iter().map(|p| p.valid_from).min(),
+            // Follows whatever the anchor providers publish
+            publication: PublicationSchedule::business_days(None, "UTC"),
+            supports_range: true,
+            attribution: None,
+        }
+    }
+
+    async fn supported_currencies(&self) -> Result<Vec<Currency>> {
+        let mut currencies: Vec<Currency> = Vec::new();
+        for peg in &self.pegs {
+            if !currencies.iter().any(|c| c.code == peg.currency) {
+                currencies.push(Currency {
+                    code: peg.currency.clone(),
+                    name: peg.name.clone(),
+                });
+            }
+        }
+        Ok(currencies)
+    }
+
+    async fn fetch_latest(&self) -> Result<DailyRates> {
+        let date = self
+            .repository
+            .get_latest_date(None)
+            .await?
+            .ok_or(AppError::NoDataAvailable)?;
+
+        self.fetch_date(date).await
+    }
+
+    async fn fetch_date(&self, date: NaiveDate) -> Result<DailyRates> {
+        self.fetch_range(date, date)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(AppError::NoDataAvailable)
+    }
+
+    async fn fetch_range(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyRates>> {
+        let anchor_rates = self
+            .repository
+            .get_rates_for_range_excluding(start, end, INTERNAL_BASE, DERIVED_PROVIDER)
+            .await?;
+
+        Ok(self.derive(anchor_rates))
+    }
+
+    async fn fetch_full_history(&self) -> Result<Vec<DailyRates>> {
+        let start = self
+            .pegs
+            .iter()
+            .map(|p| p.valid_from)
+            .min()
+            .ok_or(AppError::NoDataAvailable)?;
+        let end = chrono::Utc::now().date_naive();
+
+        self.fetch_range(start, end).await
+    }
+
+    fn depends_on_stored_rates(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_repository;
+
+    fn anchors(date: NaiveDate, rates: &[(&str, f64)]) -> HashMap<NaiveDate, HashMap<String, f64>> {
+        let mut map = HashMap::new();
+        map.insert(
+            date,
+            rates.iter().map(|(c, r)| (c.to_string(), *r)).collect(),
+        );
+        map
+    }
+
+    async fn provider(pegs: Vec<PegDefinition>) -> DerivedProvider {
+        DerivedProvider::new(test_repository().await, pegs)
+    }
+
+    #[tokio::test]
+    async fn test_derive_from_anchors() {
+        let provider = provider(default_pegs()).await;
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+        // SAR already supplied by another provider, BGN via ECB
+        let derived = provider.derive(anchors(
+            date,
+            &[("EUR", 0.9), ("SAR", 3.7502), ("BGN", 1.7602)],
+        ));
+
+        assert_eq!(derived.len(), 1);
+        let rates = &derived[0].rates;
+        assert_eq!(derived[0].provider, DERIVED_PROVIDER);
+        assert!((rates["XOF"] - 0.9 * 655.957).abs() < 1e-9);
+        assert!((rates["AED"] - 3.6725).abs() < 1e-9);
+        assert!(!rates.contains_key("SAR"));
+        assert!(!rates.contains_key("BGN"));
+    }
+
+    #[tokio::test]
+    async fn test_derive_respects_validity() {
+        let provider = provider(default_pegs()).await;
+
+        // BGN peg ends when Bulgaria adopts the euro
+        let after = NaiveDate::from_ymd_opt(2026, 2, 2).unwrap();
+        let derived = provider.derive(anchors(after, &[("EUR", 0.9)]));
+        assert!(!derived[0].rates.contains_key("BGN"));
+
+        let before = NaiveDate::from_ymd_opt(1999, 6, 1).unwrap();
+        let derived = provider.derive(anchors(before, &[("EUR", 0.95)]));
+        assert!((derived[0].rates["BGN"] - 0.95 * 1.95583).abs() < 1e-9);
+        // No EUR anchor, only the USD-anchored pegs are derived
+        let derived = provider.derive(anchors(before, &[("JPY", 110.0)]));
+        assert!(!derived[0].rates.contains_key("XOF"));
+        assert!(derived[0].rates.contains_key("AED"));
+    }
+}
@@ -1,116 +1,330 @@
 use async_trait::async_trait;
-use chrono::NaiveDate;
-use quick_xml::de::from_str;
-use serde::Deserialize;
-use std::collections::HashMap;
+use chrono::{Duration, NaiveDate};
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
 
 use crate::error::{AppError, Result};
+use crate::metrics;
 use crate::models::{Currency, DailyRates};
-use crate::providers::Provider;
+use crate::providers::http::{CircuitStatus, HttpClient};
+use crate::providers::sdmx::{SeriesFormat, SeriesProvider, SeriesSource};
+use crate::providers::{Provider, ProviderCapabilities, PublicationSchedule};
 
 const ECB_DAILY_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
 const ECB_HIST_90D_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist-90d.xml";
 const ECB_HIST_FULL_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist.xml";
-
-/// ECB XML structure for parsing
-#[derive(Debug, Deserialize)]
-#[serde(rename = "Envelope")]
-struct EcbEnvelope {
-    #[serde(rename = "Cube")]
-    cube: EcbOuterCube,
-}
-
-#[derive(Debug, Deserialize)]
-struct EcbOuterCube {
-    #[serde(rename = "Cube", default)]
-    cubes: Vec<EcbTimeCube>,
-}
-
-#[derive(Debug, Deserialize)]
-struct EcbTimeCube {
-    #[serde(rename = "@time")]
-    time: String,
-    #[serde(rename = "Cube", default)]
-    rates: Vec<EcbRateCube>,
-}
-
-#[derive(Debug, Deserialize)]
-struct EcbRateCube {
-    #[serde(rename = "@currency")]
-    currency: String,
-    #[serde(rename = "@rate")]
-    rate: f64,
+/// ECB data portal (SDMX) query for all daily reference rates in a date range
+const ECB_SDMX_URL: &str = "https://data-api.ecb.europa.eu/service/data/EXR/D..EUR.SP00.A?startPeriod={start}&endPeriod={end}&format=csvdata";
+
+/// A previously downloaded document and the validators to revalidate it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CachedDocument {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    #[serde(skip)]
+    body: String,
 }
 
 /// European Central Bank provider
 /// Provides EUR-based exchange rates
 pub struct EcbProvider {
-    client: reqwest::Client,
+    http: HttpClient,
+    /// Directory keeping the last downloaded documents between restarts
+    cache_dir: Option<PathBuf>,
+    cache: Mutex<HashMap<String, CachedDocument>>,
+    /// Parser for data portal responses
+    sdmx: SeriesProvider,
 }
 
 impl EcbProvider {
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::new(),
+            http: HttpClient::new("ecb"),
+            cache_dir: None,
+            cache: Mutex::new(HashMap::new()),
+            sdmx: SeriesProvider::new(sdmx_source()),
         }
     }
 
-    async fn fetch_and_parse(&self, url: &str) -> Result<Vec<DailyRates>> {
-        let response = self.client.get(url).send().await?;
-        let xml_text = response.text().await?;
-
-        let rates = self.parse_xml(&xml_text)?;
-        Ok(super::fill_gaps(rates, self.name()))
+    /// Persist downloaded documents (and their ETag/Last-Modified) in `dir`
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
     }
 
-    fn parse_xml(&self, xml: &str) -> Result<Vec<DailyRates>> {
-        let envelope: EcbEnvelope = from_str(xml)?;
+    /// Download `url`, revalidating the cached copy with ETag/If-Modified-Since
+    async fn fetch_document(&self, url: &str) -> Result<String> {
+        let key = cache_key(url);
+        let cached = self.cached_document(&key);
+
+        let response = self
+            .http
+            .send(|client| {
+                let mut request = client.get(url);
+                if let Some(cached) = &cached {
+                    if let Some(etag) = &cached.etag {
+                        request = request.header(IF_NONE_MATCH, etag);
+                    }
+                    if let Some(last_modified) = &cached.last_modified {
+                        request = request.header(IF_MODIFIED_SINCE, last_modified);
+                    }
+                }
+                request
+            })
+            .await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                tracing::debug!("ECB: {} not modified, using cached copy", url);
+                metrics::observe_cache("ecb", true);
+                return Ok(cached.body);
+            }
+            return Err(AppError::Provider(format!(
+                "ECB answered 304 for {} without a cached copy",
+                url
+            )));
+        }
 
-        let mut results = Vec::new();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let body = response.text().await?;
+        metrics::observe_cache("ecb", false);
+
+        if etag.is_some() || last_modified.is_some() {
+            self.store_document(
+                &key,
+                CachedDocument {
+                    etag,
+                    last_modified,
+                    body: body.clone(),
+                },
+            );
+        }
+
+        Ok(body)
+    }
 
-        for time_cube in envelope.cube.cubes {
-            let date = NaiveDate::parse_from_str(&time_cube.time, "%Y-%m-%d")?;
+    fn cached_document(&self, key: &str) -> Option<CachedDocument> {
+        if let Some(document) = self.cache.lock().unwrap().get(key) {
+            return Some(document.clone());
+        }
 
-            // First, collect EUR-based rates
-            let mut eur_rates: HashMap<String, f64> = HashMap::new();
-            eur_rates.insert("EUR".to_string(), 1.0);
+        let dir = self.cache_dir.as_ref()?;
+        let meta = std::fs::read_to_string(dir.join(format!("{}.json", key))).ok()?;
+        let mut document: CachedDocument = serde_json::from_str(&meta).ok()?;
+        document.body = std::fs::read_to_string(dir.join(format!("{}.xml", key))).ok()?;
 
-            for rate_cube in time_cube.rates {
-                eur_rates.insert(rate_cube.currency, rate_cube.rate);
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), document.clone());
+        Some(document)
+    }
+
+    fn store_document(&self, key: &str, document: CachedDocument) {
+        if let Some(dir) = &self.cache_dir {
+            let written = std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(dir.join(format!("{}.xml", key)), &document.body))
+                .and_then(|_| {
+                    let meta = serde_json::to_string(&document).unwrap_or_default();
+                    std::fs::write(dir.join(format!("{}.json", key)), meta)
+                });
+            if let Err(e) = written {
+                tracing::warn!("ECB: failed to write cache in {}: {}", dir.display(), e);
             }
+        }
 
-            // Get EUR/USD rate (how many USD per 1 EUR)
-            let eur_usd = match eur_rates.get("USD") {
-                Some(&rate) => rate,
-                None => {
-                    tracing::warn!("EUR/USD rate not found for date {}, skipping", date);
-                    continue;
-                }
-            };
+        self.cache.lock().unwrap().insert(key.to_string(), document);
+    }
+
+    /// Fetch an XML document and parse the days between `start` and `end`
+    async fn fetch_xml_range(
+        &self,
+        url: &str,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> Result<Vec<DailyRates>> {
+        let xml = self.fetch_document(url).await?;
+        self.parse_xml_range(&xml, start, end)
+    }
 
-            // Convert all rates to USD-based: USD/XXX = EUR/XXX / EUR/USD
-            let mut usd_rates: HashMap<String, f64> = HashMap::new();
-            usd_rates.insert("USD".to_string(), 1.0);
+    /// Fetch a date range from the ECB data portal
+    async fn fetch_sdmx_range(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyRates>> {
+        let url = ECB_SDMX_URL
+            .replace("{start}", &start.format("%Y-%m-%d").to_string())
+            .replace("{end}", &end.format("%Y-%m-%d").to_string());
+        tracing::info!("Fetching ECB rates from the data portal: {}", url);
+
+        let body = self.http.get_text(&url).await?;
+        let observations = self.sdmx.parse(&body)?;
+
+        let mut days: BTreeMap<NaiveDate, HashMap<String, f64>> = BTreeMap::new();
+        for observation in observations {
+            days.entry(observation.date)
+                .or_default()
+                .insert(observation.key, observation.value);
+        }
 
-            for (currency, eur_rate) in eur_rates {
-                if currency == "USD" {
-                    continue; // Already added as 1.0
+        Ok(days
+            .into_iter()
+            .rev()
+            .filter_map(|(date, eur_rates)| self.to_usd_based(date, eur_rates))
+            .collect())
+    }
+
+    /// Stream through an ECB XML document, keeping the days between `start` and `end`.
+    /// Documents list the newest day first, so parsing stops at the first day before `start`.
+    #[tracing::instrument(name = "parse", skip_all, fields(provider = "ecb", format = "xml"))]
+    fn parse_xml_range(
+        &self,
+        xml: &str,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> Result<Vec<DailyRates>> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut results = Vec::new();
+        // Day being read and whether it falls inside the range
+        let mut current: Option<(NaiveDate, HashMap<String, f64>)> = None;
+
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|e| AppError::Provider(format!("Invalid ECB XML: {}", e)))?;
+            match event {
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Cube" => {
+                    let mut time = None;
+                    let mut currency = None;
+                    let mut rate = None;
+                    for attr in e.attributes().flatten() {
+                        let Ok(value) = attr.unescape_value() else {
+                            continue;
+                        };
+                        match attr.key.local_name().as_ref() {
+                            b"time" => time = Some(value.to_string()),
+                            b"currency" => currency = Some(value.to_string()),
+                            b"rate" => rate = value.trim().parse::<f64>().ok(),
+                            _ => {}
+                        }
+                    }
+
+                    if let Some(time) = time {
+                        if let Some((date, eur_rates)) = current.take() {
+                            results.extend(self.to_usd_based(date, eur_rates));
+                        }
+
+                        let date = NaiveDate::parse_from_str(&time, "%Y-%m-%d")?;
+                        if start.is_some_and(|start| date < start) {
+                            break;
+                        }
+                        if end.is_none_or(|end| date <= end) {
+                            current = Some((date, HashMap::new()));
+                        }
+                    } else if let (Some((_, eur_rates)), Some(currency), Some(rate)) =
+                        (current.as_mut(), currency, rate)
+                    {
+                        eur_rates.insert(currency, rate);
+                    }
                 }
-                // USD/XXX = EUR/XXX / EUR/USD
-                let usd_rate = eur_rate / eur_usd;
-                usd_rates.insert(currency, usd_rate);
+                Event::Eof => break,
+                _ => {}
             }
+        }
 
-            results.push(DailyRates {
-                date,
-                base_currency: "USD".to_string(),
-                rates: usd_rates,
-                provider: self.name().to_string(),
-            });
+        if let Some((date, eur_rates)) = current {
+            results.extend(self.to_usd_based(date, eur_rates));
         }
 
         Ok(results)
     }
+
+    /// Convert one day of EUR-based rates to USD-based rates
+    fn to_usd_based(
+        &self,
+        date: NaiveDate,
+        mut eur_rates: HashMap<String, f64>,
+    ) -> Option<DailyRates> {
+        eur_rates.insert("EUR".to_string(), 1.0);
+
+        // Get EUR/USD rate (how many USD per 1 EUR)
+        let eur_usd = match eur_rates.get("USD") {
+            Some(&rate) => rate,
+            None => {
+                tracing::warn!("EUR/USD rate not found for date {}, skipping", date);
+                return None;
+            }
+        };
+
+        // Convert all rates to USD-based: USD/XXX = EUR/XXX / EUR/USD
+        let mut usd_rates: HashMap<String, f64> = HashMap::new();
+        usd_rates.insert("USD".to_string(), 1.0);
+
+        for (currency, eur_rate) in eur_rates {
+            if currency == "USD" {
+                continue; // Already added as 1.0
+            }
+            // USD/XXX = EUR/XXX / EUR/USD
+            let usd_rate = eur_rate / eur_usd;
+            usd_rates.insert(currency, usd_rate);
+        }
+
+        Some(DailyRates {
+            date,
+            base_currency: "USD".to_string(),
+            rates: usd_rates,
+            provider: self.name().to_string(),
+            estimated: false,
+        })
+    }
+}
+
+/// File name used to cache a document ("eurofxref-hist-90d")
+fn cache_key(url: &str) -> String {
+    let file = url.rsplit('/').next().unwrap_or(url);
+    file.trim_end_matches(".xml").to_string()
+}
+
+/// ECB data portal CSV export: one row per currency and day. Also the
+/// provider's description and capabilities.
+fn sdmx_source() -> SeriesSource {
+    SeriesSource {
+        name: "ecb".to_string(),
+        description: "European Central Bank - Daily EUR reference rates".to_string(),
+        native_base: "EUR".to_string(),
+        native_base_name: "Euro".to_string(),
+        url_template: ECB_SDMX_URL.to_string(),
+        url_date_format: "%Y-%m-%d".to_string(),
+        series_separator: "+".to_string(),
+        format: SeriesFormat::CsvLong {
+            delimiter: b',',
+            date_column: "TIME_PERIOD".to_string(),
+            key_column: "CURRENCY".to_string(),
+            value_column: "OBS_VALUE".to_string(),
+        },
+        date_format: "%Y-%m-%d".to_string(),
+        history_start: NaiveDate::from_ymd_opt(1999, 1, 4).unwrap(),
+        fill_gaps: true,
+        // Reference rates are published around 16:00 CET on TARGET business days
+        publication: PublicationSchedule::business_days(Some((16, 0)), "Europe/Berlin"),
+        attribution: Some(
+            "Source: European Central Bank (ECB) euro foreign exchange reference rates".to_string(),
+        ),
+        series: Vec::new(),
+    }
 }
 
 impl Default for EcbProvider {
@@ -126,7 +340,11 @@ impl Provider for EcbProvider {
     }
 
     fn description(&self) -> &str {
-        "European Central Bank - Daily EUR reference rates"
+        self.sdmx.description()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.sdmx.capabilities()
     }
 
     async fn supported_currencies(&self) -> Result<Vec<Currency>> {
@@ -260,7 +478,7 @@ impl Provider for EcbProvider {
     }
 
     async fn fetch_latest(&self) -> Result<DailyRates> {
-        let rates = self.fetch_and_parse(ECB_DAILY_URL).await?;
+        let rates = self.fetch_xml_range(ECB_DAILY_URL, None, None).await?;
         rates
             .into_iter()
             .next()
@@ -268,37 +486,50 @@ impl Provider for EcbProvider {
     }
 
     async fn fetch_date(&self, date: NaiveDate) -> Result<DailyRates> {
-        // ECB doesn't support fetching by specific date via URL
-        // We need to fetch historical data and filter
-        let rates = self.fetch_and_parse(ECB_HIST_90D_URL).await?;
-
-        rates
+        self.fetch_range(date, date)
+            .await?
             .into_iter()
             .find(|r| r.date == date)
             .ok_or_else(|| AppError::NoDataAvailable)
     }
 
     async fn fetch_range(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyRates>> {
-        // Determine which endpoint to use based on date range
         let today = chrono::Utc::now().date_naive();
-        let days_ago_90 = today - chrono::Duration::days(90);
+        let days_ago_90 = today - Duration::days(90);
+        let padded_start = start - Duration::days(super::GAP_PADDING_DAYS);
 
-        let url = if start >= days_ago_90 {
-            ECB_HIST_90D_URL
+        let rates = if padded_start >= days_ago_90 {
+            // Small file, revalidated with ETag/If-Modified-Since
+            self.fetch_xml_range(ECB_HIST_90D_URL, Some(padded_start), Some(end))
+                .await?
         } else {
-            ECB_HIST_FULL_URL
+            // Ask the data portal for just this range instead of the full history file
+            match self.fetch_sdmx_range(padded_start, end).await {
+                Ok(rates) => rates,
+                Err(e) => {
+                    tracing::warn!(
+                        "ECB data portal request failed ({}), falling back to the history file",
+                        e
+                    );
+                    self.fetch_xml_range(ECB_HIST_FULL_URL, Some(padded_start), Some(end))
+                        .await?
+                }
+            }
         };
 
-        let all_rates = self.fetch_and_parse(url).await?;
-
-        Ok(all_rates
+        Ok(super::fill_gaps_until(rates, self.name(), end)
             .into_iter()
             .filter(|r| r.date >= start && r.date <= end)
             .collect())
     }
 
     async fn fetch_full_history(&self) -> Result<Vec<DailyRates>> {
-        self.fetch_and_parse(ECB_HIST_FULL_URL).await
+        let rates = self.fetch_xml_range(ECB_HIST_FULL_URL, None, None).await?;
+        Ok(super::fill_gaps(rates, self.name()))
+    }
+
+    fn circuit_status(&self) -> Option<CircuitStatus> {
+        Some(self.http.circuit_status())
     }
 }
 
@@ -306,6 +537,22 @@ impl Provider for EcbProvider {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_capabilities() {
+        let provider = EcbProvider::new();
+        assert_eq!(
+            provider.description(),
+            "European Central Bank - Daily EUR reference rates"
+        );
+        let capabilities = provider.capabilities();
+        assert_eq!(capabilities.native_base, "EUR");
+        assert_eq!(
+            capabilities.history_start,
+            NaiveDate::from_ymd_opt(1999, 1, 4)
+        );
+        assert!(capabilities.attribution.is_some());
+    }
+
     #[test]
     fn test_parse_xml() {
         let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -323,7 +570,7 @@ mod tests {
 </gesmes:Envelope>"#;
 
         let provider = EcbProvider::new();
-        let rates = provider.parse_xml(xml).unwrap();
+        let rates = provider.parse_xml_range(xml, None, None).unwrap();
 
         assert_eq!(rates.len(), 1);
         assert_eq!(
@@ -341,4 +588,79 @@ mod tests {
         let jpy_rate = rates[0].rates.get("JPY").unwrap();
         assert!((jpy_rate - (158.11 / 1.0586)).abs() < 0.01);
     }
+
+    #[test]
+    fn test_parse_xml_range() {
+        let xml = r#"<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+    <Cube>
+        <Cube time="2025-11-28"><Cube currency="USD" rate="1.06"/></Cube>
+        <Cube time="2025-11-27"><Cube currency="USD" rate="1.05"/><Cube currency="JPY" rate="158.11"/></Cube>
+        <Cube time="2025-11-26"><Cube currency="USD" rate="1.04"/></Cube>
+        <Cube time="2025-11-25"><Cube currency="USD" rate="1.03"/></Cube>
+        <Cube time="not a date"/>
+    </Cube>
+</gesmes:Envelope>"#;
+
+        let provider = EcbProvider::new();
+        let day = |d| NaiveDate::from_ymd_opt(2025, 11, d).unwrap();
+
+        // Stops at 2025-11-25 without reaching the malformed entry after it
+        let rates = provider
+            .parse_xml_range(xml, Some(day(26)), Some(day(27)))
+            .unwrap();
+        let dates: Vec<_> = rates.iter().map(|r| r.date).collect();
+        assert_eq!(dates, vec![day(27), day(26)]);
+        assert_eq!(rates[0].rates.len(), 3);
+        assert!((rates[1].rates["EUR"] - 1.0 / 1.04).abs() < 1e-9);
+
+        assert!(provider.parse_xml_range(xml, None, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_conditional_request_uses_cache() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let xml = r#"<Envelope><Cube><Cube time="2025-11-27"><Cube currency="USD" rate="1.05"/></Cube></Cube></Envelope>"#;
+
+        // Serves the document with an ETag, then answers 304 only if it is sent back
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/eurofxref-daily.xml",
+            listener.local_addr().unwrap()
+        );
+        let first = format!(
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n{}",
+            xml.len(),
+            xml
+        );
+        tokio::spawn(async move {
+            for i in 0..2 {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 2048];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let response = if i == 0 {
+                    first.clone()
+                } else if request.contains("if-none-match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".to_string()
+                } else {
+                    "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n".to_string()
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+
+        let dir = std::env::temp_dir().join(format!("ecb-cache-test-{}", std::process::id()));
+        let provider = EcbProvider::new().with_cache_dir(&dir);
+        assert_eq!(provider.fetch_document(&url).await.unwrap(), xml);
+        assert!(dir.join("eurofxref-daily.xml").exists());
+
+        // A fresh instance revalidates the copy stored on disk
+        let provider = EcbProvider::new().with_cache_dir(&dir);
+        let rates = provider.fetch_xml_range(&url, None, None).await.unwrap();
+        assert_eq!(rates.len(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
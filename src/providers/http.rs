@@ -0,0 +1,469 @@
+use chrono::{DateTime, Utc};
+use reqwest::{RequestBuilder, Response, StatusCode, header::RETRY_AFTER};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::Instrument;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, Result};
+use crate::providers::limiter;
+
+/// How failed requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles with every attempt
+    pub base_delay: Duration,
+    /// Upper bound for a single delay. A `Retry-After` asking for longer
+    /// than this is not waited for.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter: a random delay between half and all
+    /// of `base_delay * 2^attempt`, capped at `max_delay`
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = exp / 2;
+        half + Duration::from_millis(fastrand::u64(0..=half.as_millis() as u64))
+    }
+}
+
+/// When the circuit breaker opens and for how long
+#[derive(Debug, Clone)]
+pub struct BreakerPolicy {
+    /// Consecutive failed requests (after retries) that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a probe request is allowed
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(300),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests go through
+    Closed,
+    /// Requests fail fast until the cooldown ends
+    Open,
+    /// Cooldown ended, the next request decides whether the circuit closes
+    HalfOpen,
+}
+
+/// Circuit breaker state reported in /health
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_until: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    /// Set while the circuit is open
+    open_until: Option<(Instant, DateTime<Utc>)>,
+    /// A half-open probe request is in flight
+    probing: bool,
+    last_error: Option<String>,
+}
+
+/// HTTP client shared by providers: retries transient failures with
+/// jittered exponential backoff, honors `Retry-After`, and stops calling a
+/// failing source for a while once its circuit opens. Every attempt waits
+/// for the per-host rate limiter.
+pub struct HttpClient {
+    name: String,
+    client: reqwest::Client,
+    retry: RetryPolicy,
+    breaker_policy: BreakerPolicy,
+    breaker: Mutex<Breaker>,
+}
+
+impl HttpClient {
+    /// Client for the provider `name` with default policies
+    pub fn new(name: &str) -> Self {
+        Self::with_policies(name, RetryPolicy::default(), BreakerPolicy::default())
+    }
+
+    pub fn with_policies(name: &str, retry: RetryPolicy, breaker_policy: BreakerPolicy) -> Self {
+        Self {
+            name: name.to_string(),
+            client: reqwest::Client::new(),
+            retry,
+            breaker_policy,
+            breaker: Mutex::new(Breaker::default()),
+        }
+    }
+
+    /// GET a URL, failing on non-success status codes
+    pub async fn get(&self, url: &str) -> Result<Response> {
+        self.send(|client| client.get(url)).await
+    }
+
+    pub async fn get_text(&self, url: &str) -> Result<String> {
+        Ok(self.get(url).await?.text().await?)
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let response = self.get(url).await?;
+        let span = tracing::info_span!("parse", provider = %self.name, format = "json");
+        Ok(response.json().instrument(span).await?)
+    }
+
+    /// Send a request built by `build` (called again for every attempt)
+    #[tracing::instrument(
+        name = "http_request",
+        skip_all,
+        fields(provider = %self.name, method, url, status, retries)
+    )]
+    pub async fn send<F>(&self, build: F) -> Result<Response>
+    where
+        F: Fn(&reqwest::Client) -> RequestBuilder,
+    {
+        let _probe = self.acquire()?;
+
+        let mut attempt = 0;
+        loop {
+            // A request that cannot be built says nothing about the source's health
+            let request = build(&self.client).build().map_err(AppError::Request)?;
+            if attempt == 0 {
+                let span = tracing::Span::current();
+                span.record("method", request.method().as_str());
+                span.record("url", request.url().as_str());
+            }
+            if let Some(host) = request.url().host_str() {
+                limiter::acquire(host).await;
+            }
+
+            let response = self.client.execute(request).await;
+            if let Ok(response) = &response {
+                let span = tracing::Span::current();
+                span.record("status", response.status().as_u16());
+                span.record("retries", attempt);
+            }
+            let (error, retry_after) = match response {
+                Ok(response) if response.status().is_success() => {
+                    self.record_success();
+                    return Ok(response);
+                }
+                Ok(response) if is_retryable_status(response.status()) => {
+                    let retry_after = parse_retry_after(&response);
+                    let status = response.status();
+                    let error = response
+                        .error_for_status()
+                        .err()
+                        .map(AppError::Request)
+                        .unwrap_or_else(|| AppError::Provider(format!("HTTP {}", status)));
+                    (error, retry_after)
+                }
+                Ok(response) => {
+                    // Client errors are not transient and say nothing about the source's health
+                    self.record_success();
+                    return response.error_for_status().map_err(AppError::Request);
+                }
+                Err(e) if e.is_timeout() || e.is_connect() || e.is_request() => {
+                    (AppError::Request(e), None)
+                }
+                // Neither a success nor a transient failure of the source
+                Err(e) => return Err(AppError::Request(e)),
+            };
+
+            if attempt >= self.retry.max_retries {
+                self.record_failure(&error);
+                return Err(error);
+            }
+
+            let delay = match retry_after {
+                Some(wait) if wait > self.retry.max_delay => {
+                    tracing::warn!(
+                        "{}: Retry-After of {}s exceeds the retry limit, giving up",
+                        self.name,
+                        wait.as_secs()
+                    );
+                    self.record_failure(&error);
+                    return Err(error);
+                }
+                Some(wait) => wait,
+                None => self.retry.backoff(attempt),
+            };
+
+            attempt += 1;
+            tracing::warn!(
+                "{}: request failed ({}), retry {}/{} in {:?}",
+                self.name,
+                error,
+                attempt,
+                self.retry.max_retries,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Current circuit breaker state
+    pub fn circuit_status(&self) -> CircuitStatus {
+        let breaker = self.breaker.lock().unwrap();
+        let state = match breaker.open_until {
+            Some((until, _)) if Instant::now() < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+            None => CircuitState::Closed,
+        };
+
+        CircuitStatus {
+            state,
+            consecutive_failures: breaker.consecutive_failures,
+            open_until: breaker.open_until.map(|(_, at)| at),
+            last_error: breaker.last_error.clone(),
+        }
+    }
+
+    /// Fail fast while the circuit is open; let a single probe through once
+    /// the cooldown has passed
+    fn acquire(&self) -> Result<Probe<'_>> {
+        let mut breaker = self.breaker.lock().unwrap();
+        match breaker.open_until {
+            None => Ok(Probe {
+                client: self,
+                active: false,
+            }),
+            Some((until, at)) if Instant::now() < until => Err(AppError::Unavailable(format!(
+                "{} circuit is open until {}",
+                self.name,
+                at.to_rfc3339()
+            ))),
+            Some(_) if breaker.probing => Err(AppError::Unavailable(format!(
+                "{} circuit is half-open, probe in progress",
+                self.name
+            ))),
+            Some(_) => {
+                breaker.probing = true;
+                Ok(Probe {
+                    client: self,
+                    active: true,
+                })
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        let mut breaker = self.breaker.lock().unwrap();
+        if breaker.open_until.is_some() {
+            tracing::info!("{}: circuit closed", self.name);
+        }
+        *breaker = Breaker::default();
+    }
+
+    fn record_failure(&self, error: &AppError) {
+        let mut breaker = self.breaker.lock().unwrap();
+        breaker.consecutive_failures += 1;
+        breaker.last_error = Some(error.to_string());
+
+        if breaker.probing || breaker.consecutive_failures >= self.breaker_policy.failure_threshold
+        {
+            let cooldown = self.breaker_policy.cooldown;
+            let until = Utc::now() + chrono::Duration::from_std(cooldown).unwrap_or_default();
+            breaker.open_until = Some((Instant::now() + cooldown, until));
+            breaker.probing = false;
+            tracing::error!(
+                "{}: circuit opened after {} consecutive failures, until {}",
+                self.name,
+                breaker.consecutive_failures,
+                until.to_rfc3339()
+            );
+        }
+    }
+}
+
+/// Request admitted by [`HttpClient::acquire`]. A half-open probe that ends
+/// without recording a success or failure (dropped, or an error unrelated to
+/// the source) gives the probe slot back.
+struct Probe<'a> {
+    client: &'a HttpClient,
+    active: bool,
+}
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        if self.active {
+            if let Ok(mut breaker) = self.client.breaker.lock() {
+                breaker.probing = false;
+            }
+        }
+    }
+}
+
+/// Rate limiting and server-side errors are worth retrying
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+/// `Retry-After` as either delay-seconds or an HTTP date
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    retry_after_delay(value, SystemTime::now())
+}
+
+fn retry_after_delay(value: &str, now: SystemTime) -> Option<Duration> {
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(at.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_bounds() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        };
+
+        for _ in 0..50 {
+            let first = policy.backoff(0);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let capped = policy.backoff(10);
+            assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let now = httpdate::parse_http_date("Wed, 04 Jun 2025 12:00:00 GMT").unwrap();
+        assert_eq!(
+            retry_after_delay("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after_delay("Wed, 04 Jun 2025 12:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after_delay("Wed, 04 Jun 2025 11:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after_delay("soon", now), None);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        let client = HttpClient::with_policies(
+            "test",
+            RetryPolicy::default(),
+            BreakerPolicy {
+                failure_threshold: 2,
+                cooldown: Duration::from_millis(50),
+            },
+        );
+        let error = AppError::Provider("boom".to_string());
+
+        client.record_failure(&error);
+        assert_eq!(client.circuit_status().state, CircuitState::Closed);
+        client.record_failure(&error);
+        assert_eq!(client.circuit_status().state, CircuitState::Open);
+        assert!(matches!(client.acquire(), Err(AppError::Unavailable(_))));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(client.circuit_status().state, CircuitState::HalfOpen);
+        // Only one probe at a time, until it is dropped without an outcome
+        let probe = client.acquire().unwrap();
+        assert!(client.acquire().is_err());
+        drop(probe);
+        let probe = client.acquire().unwrap();
+
+        // A failed probe reopens the circuit, a successful one closes it
+        client.record_failure(&error);
+        drop(probe);
+        assert_eq!(client.circuit_status().state, CircuitState::Open);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let _probe = client.acquire().unwrap();
+        client.record_success();
+        let status = client.circuit_status();
+        assert_eq!(status.state, CircuitState::Closed);
+        assert_eq!(status.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_local_errors_keep_breaker_state() {
+        let client = HttpClient::with_policies(
+            "test",
+            RetryPolicy::default(),
+            BreakerPolicy {
+                failure_threshold: 1,
+                cooldown: Duration::from_millis(50),
+            },
+        );
+        client.record_failure(&AppError::Provider("boom".to_string()));
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        // The probe fails before reaching the source: the circuit stays
+        // half-open and the next request may probe
+        assert!(matches!(
+            client.get("not a url").await,
+            Err(AppError::Request(_))
+        ));
+        let status = client.circuit_status();
+        assert_eq!(status.state, CircuitState::HalfOpen);
+        assert_eq!(status.consecutive_failures, 1);
+        assert!(client.acquire().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Responds 503 with Retry-After once, then 200
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            for response in [
+                "HTTP/1.1 503 Service Unavailable\r\nRetry-After: 0\r\nContent-Length: 0\r\n\r\n",
+                "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+            ] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await.unwrap();
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+
+        let client = HttpClient::new("test");
+        assert_eq!(client.get_text(&url).await.unwrap(), "ok");
+        assert_eq!(client.circuit_status().consecutive_failures, 0);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Process-wide limits for provider HTTP traffic
+#[derive(Debug, Clone, Copy)]
+pub struct FetchLimits {
+    /// Requests a single provider runs in parallel
+    pub concurrency: usize,
+    /// Sustained requests per second allowed to each host (0 = unlimited)
+    pub requests_per_second: f64,
+    /// Requests allowed in a burst before the sustained rate applies
+    pub burst: u32,
+}
+
+impl Default for FetchLimits {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            requests_per_second: 5.0,
+            burst: 5,
+        }
+    }
+}
+
+static LIMITS: OnceLock<FetchLimits> = OnceLock::new();
+static BUCKETS: OnceLock<Mutex<HashMap<String, Arc<TokenBucket>>>> = OnceLock::new();
+
+/// Set the limits once at startup. Returns false if they were already set.
+pub fn configure(limits: FetchLimits) -> bool {
+    LIMITS.set(limits).is_ok()
+}
+
+/// Current limits (defaults unless configured)
+pub fn limits() -> FetchLimits {
+    LIMITS.get().copied().unwrap_or_default()
+}
+
+/// Parallel requests a provider should run
+pub fn concurrency() -> usize {
+    limits().concurrency.max(1)
+}
+
+/// Wait for a request slot for `host`. All providers share one bucket per host.
+pub async fn acquire(host: &str) {
+    let limits = limits();
+    if limits.requests_per_second <= 0.0 {
+        return;
+    }
+
+    let bucket = {
+        let mut buckets = BUCKETS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap();
+        buckets
+            .entry(host.to_lowercase())
+            .or_insert_with(|| Arc::new(TokenBucket::new(limits.requests_per_second, limits.burst)))
+            .clone()
+    };
+
+    bucket.acquire().await;
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Longest wait reported by a token bucket, whatever its rate
+const MAX_WAIT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Token bucket: holds up to `capacity` tokens, refilled at `rate` per second
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, burst: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        Self {
+            rate,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token, waiting until one is available
+    pub async fn acquire(&self) {
+        while let Some(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token if available, otherwise return how long until one is
+    pub fn try_acquire(&self) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.rate).min(self.capacity);
+        state.refilled_at = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            None
+        } else {
+            Some(self.refill_time(1.0 - state.tokens))
+        }
+    }
+
+    /// Whole tokens available now and the time until the bucket is full again
+    pub fn available(&self) -> (u32, Duration) {
+        let tokens = self.tokens();
+        (
+            tokens.floor() as u32,
+            self.refill_time(self.capacity - tokens),
+        )
+    }
+
+    /// Time until a token is available, without taking it
+    pub fn wait(&self) -> Option<Duration> {
+        let tokens = self.tokens();
+        (tokens < 1.0).then(|| self.refill_time(1.0 - tokens))
+    }
+
+    fn tokens(&self) -> f64 {
+        let state = self.state.lock().unwrap();
+        let elapsed = state.refilled_at.elapsed().as_secs_f64();
+        (state.tokens + elapsed * self.rate).min(self.capacity)
+    }
+
+    /// Time to refill `tokens`, at most `MAX_WAIT` (the rate can be tiny or zero)
+    fn refill_time(&self, tokens: f64) -> Duration {
+        if tokens <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::try_from_secs_f64(tokens / self.rate).map_or(MAX_WAIT, |wait| wait.min(MAX_WAIT))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_token_bucket() {
+        let bucket = TokenBucket::new(20.0, 2);
+
+        // Burst is served immediately, then requests are spaced at the rate
+        assert!(bucket.try_acquire().is_none());
+        assert!(bucket.try_acquire().is_none());
+        let wait = bucket.try_acquire().unwrap();
+        assert!(wait <= Duration::from_millis(50));
+
+        let start = Instant::now();
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(80));
+
+        // Waits are capped instead of overflowing
+        let stalled = TokenBucket::new(1e-300, 1);
+        assert!(stalled.try_acquire().is_none());
+        assert_eq!(stalled.try_acquire(), Some(MAX_WAIT));
+        assert_eq!(stalled.available(), (0, MAX_WAIT));
+        assert_eq!(TokenBucket::new(0.0, 1).available().1, Duration::ZERO);
+    }
+}
@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use crate::db::RatesRepository;
+use crate::error::{AppError, Result};
+use crate::models::{Currency, DailyRates};
+use crate::providers::{Provider, ProviderCapabilities, PublicationSchedule};
+
+/// Provider name of manually entered rates
+pub const MANUAL_PROVIDER: &str = "manual";
+
+/// Rates entered through the admin API, kept in the `manual_rates` table.
+///
+/// Manual rates are not synced into the stored provider rates: they are
+/// applied on top of them at query time when overrides are requested, so
+/// there is nothing to fetch. The provider is registered (disabled) so it is
+/// listed with the others.
+pub struct ManualProvider {
+    repository: RatesRepository,
+}
+
+impl ManualProvider {
+    pub fn new(repository: RatesRepository) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Provider for ManualProvider {
+    fn name(&self) -> &str {
+        MANUAL_PROVIDER
+    }
+
+    fn description(&self) -> &str {
+        "Manual override rates entered through the admin API"
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_base: "USD".to_string(),
+            history_start: None,
+            // Entered whenever needed
+            publication: PublicationSchedule::business_days(None, "UTC"),
+            supports_range: true,
+            attribution: None,
+        }
+    }
+
+    async fn supported_currencies(&self) -> Result<Vec<Currency>> {
+        Ok(self
+            .repository
+            .get_manual_currencies()
+            .await?
+            .into_iter()
+            .map(|code| Currency {
+                name: code.clone(),
+                code,
+            })
+            .collect())
+    }
+
+    async fn fetch_latest(&self) -> Result<DailyRates> {
+        Err(AppError::NoDataAvailable)
+    }
+
+    async fn fetch_date(&self, _date: NaiveDate) -> Result<DailyRates> {
+        Err(AppError::NoDataAvailable)
+    }
+
+    async fn fetch_range(&self, _start: NaiveDate, _end: NaiveDate) -> Result<Vec<DailyRates>> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_full_history(&self) -> Result<Vec<DailyRates>> {
+        Ok(Vec::new())
+    }
+}
@@ -1,16 +1,147 @@
+mod boe;
+mod configurable;
+mod derived;
 mod ecb;
+pub mod http;
+pub mod limiter;
+mod manual;
 mod nbu;
 mod provider;
+pub mod sdmx;
+mod snb;
 
+pub use boe::BoeProvider;
+pub use configurable::{ConfigurableProvider, ConfigurableProviderDef};
+pub use derived::{DERIVED_PROVIDER, DerivedProvider, PegDefinition};
 pub use ecb::EcbProvider;
-pub use nbu::NbuProvider;
-pub use provider::{Provider, ProviderRegistry};
-
+pub use manual::{MANUAL_PROVIDER, ManualProvider};
+pub use nbu::{NbuCurrencyFilter, NbuProvider};
+pub use provider::{Provider, ProviderCapabilities, ProviderRegistry, PublicationSchedule};
+pub use sdmx::SeriesProvider;
+pub use snb::SnbProvider;
+
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::{Config, ProviderSettings};
+use crate::db::RatesRepository;
+use crate::error::{AppError, Result};
 use crate::models::DailyRates;
 
+/// Register all built-in and configured providers with their settings.
+/// All providers store rates with USD as the internal base currency.
+/// Disabled providers stay registered so their stored data is still served.
+pub fn build_registry(config: &Config, repository: &RatesRepository) -> Result<ProviderRegistry> {
+    let mut providers = ProviderRegistry::new();
+
+    let mut ecb = EcbProvider::new();
+    if let Some(dir) = &config.ecb_cache_dir {
+        ecb = ecb.with_cache_dir(dir);
+    }
+    providers.register_with_settings(ecb, ProviderSettings::from_env("ecb", true))?;
+
+    let nbu_filter = NbuCurrencyFilter::new(
+        config.nbu_include.clone(),
+        config
+            .nbu_exclude
+            .clone()
+            .unwrap_or_else(NbuCurrencyFilter::default_excluded),
+    )
+    .with_reference(
+        config
+            .nbu_reference
+            .clone()
+            .unwrap_or_else(NbuCurrencyFilter::default_reference),
+    );
+    providers.register_with_settings(
+        NbuProvider::new()
+            .with_filter(nbu_filter)
+            .with_repository(repository.clone()),
+        ProviderSettings::from_env("nbu", true),
+    )?;
+    providers
+        .register_with_settings(BoeProvider::new(), ProviderSettings::from_env("boe", false))?;
+    providers
+        .register_with_settings(SnbProvider::new(), ProviderSettings::from_env("snb", false))?;
+    // Applied at query time, never synced
+    providers.register_with_settings(
+        ManualProvider::new(repository.clone()),
+        ProviderSettings::new(false),
+    )?;
+
+    if let Some(path) = &config.providers_config {
+        for provider in ConfigurableProvider::load_file(Path::new(path))? {
+            tracing::info!(
+                "Loaded configurable provider {} from {}",
+                provider.name(),
+                path
+            );
+            // Registered below, after the configured providers
+            if provider.name() == DERIVED_PROVIDER {
+                return Err(AppError::Config(format!(
+                    "Provider {} is reserved for derived rates",
+                    DERIVED_PROVIDER
+                )));
+            }
+            let settings = ProviderSettings::from_env(provider.name(), true);
+            providers.register_with_settings(provider, settings)?;
+        }
+    }
+
+    // Derived pegs are computed from stored anchor rates, so they sync last
+    let pegs = DerivedProvider::load_pegs(config.pegs_config.as_deref().map(Path::new))?;
+    providers.register_with_settings(
+        DerivedProvider::new(repository.clone(), pegs),
+        ProviderSettings::from_env("derived", true),
+    )?;
+
+    Ok(providers)
+}
+
+/// Convert rates quoted against a native base ("units of XXX per 1 native base")
+/// into USD-based rates: USD/XXX = NATIVE/XXX / NATIVE/USD.
+/// Returns None if the native/USD rate is missing.
+pub fn rebase_to_usd(
+    native_base: &str,
+    mut native_rates: HashMap<String, f64>,
+) -> Option<HashMap<String, f64>> {
+    native_rates.insert(native_base.to_string(), 1.0);
+
+    // How many USD per 1 native base
+    let native_usd = *native_rates.get("USD")?;
+
+    Some(
+        native_rates
+            .into_iter()
+            .map(|(currency, rate)| {
+                if currency == "USD" {
+                    (currency, 1.0)
+                } else {
+                    (currency, rate / native_usd)
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Days fetched before the requested start of a range so weekends/holidays
+/// at its start can be filled from the previous business day
+pub const GAP_PADDING_DAYS: i64 = 7;
+
 /// Fill gaps in rate data (weekends, holidays) with the previous available day's rates.
 /// Useful for providers like ECB that don't publish on weekends.
-pub fn fill_gaps(mut rates: Vec<DailyRates>, provider_name: &str) -> Vec<DailyRates> {
+pub fn fill_gaps(rates: Vec<DailyRates>, provider_name: &str) -> Vec<DailyRates> {
+    fill_gaps_until(rates, provider_name, chrono::Utc::now().date_naive())
+}
+
+/// Like [`fill_gaps`], but only fill after the last rate up to `until`
+/// (never past today), e.g. the end of a fetched range
+pub fn fill_gaps_until(
+    mut rates: Vec<DailyRates>,
+    provider_name: &str,
+    until: NaiveDate,
+) -> Vec<DailyRates> {
     if rates.is_empty() {
         return rates;
     }
@@ -31,6 +162,7 @@ pub fn fill_gaps(mut rates: Vec<DailyRates>, provider_name: &str) -> Vec<DailyRa
                     base_currency: prev.base_currency.clone(),
                     rates: prev.rates.clone(),
                     provider: provider_name.to_string(),
+                    estimated: true,
                 });
                 fill_date += chrono::Duration::days(1);
             }
@@ -40,19 +172,20 @@ pub fn fill_gaps(mut rates: Vec<DailyRates>, provider_name: &str) -> Vec<DailyRa
         prev_rates = Some(&rates[i]);
     }
 
-    // Fill up to today if the last rate is not today
+    // Fill up to `until` if the last rate is earlier
     if let Some(last) = filled.last() {
-        let today = chrono::Utc::now().date_naive();
+        let until = until.min(chrono::Utc::now().date_naive());
         let mut fill_date = last.date + chrono::Duration::days(1);
         let last_rates = last.rates.clone();
         let last_base = last.base_currency.clone();
 
-        while fill_date <= today {
+        while fill_date <= until {
             filled.push(DailyRates {
                 date: fill_date,
                 base_currency: last_base.clone(),
                 rates: last_rates.clone(),
                 provider: provider_name.to_string(),
+                estimated: true,
             });
             fill_date += chrono::Duration::days(1);
         }
@@ -64,8 +197,22 @@ pub fn fill_gaps(mut rates: Vec<DailyRates>, provider_name: &str) -> Vec<DailyRa
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::NaiveDate;
-    use std::collections::HashMap;
+
+    #[test]
+    fn test_rebase_to_usd() {
+        let mut native = HashMap::new();
+        native.insert("USD".to_string(), 1.25);
+        native.insert("JPY".to_string(), 190.0);
+
+        let usd = rebase_to_usd("GBP", native).unwrap();
+
+        assert_eq!(usd.get("USD"), Some(&1.0));
+        assert!((usd.get("GBP").unwrap() - 0.8).abs() < 1e-9);
+        assert!((usd.get("JPY").unwrap() - 152.0).abs() < 1e-9);
+
+        // Missing native/USD rate
+        assert!(rebase_to_usd("GBP", HashMap::new()).is_none());
+    }
 
     fn make_daily_rates(date: NaiveDate, rate_value: f64) -> DailyRates {
         let mut rates = HashMap::new();
@@ -76,6 +223,7 @@ mod tests {
             base_currency: "USD".to_string(),
             rates,
             provider: "test".to_string(),
+            estimated: false,
         }
     }
 
@@ -141,6 +289,18 @@ mod tests {
         assert_eq!(result[3].rates.get("EUR"), Some(&1.1));
     }
 
+    #[test]
+    fn test_fill_gaps_until() {
+        let friday = NaiveDate::from_ymd_opt(2020, 1, 3).unwrap();
+        let sunday = NaiveDate::from_ymd_opt(2020, 1, 5).unwrap();
+
+        let result = fill_gaps_until(vec![make_daily_rates(friday, 1.0)], "test", sunday);
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[2].date, sunday);
+        assert!(result[2].estimated);
+    }
+
     #[test]
     fn test_fill_gaps_unsorted_input() {
         // Input is not sorted - should be sorted first
@@ -220,12 +380,14 @@ mod tests {
                 base_currency: "GBP".to_string(),
                 rates: rates.clone(),
                 provider: "test".to_string(),
+                estimated: false,
             },
             DailyRates {
                 date: NaiveDate::from_ymd_opt(2020, 1, 3).unwrap(),
                 base_currency: "GBP".to_string(),
                 rates,
                 provider: "test".to_string(),
+                estimated: false,
             },
         ];
 
@@ -1,33 +1,130 @@
 use async_trait::async_trait;
 use chrono::NaiveDate;
+use futures::stream::{self, StreamExt};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::Mutex;
 
+use crate::db::RatesRepository;
 use crate::error::{AppError, Result};
-use crate::models::{Currency, DailyRates};
-use crate::providers::Provider;
+use crate::models::{Currency, DailyRates, ProviderCurrency};
+use crate::providers::http::{CircuitStatus, HttpClient};
+use crate::providers::limiter;
+use crate::providers::{Provider, ProviderCapabilities, PublicationSchedule};
 
 const NBU_BASE_URL: &str = "https://bank.gov.ua/NBUStatService/v1/statdirectory/exchange";
 const NBU_BATCH_URL: &str = "https://bank.gov.ua/NBU_Exchange/exchange_site";
 
-/// Currencies to fetch from NBU (base currency is added dynamically)
-/// These are regional/unique currencies best sourced from NBU
-const NBU_CURRENCIES: &[&str] = &[
-    "KZT", // Kazakhstani Tenge
-    "LBP", // Lebanese Pound
-    "MDL", // Moldovan Leu
-    "SAR", // Saudi Riyal
-    "VND", // Vietnamese Dong
-    "EGP", // Egyptian Pound
-    "GEL", // Georgian Lari
-           // "DZD", // Algerian Dinar. From 2016-11-01
-           // "BDT", // Bangladeshi Taka. From 2016-11-01
-           // "AED", // UAE Dirham. From 2016-11-01
-           // "TND", // Tunisian Dinar. From 2016-11-01
-           // "RSD", // Serbian Dinar. From 2016-11-01
-           // "AZN", // Azerbaijani Manat. From 2014-04-04
+/// Name under which NBU rates and catalog entries are stored
+const NBU_NAME: &str = "nbu";
+
+/// Earliest date requested for full history (same as ECB start date)
+const NBU_HISTORY_START: (i32, u32, u32) = (1999, 1, 4);
+
+/// Currencies published by ECB. NBU skips them by default so each currency
+/// keeps a single source; set `NBU_EXCLUDE` to override.
+const DEFAULT_EXCLUDED: &[&str] = &[
+    "EUR", "JPY", "BGN", "CZK", "DKK", "GBP", "HUF", "PLN", "RON", "SEK", "CHF", "ISK", "NOK",
+    "TRY", "AUD", "BRL", "CAD", "CNY", "HKD", "IDR", "ILS", "INR", "KRW", "MXN", "MYR", "NZD",
+    "PHP", "SGD", "THB", "ZAR",
+];
+
+/// Codes NBU publishes that are not currencies: precious metals and the IMF
+/// SDR basket. Only fetched when listed in `NBU_INCLUDE`.
+const NON_CURRENCIES: &[&str] = &["XAU", "XAG", "XPT", "XPD", "XDR"];
+
+/// Excluded currencies NBU still fetches to compare with the providers that
+/// serve them (see `crate::reconciliation`); set `NBU_REFERENCE` to override.
+const DEFAULT_REFERENCE: &[&str] = &["EUR", "GBP", "CHF", "JPY"];
+
+/// First publication dates known up front, used until a full-history fetch
+/// records the real ones
+const KNOWN_FIRST_DATES: &[(&str, (i32, u32, u32))] = &[
+    ("DZD", (2016, 11, 1)), // Algerian Dinar
+    ("BDT", (2016, 11, 1)), // Bangladeshi Taka
+    ("AED", (2016, 11, 1)), // UAE Dirham
+    ("TND", (2016, 11, 1)), // Tunisian Dinar
+    ("RSD", (2016, 11, 1)), // Serbian Dinar
+    ("AZN", (2014, 4, 4)),  // Azerbaijani Manat
 ];
 
+/// Which of the currencies published by NBU to fetch.
+/// USD is always fetched because it is needed for conversion.
+#[derive(Debug, Clone)]
+pub struct NbuCurrencyFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    /// Fetched even when not allowed, kept for reconciliation only
+    reference: Vec<String>,
+}
+
+impl NbuCurrencyFilter {
+    /// An empty include list means "every currency not excluded", which
+    /// leaves out metals and XDR
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        let upper = |v: Vec<String>| v.into_iter().map(|c| c.to_uppercase()).collect();
+        Self {
+            include: upper(include),
+            exclude: upper(exclude),
+            reference: Vec::new(),
+        }
+    }
+
+    /// Also fetch these currencies when they are not allowed, to compare
+    /// them with other providers without serving them
+    pub fn with_reference(mut self, reference: Vec<String>) -> Self {
+        self.reference = reference.into_iter().map(|c| c.to_uppercase()).collect();
+        self
+    }
+
+    /// Currencies excluded when no exclude list is configured
+    pub fn default_excluded() -> Vec<String> {
+        DEFAULT_EXCLUDED.iter().map(|c| c.to_string()).collect()
+    }
+
+    /// Reference currencies used when none are configured
+    pub fn default_reference() -> Vec<String> {
+        DEFAULT_REFERENCE.iter().map(|c| c.to_string()).collect()
+    }
+
+    pub fn allows(&self, code: &str) -> bool {
+        if code.eq_ignore_ascii_case(INTERNAL_BASE) {
+            return true;
+        }
+        let code = code.to_uppercase();
+        let included = if self.include.is_empty() {
+            !NON_CURRENCIES.contains(&code.as_str())
+        } else {
+            self.include.contains(&code)
+        };
+        included && !self.exclude.contains(&code)
+    }
+
+    /// Fetched for reconciliation only
+    pub fn is_reference(&self, code: &str) -> bool {
+        !self.allows(code) && self.reference.contains(&code.to_uppercase())
+    }
+
+    /// Allowed or reference currency
+    pub fn fetches(&self, code: &str) -> bool {
+        self.allows(code) || self.is_reference(code)
+    }
+}
+
+impl Default for NbuCurrencyFilter {
+    fn default() -> Self {
+        Self::new(Vec::new(), Self::default_excluded()).with_reference(Self::default_reference())
+    }
+}
+
+/// Catalog entry: known publication dates and whether the currency is still
+/// listed on the daily endpoint
+#[derive(Debug, Clone)]
+struct CatalogEntry {
+    currency: ProviderCurrency,
+    active: bool,
+}
+
 /// NBU API response structure for single date
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -60,23 +157,351 @@ struct NbuBatchRate {
 const INTERNAL_BASE: &str = "USD";
 
 /// National Bank of Ukraine provider
-/// Fetches UAH-based rates and converts to USD for internal storage
+/// Fetches UAH-based rates and converts to USD for internal storage.
+/// The currency list is discovered from the daily endpoint; batches are only
+/// requested over each currency's known publication range.
 pub struct NbuProvider {
-    client: reqwest::Client,
+    http: HttpClient,
+    filter: NbuCurrencyFilter,
+    /// Persists the catalog between runs when set
+    repository: Option<RatesRepository>,
+    catalog: Mutex<HashMap<String, CatalogEntry>>,
 }
 
 impl NbuProvider {
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::new(),
+            http: HttpClient::new(NBU_NAME),
+            filter: NbuCurrencyFilter::default(),
+            repository: None,
+            catalog: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Restrict which currencies are fetched
+    pub fn with_filter(mut self, filter: NbuCurrencyFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Keep the currency catalog (first/last publication dates) in the database
+    pub fn with_repository(mut self, repository: RatesRepository) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    fn history_start() -> NaiveDate {
+        let (y, m, d) = NBU_HISTORY_START;
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn known_first_date(code: &str) -> Option<NaiveDate> {
+        KNOWN_FIRST_DATES
+            .iter()
+            .find(|(c, _)| *c == code)
+            .and_then(|(_, (y, m, d))| NaiveDate::from_ymd_opt(*y, *m, *d))
+    }
+
+    /// Fetch the daily endpoint (latest rates when no date is given)
+    async fn fetch_daily(&self, date: Option<NaiveDate>) -> Result<Vec<NbuRate>> {
+        let url = match date {
+            Some(date) => format!(
+                "{}?date={}&json",
+                NBU_BASE_URL,
+                Self::format_date_for_nbu(date)
+            ),
+            None => format!("{}?json", NBU_BASE_URL),
+        };
+        self.http.get_json(&url).await
+    }
+
+    /// Refresh the catalog from the latest daily rates and return a snapshot.
+    /// Currencies missing from the daily list are kept but marked inactive.
+    async fn discover(&self) -> Result<HashMap<String, CatalogEntry>> {
+        let latest = self.fetch_daily(None).await?;
+        self.record_latest(&latest).await?;
+        Ok(self.catalog.lock().unwrap().clone())
+    }
+
+    /// Merge the latest daily list into the catalog
+    async fn record_latest(&self, latest: &[NbuRate]) -> Result<()> {
+        let stored = match &self.repository {
+            Some(repository) => repository.get_provider_currencies(NBU_NAME).await?,
+            None => Vec::new(),
+        };
+
+        let listed: Vec<ProviderCurrency> = latest
+            .iter()
+            .map(|r| ProviderCurrency {
+                code: r.cc.to_uppercase(),
+                name: r.txt.clone(),
+                first_date: None,
+                last_date: Self::parse_nbu_date(&r.exchangedate).ok(),
+            })
+            .collect();
+
+        {
+            let mut catalog = self.catalog.lock().unwrap();
+            for currency in stored {
+                Self::merge_entry(&mut catalog, currency);
+            }
+            for entry in catalog.values_mut() {
+                entry.active = false;
+            }
+            for currency in &listed {
+                Self::merge_entry(&mut catalog, currency.clone()).active = true;
+            }
+        }
+
+        if let Some(repository) = &self.repository {
+            repository
+                .upsert_provider_currencies(NBU_NAME, &listed)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Record the dates observed in a batch. The first observed date is only
+    /// trusted as the first publication date when the batch covered the whole history.
+    async fn record_observed(
+        &self,
+        window_start: NaiveDate,
+        observed: &HashMap<String, (NaiveDate, NaiveDate)>,
+    ) -> Result<()> {
+        let full_history = window_start <= Self::history_start();
+
+        let updates: Vec<ProviderCurrency> = {
+            let mut catalog = self.catalog.lock().unwrap();
+            observed
+                .iter()
+                .map(|(code, &(first, last))| {
+                    let name = catalog
+                        .get(code)
+                        .map(|e| e.currency.name.clone())
+                        .unwrap_or_else(|| code.clone());
+                    let currency = ProviderCurrency {
+                        code: code.clone(),
+                        name,
+                        first_date: full_history.then_some(first),
+                        last_date: Some(last),
+                    };
+                    Self::merge_entry(&mut catalog, currency.clone());
+                    currency
+                })
+                .collect()
+        };
+
+        if let Some(repository) = &self.repository {
+            repository
+                .upsert_provider_currencies(NBU_NAME, &updates)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert a catalog entry or widen the known dates of an existing one
+    fn merge_entry(
+        catalog: &mut HashMap<String, CatalogEntry>,
+        currency: ProviderCurrency,
+    ) -> &mut CatalogEntry {
+        let entry = catalog
+            .entry(currency.code.clone())
+            .or_insert_with(|| CatalogEntry {
+                currency: ProviderCurrency {
+                    first_date: None,
+                    last_date: None,
+                    ..currency.clone()
+                },
+                active: false,
+            });
+
+        let known = &mut entry.currency;
+        known.first_date = match (known.first_date, currency.first_date) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        known.last_date = match (known.last_date, currency.last_date) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+
+        entry
+    }
+
+    /// Fetch per-currency batches for `start..=end`, optionally limited to `only`
+    /// (USD is always fetched, it is needed to convert the others)
+    async fn fetch_batches(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        only: Option<&[String]>,
+    ) -> Result<Vec<DailyRates>> {
+        let catalog = self.discover().await?;
+        let mut batches = self.plan_batches(&catalog, start, end);
+        if let Some(only) = only {
+            batches.retain(|(code, _, _)| code == INTERNAL_BASE || only.contains(code));
+        }
+
+        // Collect all XXX/UAH rates by date first
+        let mut uah_rates_by_date: HashMap<NaiveDate, HashMap<String, f64>> = HashMap::new();
+        // First and last date seen per currency
+        let mut observed: HashMap<String, (NaiveDate, NaiveDate)> = HashMap::new();
+        // Currencies whose batch failed after retries
+        let mut missing: Vec<String> = Vec::new();
+
+        // Fetch batches concurrently; the per-host limiter paces the requests
+        let mut fetched = stream::iter(batches)
+            .map(|(currency, from, to)| async move {
+                let url = format!(
+                    "{}?start={}&end={}&valcode={}&sort=exchangedate&order=asc&json",
+                    NBU_BATCH_URL,
+                    Self::format_date_for_batch(from),
+                    Self::format_date_for_batch(to),
+                    currency.to_lowercase()
+                );
+
+                tracing::info!("Fetching NBU batch for {}: {}", currency, url);
+                let result = self.http.get_json::<Vec<NbuBatchRate>>(&url).await;
+                (currency, result)
+            })
+            .buffer_unordered(limiter::concurrency());
+
+        while let Some((currency, result)) = fetched.next().await {
+            match result {
+                Ok(batch_rates) => {
+                    for batch_rate in batch_rates {
+                        let date = match Self::parse_nbu_date(&batch_rate.exchangedate) {
+                            Ok(d) => d,
+                            Err(_) => continue,
+                        };
+
+                        let code = batch_rate.cc.to_uppercase();
+                        observed
+                            .entry(code.clone())
+                            .and_modify(|(first, last)| {
+                                *first = (*first).min(date);
+                                *last = (*last).max(date);
+                            })
+                            .or_insert((date, date));
+
+                        uah_rates_by_date
+                            .entry(date)
+                            .or_default()
+                            .insert(code, batch_rate.rate_per_unit);
+                    }
+                }
+                // Nothing can be converted without USD/UAH
+                Err(e) if currency == INTERNAL_BASE => return Err(e),
+                Err(e) if self.filter.is_reference(&currency) => {
+                    tracing::warn!(
+                        "Failed to fetch NBU reference batch for {}: {}",
+                        currency,
+                        e
+                    );
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to fetch NBU batch for {}: {}", currency, e);
+                    missing.push(currency);
+                }
+            }
+        }
+        missing.sort();
+
+        self.record_observed(start, &observed).await?;
+
+        // Convert to USD-based DailyRates
+        let mut results: Vec<DailyRates> = Vec::new();
+
+        for (date, uah_rates) in uah_rates_by_date {
+            // Get USD/UAH rate for this date
+            let usd_uah = match uah_rates.get(INTERNAL_BASE) {
+                Some(&rate) => rate,
+                None => {
+                    tracing::warn!(
+                        "{}/UAH rate not found for date {}, skipping",
+                        INTERNAL_BASE,
+                        date
+                    );
+                    continue;
+                }
+            };
+
+            // Convert all rates to USD-based: USD/XXX = USD/UAH / XXX/UAH
+            let mut usd_rates: HashMap<String, f64> = HashMap::new();
+            usd_rates.insert(INTERNAL_BASE.to_string(), 1.0);
+            usd_rates.insert("UAH".to_string(), usd_uah);
+
+            for (currency, uah_rate) in uah_rates {
+                if currency == INTERNAL_BASE {
+                    continue;
+                }
+                let usd_rate = usd_uah / uah_rate;
+                usd_rates.insert(currency, usd_rate);
+            }
+
+            results.push(DailyRates {
+                date,
+                base_currency: INTERNAL_BASE.to_string(),
+                rates: usd_rates,
+                provider: self.name().to_string(),
+                estimated: false,
+            });
+        }
+
+        // Sort by date
+        results.sort_by_key(|r| r.date);
+
+        tracing::info!("Fetched {} days of NBU data via batch API", results.len());
+        if !missing.is_empty() {
+            return Err(AppError::PartialData {
+                rates: results,
+                missing,
+            });
         }
+        Ok(results)
     }
 
-    /// Get currencies to fetch (USD for conversion + NBU-specific currencies)
-    fn currencies_to_fetch() -> Vec<&'static str> {
-        let mut currencies: Vec<&str> = vec![INTERNAL_BASE];
-        currencies.extend(NBU_CURRENCIES.iter().filter(|&&c| c != INTERNAL_BASE));
-        currencies
+    /// Per-currency batch windows within `start..=end`: allowed and reference currencies only,
+    /// starting no earlier than their first publication and, for currencies
+    /// no longer published, ending at their last publication
+    fn plan_batches(
+        &self,
+        catalog: &HashMap<String, CatalogEntry>,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(String, NaiveDate, NaiveDate)> {
+        let mut batches: Vec<(String, NaiveDate, NaiveDate)> = Vec::new();
+
+        // USD is needed for every date to convert the others
+        batches.push((INTERNAL_BASE.to_string(), start, end));
+
+        let mut codes: Vec<&String> = catalog.keys().collect();
+        codes.sort();
+
+        for code in codes {
+            if code == INTERNAL_BASE || !self.filter.fetches(code) {
+                continue;
+            }
+            let entry = &catalog[code];
+
+            let first = entry
+                .currency
+                .first_date
+                .or_else(|| Self::known_first_date(code));
+            let from = first.map_or(start, |f| f.max(start));
+            let to = match (entry.active, entry.currency.last_date) {
+                (false, Some(last)) => last.min(end),
+                _ => end,
+            };
+
+            if from <= to {
+                batches.push((code.clone(), from, to));
+            }
+        }
+
+        batches
     }
 
     fn format_date_for_nbu(date: NaiveDate) -> String {
@@ -101,26 +526,37 @@ impl Default for NbuProvider {
 #[async_trait]
 impl Provider for NbuProvider {
     fn name(&self) -> &str {
-        "nbu"
+        NBU_NAME
     }
 
     fn description(&self) -> &str {
         "National Bank of Ukraine - Daily UAH reference rates"
     }
 
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_base: "UAH".to_string(),
+            history_start: Some(Self::history_start()),
+            // No fixed publication time
+            publication: PublicationSchedule::business_days(None, "Europe/Kyiv"),
+            supports_range: true,
+            attribution: Some("Source: National Bank of Ukraine (bank.gov.ua)".to_string()),
+        }
+    }
+
     async fn supported_currencies(&self) -> Result<Vec<Currency>> {
-        // Fetch latest to get available currencies
-        let url = format!("{}?json", NBU_BASE_URL);
-        let response = self.client.get(&url).send().await?;
-        let rates: Vec<NbuRate> = response.json().await?;
-
-        let mut currencies: Vec<Currency> = rates
-            .into_iter()
-            .map(|r| Currency {
-                code: r.cc,
-                name: r.txt,
+        // Discover currencies from the latest daily rates
+        let catalog = self.discover().await?;
+
+        let mut currencies: Vec<Currency> = catalog
+            .into_values()
+            .filter(|e| e.active && self.filter.allows(&e.currency.code))
+            .map(|e| Currency {
+                code: e.currency.code,
+                name: e.currency.name,
             })
             .collect();
+        currencies.sort_by(|a, b| a.code.cmp(&b.code));
 
         // Add UAH as base
         currencies.insert(
@@ -135,9 +571,9 @@ impl Provider for NbuProvider {
     }
 
     async fn fetch_latest(&self) -> Result<DailyRates> {
-        let url = format!("{}?json", NBU_BASE_URL);
-        let response = self.client.get(&url).send().await?;
-        let nbu_rates: Vec<NbuRate> = response.json().await?;
+        let mut nbu_rates = self.fetch_daily(None).await?;
+        self.record_latest(&nbu_rates).await?;
+        nbu_rates.retain(|r| self.filter.fetches(&r.cc));
 
         if nbu_rates.is_empty() {
             return Err(AppError::Provider("No rates returned from NBU".to_string()));
@@ -183,15 +619,13 @@ impl Provider for NbuProvider {
             base_currency: INTERNAL_BASE.to_string(),
             rates: usd_rates,
             provider: self.name().to_string(),
+            estimated: false,
         })
     }
 
     async fn fetch_date(&self, date: NaiveDate) -> Result<DailyRates> {
-        let date_str = Self::format_date_for_nbu(date);
-        let url = format!("{}?date={}&json", NBU_BASE_URL, date_str);
-
-        let response = self.client.get(&url).send().await?;
-        let nbu_rates: Vec<NbuRate> = response.json().await?;
+        let mut nbu_rates = self.fetch_daily(Some(date)).await?;
+        nbu_rates.retain(|r| self.filter.fetches(&r.cc));
 
         if nbu_rates.is_empty() {
             return Err(AppError::NoDataAvailable);
@@ -232,118 +666,58 @@ impl Provider for NbuProvider {
             base_currency: INTERNAL_BASE.to_string(),
             rates: usd_rates,
             provider: self.name().to_string(),
+            estimated: false,
         })
     }
 
     async fn fetch_range(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyRates>> {
-        let start_str = Self::format_date_for_batch(start);
-        let end_str = Self::format_date_for_batch(end);
-        let currencies = Self::currencies_to_fetch();
-
-        // Collect all XXX/UAH rates by date first
-        let mut uah_rates_by_date: HashMap<NaiveDate, HashMap<String, f64>> = HashMap::new();
-
-        for currency in currencies {
-            let url = format!(
-                "{}?start={}&end={}&valcode={}&sort=exchangedate&order=asc&json",
-                NBU_BATCH_URL,
-                start_str,
-                end_str,
-                currency.to_lowercase()
-            );
-
-            tracing::info!("Fetching NBU batch for {}: {}", currency, url);
-
-            match self.client.get(&url).send().await {
-                Ok(response) => match response.json::<Vec<NbuBatchRate>>().await {
-                    Ok(batch_rates) => {
-                        for batch_rate in batch_rates {
-                            let date = match Self::parse_nbu_date(&batch_rate.exchangedate) {
-                                Ok(d) => d,
-                                Err(_) => continue,
-                            };
-
-                            uah_rates_by_date
-                                .entry(date)
-                                .or_default()
-                                .insert(batch_rate.cc.to_uppercase(), batch_rate.rate_per_unit);
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            "Failed to parse NBU batch response for {}: {}",
-                            currency,
-                            e
-                        );
-                    }
-                },
-                Err(e) => {
-                    tracing::warn!("Failed to fetch NBU batch for {}: {}", currency, e);
-                }
-            }
-
-            // Small delay between currency fetches
-            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-        }
-
-        // Convert to USD-based DailyRates
-        let mut results: Vec<DailyRates> = Vec::new();
-
-        for (date, uah_rates) in uah_rates_by_date {
-            // Get USD/UAH rate for this date
-            let usd_uah = match uah_rates.get(INTERNAL_BASE) {
-                Some(&rate) => rate,
-                None => {
-                    tracing::warn!(
-                        "{}/UAH rate not found for date {}, skipping",
-                        INTERNAL_BASE,
-                        date
-                    );
-                    continue;
-                }
-            };
-
-            // Convert all rates to USD-based: USD/XXX = USD/UAH / XXX/UAH
-            let mut usd_rates: HashMap<String, f64> = HashMap::new();
-            usd_rates.insert(INTERNAL_BASE.to_string(), 1.0);
-            usd_rates.insert("UAH".to_string(), usd_uah);
-
-            for (currency, uah_rate) in uah_rates {
-                if currency == INTERNAL_BASE {
-                    continue;
-                }
-                let usd_rate = usd_uah / uah_rate;
-                usd_rates.insert(currency, usd_rate);
-            }
-
-            results.push(DailyRates {
-                date,
-                base_currency: INTERNAL_BASE.to_string(),
-                rates: usd_rates,
-                provider: self.name().to_string(),
-            });
-        }
+        self.fetch_batches(start, end, None).await
+    }
 
-        // Sort by date
-        results.sort_by_key(|r| r.date);
+    fn reference_rates(&self, rates: &mut [DailyRates]) -> Vec<DailyRates> {
+        rates
+            .iter_mut()
+            .filter_map(|daily| {
+                let (kept, reference): (HashMap<_, _>, HashMap<_, _>) =
+                    std::mem::take(&mut daily.rates)
+                        .into_iter()
+                        .partition(|(code, _)| !self.filter.is_reference(code));
+                daily.rates = kept;
+                (!reference.is_empty()).then(|| DailyRates {
+                    rates: reference,
+                    ..daily.clone()
+                })
+            })
+            .collect()
+    }
 
-        tracing::info!("Fetched {} days of NBU data via batch API", results.len());
-        Ok(results)
+    async fn fetch_range_for(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        currencies: &[String],
+    ) -> Result<Vec<DailyRates>> {
+        self.fetch_batches(start, end, Some(currencies)).await
     }
 
     async fn fetch_full_history(&self) -> Result<Vec<DailyRates>> {
         // Fetch full history from 1999-01-04 (same as ECB start date)
         let end = chrono::Utc::now().date_naive();
-        let start = NaiveDate::from_ymd_opt(1999, 1, 4).unwrap();
+        let start = Self::history_start();
 
         tracing::info!("Fetching NBU history from {} to {}", start, end);
         self.fetch_range(start, end).await
     }
+
+    fn circuit_status(&self) -> Option<CircuitStatus> {
+        Some(self.http.circuit_status())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::test_repository;
 
     #[test]
     fn test_date_formatting() {
@@ -356,4 +730,165 @@ mod tests {
         let result = NbuProvider::parse_nbu_date("27.11.2025").unwrap();
         assert_eq!(result, NaiveDate::from_ymd_opt(2025, 11, 27).unwrap());
     }
+
+    fn entry(
+        code: &str,
+        first: Option<NaiveDate>,
+        last: Option<NaiveDate>,
+        active: bool,
+    ) -> CatalogEntry {
+        CatalogEntry {
+            currency: ProviderCurrency {
+                code: code.to_string(),
+                name: code.to_string(),
+                first_date: first,
+                last_date: last,
+            },
+            active,
+        }
+    }
+
+    #[test]
+    fn test_currency_filter() {
+        let default = NbuCurrencyFilter::default();
+        assert!(default.allows("USD"));
+        assert!(default.allows("KZT"));
+        assert!(!default.allows("EUR"));
+        for code in ["XAU", "XAG", "XPT", "XPD", "xdr"] {
+            assert!(!default.fetches(code), "{} is not a currency", code);
+        }
+        let no_exclude = NbuCurrencyFilter::new(Vec::new(), Vec::new());
+        assert!(!no_exclude.allows("XAU") && no_exclude.allows("EUR"));
+
+        assert!(default.is_reference("eur") && default.fetches("EUR"));
+        assert!(!default.is_reference("KZT") && !default.fetches("BGN"));
+
+        let filter = NbuCurrencyFilter::new(vec!["kzt".into(), "EUR".into()], vec!["eur".into()]);
+        assert!(filter.allows("KZT"));
+        assert!(filter.allows("USD"));
+        assert!(!filter.allows("EUR"));
+        assert!(!filter.allows("GEL"));
+
+        // Metals are served when asked for by name
+        assert!(NbuCurrencyFilter::new(vec!["XAU".into()], Vec::new()).allows("XAU"));
+    }
+
+    #[test]
+    fn test_plan_batches() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let provider = NbuProvider::new();
+
+        let mut catalog = HashMap::new();
+        catalog.insert(
+            "KZT".to_string(),
+            entry("KZT", Some(date(1999, 1, 4)), None, true),
+        );
+        // Excluded, but fetched as a reference
+        catalog.insert("EUR".to_string(), entry("EUR", None, None, true));
+        catalog.insert("BGN".to_string(), entry("BGN", None, None, true));
+        // Unknown first date, falls back to the built-in one
+        catalog.insert("AZN".to_string(), entry("AZN", None, None, true));
+        // No longer published
+        catalog.insert(
+            "LTL".to_string(),
+            entry("LTL", None, Some(date(2014, 12, 31)), false),
+        );
+
+        let batches = provider.plan_batches(&catalog, date(2010, 1, 1), date(2015, 6, 30));
+        assert_eq!(
+            batches,
+            vec![
+                ("USD".to_string(), date(2010, 1, 1), date(2015, 6, 30)),
+                ("AZN".to_string(), date(2014, 4, 4), date(2015, 6, 30)),
+                ("EUR".to_string(), date(2010, 1, 1), date(2015, 6, 30)),
+                ("KZT".to_string(), date(2010, 1, 1), date(2015, 6, 30)),
+                ("LTL".to_string(), date(2010, 1, 1), date(2014, 12, 31)),
+            ]
+        );
+
+        // Window entirely before AZN and after LTL
+        let batches = provider.plan_batches(&catalog, date(2016, 1, 1), date(2016, 1, 31));
+        let codes: Vec<&str> = batches.iter().map(|(c, _, _)| c.as_str()).collect();
+        assert_eq!(codes, vec!["USD", "AZN", "EUR", "KZT"]);
+    }
+
+    #[test]
+    fn test_merge_entry_widens_dates() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let mut catalog = HashMap::new();
+
+        NbuProvider::merge_entry(
+            &mut catalog,
+            entry("AZN", Some(date(2014, 4, 4)), Some(date(2020, 1, 1)), true).currency,
+        );
+        NbuProvider::merge_entry(
+            &mut catalog,
+            entry("AZN", Some(date(2015, 1, 1)), Some(date(2025, 1, 1)), true).currency,
+        );
+
+        let azn = &catalog["AZN"].currency;
+        assert_eq!(azn.first_date, Some(date(2014, 4, 4)));
+        assert_eq!(azn.last_date, Some(date(2025, 1, 1)));
+    }
+
+    #[tokio::test]
+    async fn test_reference_rates_reconcile() {
+        use crate::providers::rebase_to_usd;
+        use crate::reconciliation::{self, DEFAULT_TOLERANCE};
+
+        let repository = test_repository().await;
+        let nbu = NbuProvider::new();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+
+        // Default filter: EUR is not served by NBU but kept as a reference
+        let mut fetched = [DailyRates {
+            date,
+            base_currency: INTERNAL_BASE.to_string(),
+            rates: HashMap::from([
+                ("UAH".to_string(), 41.5),
+                ("KZT".to_string(), 500.0),
+                ("EUR".to_string(), 41.5 / 45.9),
+            ]),
+            provider: NBU_NAME.to_string(),
+            estimated: false,
+        }];
+        let reference = nbu.reference_rates(&mut fetched);
+        assert!(!fetched[0].rates.contains_key("EUR"));
+        assert_eq!(reference[0].rates.keys().collect::<Vec<_>>(), ["EUR"]);
+        repository.store_reference_rates(&reference).await.unwrap();
+        repository.store_daily_rates_batch(&fetched).await.unwrap();
+
+        let ecb = rebase_to_usd(
+            "EUR",
+            HashMap::from([("USD".to_string(), 1.08), ("GBP".to_string(), 0.85)]),
+        )
+        .unwrap();
+        repository
+            .store_daily_rates_batch(&[DailyRates {
+                date,
+                base_currency: INTERNAL_BASE.to_string(),
+                rates: ecb,
+                provider: "ecb".to_string(),
+                estimated: false,
+            }])
+            .await
+            .unwrap();
+
+        let served = repository
+            .get_rates_for_date(date, INTERNAL_BASE, None, &[])
+            .await
+            .unwrap();
+        assert!((served["EUR"] - 1.0 / 1.08).abs() < 1e-9);
+
+        let rates = repository.get_overlapping_rates(date, date).await.unwrap();
+        let discrepancies = reconciliation::compare(&rates);
+        assert_eq!(discrepancies.len(), 1);
+        let eur = &discrepancies[0];
+        assert_eq!(eur.currency, "EUR");
+        assert_eq!(
+            (eur.provider_a.as_str(), eur.provider_b.as_str()),
+            ("ecb", "nbu")
+        );
+        assert!(eur.diff > DEFAULT_TOLERANCE);
+    }
 }
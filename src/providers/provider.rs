@@ -1,10 +1,62 @@
 use async_trait::async_trait;
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
-use crate::error::Result;
+use crate::config::ProviderSettings;
+use crate::error::{AppError, Result};
 use crate::models::{Currency, DailyRates};
+use crate::providers::http::CircuitStatus;
+use crate::providers::limiter;
+
+/// When a provider publishes new rates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PublicationSchedule {
+    /// Days of the week rates are published on
+    #[schema(value_type = Vec<String>, example = json!(["Mon", "Tue", "Wed", "Thu", "Fri"]))]
+    pub weekdays: Vec<Weekday>,
+    /// Approximate local publication time, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<NaiveTime>,
+    /// IANA time zone the publication time is given in
+    pub timezone: String,
+}
+
+impl PublicationSchedule {
+    /// Monday to Friday, optionally at a fixed local time (hour, minute)
+    pub fn business_days(time: Option<(u32, u32)>, timezone: &str) -> Self {
+        Self {
+            weekdays: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            time: time.and_then(|(h, m)| NaiveTime::from_hms_opt(h, m, 0)),
+            timezone: timezone.to_string(),
+        }
+    }
+}
+
+/// Static metadata describing a provider's data and what it can do
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProviderCapabilities {
+    /// Currency the source quotes its rates against
+    pub native_base: String,
+    /// First date historical data is available for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_start: Option<NaiveDate>,
+    pub publication: PublicationSchedule,
+    /// Whether a date range can be fetched in one request
+    pub supports_range: bool,
+    /// License or attribution required when redistributing the data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+}
 
 /// Trait that all currency rate providers must implement.
 /// This allows easy addition of new data sources.
@@ -16,6 +68,9 @@ pub trait Provider: Send + Sync {
     /// Human-readable description
     fn description(&self) -> &str;
 
+    /// Native base, history depth, publication schedule and other metadata
+    fn capabilities(&self) -> ProviderCapabilities;
+
     /// List of currencies this provider supports
     async fn supported_currencies(&self) -> Result<Vec<Currency>>;
 
@@ -26,49 +81,158 @@ pub trait Provider: Send + Sync {
     async fn fetch_date(&self, date: NaiveDate) -> Result<DailyRates>;
 
     /// Fetch rates for a date range (batch operation)
-    /// Default implementation calls fetch_date for each day
+    /// Default implementation calls fetch_date for each day, see [`fetch_each_day`]
     async fn fetch_range(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyRates>> {
-        let mut results = Vec::new();
-        let mut current = start;
-
-        while current <= end {
-            match self.fetch_date(current).await {
-                Ok(rates) => results.push(rates),
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to fetch rates for {} from {}: {}",
-                        current,
-                        self.name(),
-                        e
-                    );
-                }
-            }
-            current = current.succ_opt().unwrap_or(current);
-        }
+        fetch_each_day(self, start, end).await
+    }
 
-        Ok(results)
+    /// Fetch a date range for some currencies only (used to repair holes).
+    /// Default implementation fetches the whole range and drops the other currencies;
+    /// providers that fetch per currency override it to skip the unneeded requests.
+    async fn fetch_range_for(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        currencies: &[String],
+    ) -> Result<Vec<DailyRates>> {
+        let mut rates = self.fetch_range(start, end).await?;
+        for daily in &mut rates {
+            daily
+                .rates
+                .retain(|code, _| code == &daily.base_currency || currencies.contains(code));
+        }
+        Ok(rates)
     }
 
     /// Fetch full historical data (if provider supports it)
     async fn fetch_full_history(&self) -> Result<Vec<DailyRates>>;
+
+    /// Move the rates fetched only to compare with other providers out of
+    /// `rates`. They are stored for reconciliation and never served.
+    fn reference_rates(&self, _rates: &mut [DailyRates]) -> Vec<DailyRates> {
+        Vec::new()
+    }
+
+    /// Whether the provider computes its rates from other providers' stored
+    /// data, so it has to be synced after them
+    fn depends_on_stored_rates(&self) -> bool {
+        false
+    }
+
+    /// Circuit breaker state of the provider's HTTP client, if it has one
+    fn circuit_status(&self) -> Option<CircuitStatus> {
+        None
+    }
+}
+
+/// Call `fetch_date` for each day of `start..=end`, a few days at a time.
+/// Days without data (weekends, holidays) are skipped; if other days fail,
+/// the fetched ones are returned in `AppError::PartialData` with the failed
+/// dates as missing, so the sync applies its partial data policy. Results are
+/// in date order.
+pub(super) async fn fetch_each_day<P: Provider + ?Sized>(
+    provider: &P,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<DailyRates>> {
+    let dates: Vec<NaiveDate> = start.iter_days().take_while(|d| *d <= end).collect();
+
+    let fetched: Vec<(NaiveDate, Result<DailyRates>)> = stream::iter(dates)
+        .map(|date| async move { (date, provider.fetch_date(date).await) })
+        .buffered(limiter::concurrency())
+        .collect()
+        .await;
+
+    let mut results = Vec::new();
+    let mut missing = Vec::new();
+    for (date, result) in fetched {
+        match result {
+            Ok(rates) => results.push(rates),
+            Err(AppError::NoDataAvailable) => {}
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to fetch rates for {} from {}: {}",
+                    date,
+                    provider.name(),
+                    e
+                );
+                missing.push(date.to_string());
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(results)
+    } else {
+        Err(AppError::PartialData {
+            rates: results,
+            missing,
+        })
+    }
 }
 
 /// Registry of all available providers
+/// Providers are kept in registration order, which is also the sync order.
 pub struct ProviderRegistry {
     providers: HashMap<String, Arc<dyn Provider>>,
+    settings: HashMap<String, ProviderSettings>,
+    order: Vec<String>,
 }
 
 impl ProviderRegistry {
     pub fn new() -> Self {
         Self {
             providers: HashMap::new(),
+            settings: HashMap::new(),
+            order: Vec::new(),
         }
     }
 
-    /// Register a new provider
-    pub fn register<P: Provider + 'static>(&mut self, provider: P) {
+    /// Register a new provider, enabled with default sync settings
+    pub fn register<P: Provider + 'static>(&mut self, provider: P) -> Result<()> {
+        self.register_with_settings(provider, ProviderSettings::new(true))
+    }
+
+    /// Register a new provider with its own sync settings.
+    /// Fails if a provider with the same name is already registered.
+    pub fn register_with_settings<P: Provider + 'static>(
+        &mut self,
+        provider: P,
+        settings: ProviderSettings,
+    ) -> Result<()> {
         let name = provider.name().to_string();
-        self.providers.insert(name, Arc::new(provider));
+        if self.providers.contains_key(&name) {
+            return Err(AppError::Config(format!(
+                "Provider {} is already registered",
+                name
+            )));
+        }
+        self.settings.insert(name.clone(), settings);
+        self.providers.insert(name.clone(), Arc::new(provider));
+        self.order.push(name);
+        Ok(())
+    }
+
+    /// Sync settings of a provider
+    pub fn settings(&self, name: &str) -> ProviderSettings {
+        self.settings
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| ProviderSettings::new(true))
+    }
+
+    /// Whether a provider is synced (disabled providers are still queryable)
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.settings.get(name).is_none_or(|s| s.enabled)
+    }
+
+    /// Names of enabled providers in sync order
+    pub fn enabled_names(&self) -> Vec<String> {
+        self.order
+            .iter()
+            .filter(|name| self.is_enabled(name))
+            .cloned()
+            .collect()
     }
 
     /// Get a provider by name
@@ -78,12 +242,15 @@ impl ProviderRegistry {
 
     /// Get all registered providers
     pub fn all(&self) -> Vec<Arc<dyn Provider>> {
-        self.providers.values().cloned().collect()
+        self.order
+            .iter()
+            .filter_map(|name| self.providers.get(name).cloned())
+            .collect()
     }
 
     /// Get provider names
     pub fn names(&self) -> Vec<String> {
-        self.providers.keys().cloned().collect()
+        self.order.clone()
     }
 }
 
@@ -92,3 +259,101 @@ impl Default for ProviderRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    /// Fails on weekends, answers weekdays after a short delay
+    struct WeekdayProvider;
+
+    #[async_trait]
+    impl Provider for WeekdayProvider {
+        fn name(&self) -> &str {
+            "weekday"
+        }
+
+        fn description(&self) -> &str {
+            "Test provider"
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                native_base: "USD".to_string(),
+                history_start: None,
+                publication: PublicationSchedule::business_days(None, "UTC"),
+                supports_range: false,
+                attribution: None,
+            }
+        }
+
+        async fn supported_currencies(&self) -> Result<Vec<Currency>> {
+            Ok(Vec::new())
+        }
+
+        async fn fetch_latest(&self) -> Result<DailyRates> {
+            Err(AppError::NoDataAvailable)
+        }
+
+        async fn fetch_date(&self, date: NaiveDate) -> Result<DailyRates> {
+            if date.weekday().number_from_monday() > 5 {
+                return Err(AppError::NoDataAvailable);
+            }
+            if date.day() == 25 {
+                return Err(AppError::Unavailable("weekday circuit is open".to_string()));
+            }
+            // Later dates finish first
+            let delay = 20 - date.day() as u64 % 20;
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+
+            Ok(DailyRates {
+                date,
+                base_currency: "USD".to_string(),
+                rates: HashMap::new(),
+                provider: self.name().to_string(),
+                estimated: false,
+            })
+        }
+
+        async fn fetch_full_history(&self) -> Result<Vec<DailyRates>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(WeekdayProvider).unwrap();
+        assert!(matches!(
+            registry.register_with_settings(WeekdayProvider, ProviderSettings::new(false)),
+            Err(AppError::Config(_))
+        ));
+        assert_eq!(registry.names(), vec!["weekday"]);
+        assert!(registry.is_enabled("weekday"));
+    }
+
+    #[tokio::test]
+    async fn test_default_fetch_range() {
+        let start = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 6, 15).unwrap();
+
+        let rates = WeekdayProvider.fetch_range(start, end).await.unwrap();
+
+        // Weekends skipped, results in date order despite concurrent fetching
+        let dates: Vec<u32> = rates.iter().map(|r| r.date.day()).collect();
+        assert_eq!(dates, vec![2, 3, 4, 5, 6, 9, 10, 11, 12, 13]);
+
+        // A failed day is reported, not dropped
+        let start = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 6, 27).unwrap();
+        match WeekdayProvider.fetch_range(start, end).await {
+            Err(AppError::PartialData { rates, missing }) => {
+                let dates: Vec<u32> = rates.iter().map(|r| r.date.day()).collect();
+                assert_eq!(dates, vec![23, 24, 26, 27]);
+                assert_eq!(missing, vec!["2025-06-25"]);
+            }
+            other => panic!("expected partial data, got {:?}", other.map(|r| r.len())),
+        }
+    }
+}
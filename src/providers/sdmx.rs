@@ -0,0 +1,960 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashMap;
+
+use crate::error::{AppError, Result};
+use crate::models::{Currency, DailyRates};
+use crate::providers::http::{CircuitStatus, HttpClient};
+use crate::providers::{Provider, ProviderCapabilities, PublicationSchedule};
+
+/// Internal base currency for storage (all providers convert to this)
+const INTERNAL_BASE: &str = "USD";
+
+/// How a series value is quoted against the source's native base currency
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quotation {
+    /// Units of the foreign currency per 1 unit of the native base
+    /// (e.g. ECB: 1 EUR = 1.0956 USD)
+    ForeignPerBase,
+    /// Units of the native base per `units` of the foreign currency
+    /// (e.g. SNB: 100 JPY = 0.5987 CHF)
+    BasePerForeign { units: f64 },
+}
+
+impl Quotation {
+    /// Convert a published value into "units of foreign currency per 1 native base"
+    pub fn foreign_per_base(&self, value: f64) -> f64 {
+        match self {
+            Quotation::ForeignPerBase => value,
+            Quotation::BasePerForeign { units } => units / value,
+        }
+    }
+}
+
+/// Maps a series key published by the source to a currency
+#[derive(Debug, Clone)]
+pub struct SeriesMapping {
+    /// Series key as it appears in the response (e.g. "XUDLUSS", "D.USD.EUR.SP00.A")
+    pub key: String,
+    /// ISO currency code the series describes
+    pub currency: String,
+    /// Human-readable currency name
+    pub name: String,
+    pub quotation: Quotation,
+}
+
+impl SeriesMapping {
+    pub fn new(key: &str, currency: &str, name: &str, quotation: Quotation) -> Self {
+        Self {
+            key: key.to_string(),
+            currency: currency.to_string(),
+            name: name.to_string(),
+            quotation,
+        }
+    }
+}
+
+/// Response format of a series API
+#[derive(Debug, Clone)]
+pub enum SeriesFormat {
+    /// SDMX-JSON data message; series keys are dimension values joined with '.'
+    SdmxJson,
+    /// SDMX-ML (generic or structure-specific) data message; series keys are the
+    /// values of `key_dimensions` joined with '.'
+    SdmxMl { key_dimensions: Vec<String> },
+    /// CSV with a date column and one column per series (header = series key)
+    CsvWide { delimiter: u8, date_column: String },
+    /// CSV with one observation per row (date, series key, value)
+    CsvLong {
+        delimiter: u8,
+        date_column: String,
+        key_column: String,
+        value_column: String,
+    },
+}
+
+/// Configuration of a statistical-series source
+#[derive(Debug, Clone)]
+pub struct SeriesSource {
+    /// Provider name (e.g. "boe")
+    pub name: String,
+    pub description: String,
+    /// Currency all series are quoted against (e.g. "GBP")
+    pub native_base: String,
+    pub native_base_name: String,
+    /// Request URL; supports `{start}`, `{end}` and `{series}` placeholders
+    pub url_template: String,
+    /// chrono format for `{start}`/`{end}` in the URL
+    pub url_date_format: String,
+    /// Separator used to join series keys for `{series}`
+    pub series_separator: String,
+    pub format: SeriesFormat,
+    /// chrono format of observation dates in the response
+    pub date_format: String,
+    /// First date the source has data for
+    pub history_start: NaiveDate,
+    /// Fill weekends/holidays with the previous available day's rates
+    pub fill_gaps: bool,
+    /// When the source publishes new observations
+    pub publication: PublicationSchedule,
+    /// License or attribution text required by the source
+    pub attribution: Option<String>,
+    pub series: Vec<SeriesMapping>,
+}
+
+/// A single published value of a series
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub key: String,
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+/// Generic provider for series-oriented statistical APIs.
+/// Specific sources (BoE, SNB, ...) are just a `SeriesSource` configuration.
+pub struct SeriesProvider {
+    source: SeriesSource,
+    http: HttpClient,
+}
+
+impl SeriesProvider {
+    pub fn new(source: SeriesSource) -> Self {
+        Self {
+            http: HttpClient::new(&source.name),
+            source,
+        }
+    }
+
+    pub fn source(&self) -> &SeriesSource {
+        &self.source
+    }
+
+    fn build_url(&self, start: NaiveDate, end: NaiveDate) -> String {
+        let series = self
+            .source
+            .series
+            .iter()
+            .map(|s| s.key.as_str())
+            .collect::<Vec<_>>()
+            .join(&self.source.series_separator);
+
+        self.source
+            .url_template
+            .replace(
+                "{start}",
+                &start.format(&self.source.url_date_format).to_string(),
+            )
+            .replace(
+                "{end}",
+                &end.format(&self.source.url_date_format).to_string(),
+            )
+            .replace("{series}", &series)
+    }
+
+    async fn fetch_observations(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<Observation>> {
+        let url = self.build_url(start, end);
+        tracing::info!("Fetching {} series: {}", self.source.name, url);
+
+        let body = self.http.get_text(&url).await?;
+
+        self.parse(&body)
+    }
+
+    /// Parse a response body into observations according to the configured format
+    #[tracing::instrument(name = "parse", skip_all, fields(source = %self.source.name))]
+    pub fn parse(&self, body: &str) -> Result<Vec<Observation>> {
+        match &self.source.format {
+            SeriesFormat::SdmxJson => parse_sdmx_json(body, &self.source.date_format),
+            SeriesFormat::SdmxMl { key_dimensions } => {
+                parse_sdmx_ml(body, key_dimensions, &self.source.date_format)
+            }
+            SeriesFormat::CsvWide {
+                delimiter,
+                date_column,
+            } => parse_csv_wide(body, *delimiter, date_column, &self.source.date_format),
+            SeriesFormat::CsvLong {
+                delimiter,
+                date_column,
+                key_column,
+                value_column,
+            } => parse_csv_long(
+                body,
+                *delimiter,
+                date_column,
+                key_column,
+                value_column,
+                &self.source.date_format,
+            ),
+        }
+    }
+
+    /// Convert observations into USD-based daily rates.
+    /// Observations for unmapped series keys are ignored.
+    pub fn to_daily_rates(&self, observations: Vec<Observation>) -> Vec<DailyRates> {
+        let mappings: HashMap<&str, &SeriesMapping> = self
+            .source
+            .series
+            .iter()
+            .map(|s| (s.key.as_str(), s))
+            .collect();
+
+        // Collect "foreign per native base" rates by date
+        let mut native_rates_by_date: HashMap<NaiveDate, HashMap<String, f64>> = HashMap::new();
+
+        for obs in observations {
+            let Some(mapping) = mappings.get(obs.key.as_str()) else {
+                continue;
+            };
+            if obs.value <= 0.0 || !obs.value.is_finite() {
+                continue;
+            }
+
+            native_rates_by_date.entry(obs.date).or_default().insert(
+                mapping.currency.clone(),
+                mapping.quotation.foreign_per_base(obs.value),
+            );
+        }
+
+        let mut results: Vec<DailyRates> = Vec::new();
+
+        for (date, native_rates) in native_rates_by_date {
+            let Some(usd_rates) = super::rebase_to_usd(&self.source.native_base, native_rates)
+            else {
+                tracing::warn!(
+                    "{}/{} rate not found for date {}, skipping",
+                    self.source.native_base,
+                    INTERNAL_BASE,
+                    date
+                );
+                continue;
+            };
+
+            results.push(DailyRates {
+                date,
+                base_currency: INTERNAL_BASE.to_string(),
+                rates: usd_rates,
+                provider: self.source.name.clone(),
+                estimated: false,
+            });
+        }
+
+        results.sort_by_key(|r| r.date);
+        results
+    }
+
+    /// Daily rates from `start` to `end` out of observations fetched from
+    /// before `start`, so days at its start can be gap-filled
+    fn range_rates(
+        &self,
+        observations: Vec<Observation>,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<DailyRates> {
+        let mut rates = self.to_daily_rates(observations);
+
+        if self.source.fill_gaps {
+            rates = super::fill_gaps_until(rates, self.name(), end);
+        }
+
+        rates
+            .into_iter()
+            .filter(|r| r.date >= start && r.date <= end)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Provider for SeriesProvider {
+    fn name(&self) -> &str {
+        &self.source.name
+    }
+
+    fn description(&self) -> &str {
+        &self.source.description
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_base: self.source.native_base.clone(),
+            history_start: Some(self.source.history_start),
+            publication: self.source.publication.clone(),
+            supports_range: true,
+            attribution: self.source.attribution.clone(),
+        }
+    }
+
+    async fn supported_currencies(&self) -> Result<Vec<Currency>> {
+        let mut currencies: Vec<Currency> = self
+            .source
+            .series
+            .iter()
+            .map(|s| Currency {
+                code: s.currency.clone(),
+                name: s.name.clone(),
+            })
+            .collect();
+
+        if !currencies.iter().any(|c| c.code == self.source.native_base) {
+            currencies.insert(
+                0,
+                Currency {
+                    code: self.source.native_base.clone(),
+                    name: self.source.native_base_name.clone(),
+                },
+            );
+        }
+
+        Ok(currencies)
+    }
+
+    async fn fetch_latest(&self) -> Result<DailyRates> {
+        // Series APIs have no "latest" document, look back far enough to cover holidays
+        let end = chrono::Utc::now().date_naive();
+        let start = end - chrono::Duration::days(14);
+
+        let observations = self.fetch_observations(start, end).await?;
+        self.to_daily_rates(observations).pop().ok_or_else(|| {
+            AppError::Provider(format!("No rates found in {} response", self.name()))
+        })
+    }
+
+    async fn fetch_date(&self, date: NaiveDate) -> Result<DailyRates> {
+        self.fetch_range(date, date)
+            .await?
+            .into_iter()
+            .find(|r| r.date == date)
+            .ok_or(AppError::NoDataAvailable)
+    }
+
+    async fn fetch_range(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyRates>> {
+        let padded_start = start - chrono::Duration::days(super::GAP_PADDING_DAYS);
+        let observations = self.fetch_observations(padded_start, end).await?;
+
+        Ok(self.range_rates(observations, start, end))
+    }
+
+    async fn fetch_full_history(&self) -> Result<Vec<DailyRates>> {
+        let end = chrono::Utc::now().date_naive();
+        tracing::info!(
+            "Fetching {} history from {} to {}",
+            self.name(),
+            self.source.history_start,
+            end
+        );
+        self.fetch_range(self.source.history_start, end).await
+    }
+
+    fn circuit_status(&self) -> Option<CircuitStatus> {
+        Some(self.http.circuit_status())
+    }
+}
+
+/// Provider reading a fixed [`SeriesSource`], like BoE or SNB. Every
+/// [`Provider`] method is served by the wrapped [`SeriesProvider`].
+pub trait SeriesBacked: Send + Sync {
+    fn series(&self) -> &SeriesProvider;
+}
+
+#[async_trait]
+impl<T: SeriesBacked> Provider for T {
+    fn name(&self) -> &str {
+        self.series().name()
+    }
+
+    fn description(&self) -> &str {
+        self.series().description()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.series().capabilities()
+    }
+
+    async fn supported_currencies(&self) -> Result<Vec<Currency>> {
+        self.series().supported_currencies().await
+    }
+
+    async fn fetch_latest(&self) -> Result<DailyRates> {
+        self.series().fetch_latest().await
+    }
+
+    async fn fetch_date(&self, date: NaiveDate) -> Result<DailyRates> {
+        self.series().fetch_date(date).await
+    }
+
+    async fn fetch_range(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<DailyRates>> {
+        self.series().fetch_range(start, end).await
+    }
+
+    async fn fetch_range_for(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        currencies: &[String],
+    ) -> Result<Vec<DailyRates>> {
+        self.series().fetch_range_for(start, end, currencies).await
+    }
+
+    async fn fetch_full_history(&self) -> Result<Vec<DailyRates>> {
+        self.series().fetch_full_history().await
+    }
+
+    fn circuit_status(&self) -> Option<CircuitStatus> {
+        self.series().circuit_status()
+    }
+}
+
+fn parse_date(value: &str, format: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), format).ok()
+}
+
+/// Parse an SDMX-JSON data message (supports both 1.0 and 2.0 layouts)
+fn parse_sdmx_json(body: &str, date_format: &str) -> Result<Vec<Observation>> {
+    let root: serde_json::Value = serde_json::from_str(body)?;
+    // SDMX-JSON 2.0 nests everything under "data"
+    let message = root.get("data").unwrap_or(&root);
+
+    let invalid = |what: &str| AppError::Provider(format!("Invalid SDMX-JSON message: {}", what));
+
+    let structure = message
+        .get("structure")
+        .ok_or_else(|| invalid("no structure"))?;
+    let dimensions = &structure["dimensions"];
+
+    // Values of each series dimension, indexed by position
+    let series_dims: Vec<Vec<String>> = dimensions["series"]
+        .as_array()
+        .ok_or_else(|| invalid("no series dimensions"))?
+        .iter()
+        .map(|dim| {
+            dim["values"]
+                .as_array()
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|v| v["id"].as_str().unwrap_or_default().to_string())
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let time_values: Vec<Option<NaiveDate>> = dimensions["observation"]
+        .as_array()
+        .and_then(|dims| dims.first())
+        .and_then(|dim| dim["values"].as_array())
+        .ok_or_else(|| invalid("no observation dimension"))?
+        .iter()
+        .map(|v| parse_date(v["id"].as_str().unwrap_or_default(), date_format))
+        .collect();
+
+    let mut observations = Vec::new();
+
+    let datasets = message["dataSets"]
+        .as_array()
+        .ok_or_else(|| invalid("no dataSets"))?;
+    for dataset in datasets {
+        let Some(series) = dataset["series"].as_object() else {
+            continue;
+        };
+
+        for (index_key, series_data) in series {
+            // "0:3:0:0:0" -> dimension value ids joined with '.'
+            let key = index_key
+                .split(':')
+                .enumerate()
+                .map(|(dim, idx)| {
+                    idx.parse::<usize>()
+                        .ok()
+                        .and_then(|idx| series_dims.get(dim)?.get(idx).cloned())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+
+            let Some(obs) = series_data["observations"].as_object() else {
+                continue;
+            };
+
+            for (time_idx, values) in obs {
+                let date = time_idx
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|idx| time_values.get(idx).copied().flatten());
+                let value = values.get(0).and_then(|v| v.as_f64());
+
+                if let (Some(date), Some(value)) = (date, value) {
+                    observations.push(Observation {
+                        key: key.clone(),
+                        date,
+                        value,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(observations)
+}
+
+/// Parse an SDMX-ML data message.
+/// Handles structure-specific (`<Series CURRENCY=".."><Obs TIME_PERIOD=".." OBS_VALUE=".."/>`)
+/// and generic (`<SeriesKey><Value id=".." value=".."/>`, `<ObsDimension>`, `<ObsValue>`) layouts.
+fn parse_sdmx_ml(
+    body: &str,
+    key_dimensions: &[String],
+    date_format: &str,
+) -> Result<Vec<Observation>> {
+    fn attributes(e: &BytesStart) -> HashMap<String, String> {
+        e.attributes()
+            .flatten()
+            .filter_map(|attr| {
+                let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+                let value = attr.unescape_value().ok()?.to_string();
+                Some((key, value))
+            })
+            .collect()
+    }
+
+    let mut reader = Reader::from_str(body);
+    reader.trim_text(true);
+
+    let mut observations = Vec::new();
+    let mut series_dims: HashMap<String, String> = HashMap::new();
+    let mut obs_date: Option<String> = None;
+    let mut obs_value: Option<String> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| AppError::Provider(format!("Invalid SDMX-ML message: {}", e)))?;
+
+        let (element, is_empty) = match &event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                if e.local_name().as_ref() == b"Obs" {
+                    push_sdmx_ml_obs(
+                        &mut observations,
+                        &series_dims,
+                        key_dimensions,
+                        obs_date.take(),
+                        obs_value.take(),
+                        date_format,
+                    );
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let attrs = attributes(element);
+        match element.local_name().as_ref() {
+            b"Series" => {
+                series_dims = attrs;
+            }
+            b"Value" => {
+                // Generic format: <Value id="CURRENCY" value="USD"/> inside <SeriesKey>
+                if let (Some(id), Some(value)) = (attrs.get("id"), attrs.get("value")) {
+                    series_dims.insert(id.clone(), value.clone());
+                }
+            }
+            b"Obs" => {
+                obs_date = attrs.get("TIME_PERIOD").cloned();
+                obs_value = attrs.get("OBS_VALUE").cloned();
+                if is_empty {
+                    push_sdmx_ml_obs(
+                        &mut observations,
+                        &series_dims,
+                        key_dimensions,
+                        obs_date.take(),
+                        obs_value.take(),
+                        date_format,
+                    );
+                }
+            }
+            b"ObsDimension" => obs_date = attrs.get("value").cloned(),
+            b"ObsValue" => obs_value = attrs.get("value").cloned(),
+            _ => {}
+        }
+    }
+
+    Ok(observations)
+}
+
+fn push_sdmx_ml_obs(
+    observations: &mut Vec<Observation>,
+    series_dims: &HashMap<String, String>,
+    key_dimensions: &[String],
+    date: Option<String>,
+    value: Option<String>,
+    date_format: &str,
+) {
+    let date = date.and_then(|d| parse_date(&d, date_format));
+    let value = value.and_then(|v| v.trim().parse::<f64>().ok());
+
+    if let (Some(date), Some(value)) = (date, value) {
+        let key = key_dimensions
+            .iter()
+            .map(|dim| series_dims.get(dim).map(String::as_str).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(".");
+
+        observations.push(Observation { key, date, value });
+    }
+}
+
+fn csv_reader(body: &str, delimiter: u8) -> csv::Reader<&[u8]> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes())
+}
+
+/// Read CSV records, skipping any preamble before the row whose first
+/// field is `date_column` (data portals often prepend metadata lines).
+fn csv_records(body: &str, delimiter: u8, date_column: &str) -> Result<Vec<csv::StringRecord>> {
+    let mut records = Vec::new();
+    let mut header_found = false;
+
+    for record in csv_reader(body, delimiter).records() {
+        let record = record.map_err(|e| AppError::Provider(format!("Invalid CSV: {}", e)))?;
+
+        if !header_found {
+            if record
+                .iter()
+                .any(|field| field.eq_ignore_ascii_case(date_column))
+            {
+                header_found = true;
+                records.push(record);
+            }
+            continue;
+        }
+        records.push(record);
+    }
+
+    if !header_found {
+        return Err(AppError::Provider(format!(
+            "CSV header with column '{}' not found",
+            date_column
+        )));
+    }
+
+    Ok(records)
+}
+
+fn column_index(header: &csv::StringRecord, column: &str) -> Result<usize> {
+    header
+        .iter()
+        .position(|field| field.eq_ignore_ascii_case(column))
+        .ok_or_else(|| AppError::Provider(format!("CSV column '{}' not found", column)))
+}
+
+/// Parse a CSV with one column per series
+fn parse_csv_wide(
+    body: &str,
+    delimiter: u8,
+    date_column: &str,
+    date_format: &str,
+) -> Result<Vec<Observation>> {
+    let records = csv_records(body, delimiter, date_column)?;
+    let Some((header, rows)) = records.split_first() else {
+        return Ok(Vec::new());
+    };
+    let date_idx = column_index(header, date_column)?;
+
+    let mut observations = Vec::new();
+    for row in rows {
+        let Some(date) = row.get(date_idx).and_then(|d| parse_date(d, date_format)) else {
+            continue;
+        };
+
+        for (idx, key) in header.iter().enumerate() {
+            if idx == date_idx {
+                continue;
+            }
+            if let Some(value) = row.get(idx).and_then(|v| v.parse::<f64>().ok()) {
+                observations.push(Observation {
+                    key: key.to_string(),
+                    date,
+                    value,
+                });
+            }
+        }
+    }
+
+    Ok(observations)
+}
+
+/// Parse a CSV with one observation per row
+fn parse_csv_long(
+    body: &str,
+    delimiter: u8,
+    date_column: &str,
+    key_column: &str,
+    value_column: &str,
+    date_format: &str,
+) -> Result<Vec<Observation>> {
+    let records = csv_records(body, delimiter, date_column)?;
+    let Some((header, rows)) = records.split_first() else {
+        return Ok(Vec::new());
+    };
+    let date_idx = column_index(header, date_column)?;
+    let key_idx = column_index(header, key_column)?;
+    let value_idx = column_index(header, value_column)?;
+
+    let observations = rows
+        .iter()
+        .filter_map(|row| {
+            Some(Observation {
+                date: parse_date(row.get(date_idx)?, date_format)?,
+                key: row.get(key_idx)?.to_string(),
+                value: row.get(value_idx)?.parse().ok()?,
+            })
+        })
+        .collect();
+
+    Ok(observations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_source(format: SeriesFormat, date_format: &str) -> SeriesSource {
+        SeriesSource {
+            name: "test".to_string(),
+            description: "Test source".to_string(),
+            native_base: "EUR".to_string(),
+            native_base_name: "Euro".to_string(),
+            url_template: "https://example.com/{series}?from={start}&to={end}".to_string(),
+            url_date_format: "%Y-%m-%d".to_string(),
+            series_separator: "+".to_string(),
+            format,
+            date_format: date_format.to_string(),
+            history_start: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            fill_gaps: false,
+            publication: PublicationSchedule::business_days(None, "UTC"),
+            attribution: None,
+            series: vec![
+                SeriesMapping::new(
+                    "D.USD.EUR.SP00.A",
+                    "USD",
+                    "US Dollar",
+                    Quotation::ForeignPerBase,
+                ),
+                SeriesMapping::new(
+                    "D.JPY.EUR.SP00.A",
+                    "JPY",
+                    "Japanese Yen",
+                    Quotation::ForeignPerBase,
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_series_backed_providers() {
+        use crate::providers::{BoeProvider, SnbProvider};
+
+        let boe = BoeProvider::new();
+        assert_eq!(boe.name(), "boe");
+        assert_eq!(boe.capabilities().native_base, "GBP");
+        let snb = SnbProvider::new();
+        assert_eq!(snb.name(), "snb");
+        assert_eq!(snb.description(), SnbProvider::source().description);
+    }
+
+    #[test]
+    fn test_build_url() {
+        let provider = SeriesProvider::new(test_source(SeriesFormat::SdmxJson, "%Y-%m-%d"));
+        let url = provider.build_url(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+
+        assert_eq!(
+            url,
+            "https://example.com/D.USD.EUR.SP00.A+D.JPY.EUR.SP00.A?from=2024-01-01&to=2024-01-31"
+        );
+    }
+
+    #[test]
+    fn test_parse_sdmx_json() {
+        let body = r#"{
+            "dataSets": [{
+                "series": {
+                    "0:0:0:0:0": { "observations": { "0": [1.0956], "1": [1.0919] } },
+                    "0:1:0:0:0": { "observations": { "0": [155.73], "1": [null] } }
+                }
+            }],
+            "structure": {
+                "dimensions": {
+                    "series": [
+                        { "id": "FREQ", "values": [{ "id": "D" }] },
+                        { "id": "CURRENCY", "values": [{ "id": "USD" }, { "id": "JPY" }] },
+                        { "id": "CURRENCY_DENOM", "values": [{ "id": "EUR" }] },
+                        { "id": "EXR_TYPE", "values": [{ "id": "SP00" }] },
+                        { "id": "EXR_SUFFIX", "values": [{ "id": "A" }] }
+                    ],
+                    "observation": [
+                        { "id": "TIME_PERIOD", "values": [{ "id": "2024-01-02" }, { "id": "2024-01-03" }] }
+                    ]
+                }
+            }
+        }"#;
+
+        let provider = SeriesProvider::new(test_source(SeriesFormat::SdmxJson, "%Y-%m-%d"));
+        let observations = provider.parse(body).unwrap();
+
+        assert_eq!(observations.len(), 3);
+        assert!(observations.contains(&Observation {
+            key: "D.JPY.EUR.SP00.A".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            value: 155.73,
+        }));
+
+        let rates = provider.to_daily_rates(observations);
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates[0].base_currency, "USD");
+        let jpy = rates[0].rates.get("JPY").unwrap();
+        assert!((jpy - 155.73 / 1.0956).abs() < 0.0001);
+        let eur = rates[0].rates.get("EUR").unwrap();
+        assert!((eur - 1.0 / 1.0956).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_parse_sdmx_ml() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<message:StructureSpecificData xmlns:message="http://www.sdmx.org/resources/sdmxml/schemas/v2_1/message">
+    <message:DataSet>
+        <Series FREQ="D" CURRENCY="USD" CURRENCY_DENOM="EUR" EXR_TYPE="SP00" EXR_SUFFIX="A">
+            <Obs TIME_PERIOD="2024-01-02" OBS_VALUE="1.0956"/>
+            <Obs TIME_PERIOD="2024-01-03" OBS_VALUE="1.0919"/>
+        </Series>
+    </message:DataSet>
+</message:StructureSpecificData>"#;
+
+        let format = SeriesFormat::SdmxMl {
+            key_dimensions: [
+                "FREQ",
+                "CURRENCY",
+                "CURRENCY_DENOM",
+                "EXR_TYPE",
+                "EXR_SUFFIX",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        };
+        let provider = SeriesProvider::new(test_source(format, "%Y-%m-%d"));
+        let observations = provider.parse(body).unwrap();
+
+        assert_eq!(observations.len(), 2);
+        assert_eq!(observations[0].key, "D.USD.EUR.SP00.A");
+        assert_eq!(observations[1].value, 1.0919);
+    }
+
+    #[test]
+    fn test_parse_csv_wide() {
+        let body = "DATE,XUDLUSS,XUDLERS\n02 Jan 2024,1.2645,1.1530\n03 Jan 2024,1.2636,\n";
+
+        let mut source = test_source(
+            SeriesFormat::CsvWide {
+                delimiter: b',',
+                date_column: "DATE".to_string(),
+            },
+            "%d %b %Y",
+        );
+        source.native_base = "GBP".to_string();
+        source.series = vec![
+            SeriesMapping::new("XUDLUSS", "USD", "US Dollar", Quotation::ForeignPerBase),
+            SeriesMapping::new("XUDLERS", "EUR", "Euro", Quotation::ForeignPerBase),
+        ];
+        let provider = SeriesProvider::new(source);
+
+        let observations = provider.parse(body).unwrap();
+        assert_eq!(observations.len(), 3);
+
+        let rates = provider.to_daily_rates(observations);
+        assert_eq!(rates.len(), 2);
+        let gbp = rates[0].rates.get("GBP").unwrap();
+        assert!((gbp - 1.0 / 1.2645).abs() < 0.0001);
+        let eur = rates[0].rates.get("EUR").unwrap();
+        assert!((eur - 1.1530 / 1.2645).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_parse_csv_long_with_units() {
+        let body = "\"CubeId\";\"devkud\"\n\
+                    \"PublishingDate\";\"2024-01-04 14:30\"\n\
+                    \n\
+                    \"Date\";\"D0\";\"Value\"\n\
+                    \"2024-01-03\";\"USD1\";\"0.8512\"\n\
+                    \"2024-01-03\";\"JPY100\";\"0.5987\"\n";
+
+        let mut source = test_source(
+            SeriesFormat::CsvLong {
+                delimiter: b';',
+                date_column: "Date".to_string(),
+                key_column: "D0".to_string(),
+                value_column: "Value".to_string(),
+            },
+            "%Y-%m-%d",
+        );
+        source.native_base = "CHF".to_string();
+        source.series = vec![
+            SeriesMapping::new(
+                "USD1",
+                "USD",
+                "US Dollar",
+                Quotation::BasePerForeign { units: 1.0 },
+            ),
+            SeriesMapping::new(
+                "JPY100",
+                "JPY",
+                "Japanese Yen",
+                Quotation::BasePerForeign { units: 100.0 },
+            ),
+        ];
+        let provider = SeriesProvider::new(source);
+
+        let rates = provider.to_daily_rates(provider.parse(body).unwrap());
+        assert_eq!(rates.len(), 1);
+
+        // 1 USD = 0.8512 CHF, 100 JPY = 0.5987 CHF -> 1 USD = 0.8512 / 0.005987 JPY
+        let jpy = rates[0].rates.get("JPY").unwrap();
+        assert!((jpy - 0.8512 / 0.005987).abs() < 0.01);
+        let chf = rates[0].rates.get("CHF").unwrap();
+        assert!((chf - 0.8512).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_range_rates_fill_from_padding() {
+        let mut source = test_source(SeriesFormat::SdmxJson, "%Y-%m-%d");
+        source.fill_gaps = true;
+        let provider = SeriesProvider::new(source);
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let usd = |d, value| Observation {
+            key: "D.USD.EUR.SP00.A".to_string(),
+            date: date(d),
+            value,
+        };
+
+        // Saturday to Monday (a holiday), filled from the Friday before the range
+        let rates = provider.range_rates(vec![usd(5, 1.0956), usd(9, 1.0919)], date(6), date(8));
+
+        let dates: Vec<_> = rates.iter().map(|r| r.date).collect();
+        assert_eq!(dates, [date(6), date(7), date(8)]);
+        assert!(rates.iter().all(|r| r.estimated));
+        assert!((rates[2].rates["EUR"] - 1.0 / 1.0956).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,80 @@
+use chrono::NaiveDate;
+
+use crate::providers::PublicationSchedule;
+use crate::providers::sdmx::{
+    Quotation, SeriesBacked, SeriesFormat, SeriesMapping, SeriesProvider, SeriesSource,
+};
+
+const SNB_DAILY_CUBE_URL: &str =
+    "https://data.snb.ch/api/cube/devkud/data/csv/en?fromDate={start}&toDate={end}";
+
+/// Daily cube series (CHF per `units` of currency)
+const SNB_SERIES: &[(&str, &str, &str, f64)] = &[
+    ("USD1", "USD", "US Dollar", 1.0),
+    ("EUR1", "EUR", "Euro", 1.0),
+    ("GBP1", "GBP", "British Pound", 1.0),
+    ("JPY100", "JPY", "Japanese Yen", 100.0),
+];
+
+/// Swiss National Bank provider
+/// Fetches CHF-based rates from the SNB data portal
+pub struct SnbProvider {
+    inner: SeriesProvider,
+}
+
+impl SnbProvider {
+    pub fn new() -> Self {
+        Self {
+            inner: SeriesProvider::new(Self::source()),
+        }
+    }
+
+    /// Series source definition for the SNB data portal CSV export
+    pub fn source() -> SeriesSource {
+        let series = SNB_SERIES
+            .iter()
+            .map(|(key, currency, name, units)| {
+                SeriesMapping::new(
+                    key,
+                    currency,
+                    name,
+                    Quotation::BasePerForeign { units: *units },
+                )
+            })
+            .collect();
+
+        SeriesSource {
+            name: "snb".to_string(),
+            description: "Swiss National Bank - Daily CHF exchange rates".to_string(),
+            native_base: "CHF".to_string(),
+            native_base_name: "Swiss Franc".to_string(),
+            url_template: SNB_DAILY_CUBE_URL.to_string(),
+            url_date_format: "%Y-%m-%d".to_string(),
+            series_separator: ",".to_string(),
+            format: SeriesFormat::CsvLong {
+                delimiter: b';',
+                date_column: "Date".to_string(),
+                key_column: "D0".to_string(),
+                value_column: "Value".to_string(),
+            },
+            date_format: "%Y-%m-%d".to_string(),
+            history_start: NaiveDate::from_ymd_opt(1999, 1, 4).unwrap(),
+            fill_gaps: true,
+            publication: PublicationSchedule::business_days(None, "Europe/Zurich"),
+            attribution: Some("Source: Swiss National Bank (SNB), data.snb.ch".to_string()),
+            series,
+        }
+    }
+}
+
+impl Default for SnbProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SeriesBacked for SnbProvider {
+    fn series(&self) -> &SeriesProvider {
+        &self.inner
+    }
+}
@@ -0,0 +1,321 @@
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{DailyRates, QualityIssue, QualityReason, QuarantinedRate};
+
+/// Default largest accepted day-over-day change (25%)
+pub const DEFAULT_MAX_CHANGE: f64 = 0.25;
+
+/// Published days a changed rate has to hold before it is accepted as the
+/// currency's new level (a devaluation rather than a bad value)
+pub const NEW_LEVEL_DAYS: usize = 2;
+
+/// Limits applied to fetched rates before they are stored
+#[derive(Debug, Clone)]
+pub struct QualityRules {
+    /// Largest accepted relative change from the previous accepted rate
+    /// (`QUALITY_MAX_CHANGE`, 0.25 = 25%)
+    pub max_change: f64,
+    /// Per-currency limits (`QUALITY_MAX_CHANGE_BY_CURRENCY=ARS:0.6,UAH:0.4`)
+    pub max_change_by_currency: HashMap<String, f64>,
+}
+
+impl Default for QualityRules {
+    fn default() -> Self {
+        Self {
+            max_change: DEFAULT_MAX_CHANGE,
+            max_change_by_currency: HashMap::new(),
+        }
+    }
+}
+
+impl QualityRules {
+    fn max_change_for(&self, currency: &str) -> f64 {
+        self.max_change_by_currency
+            .get(currency)
+            .copied()
+            .unwrap_or(self.max_change)
+    }
+
+    /// Check fetched rates in date order and remove the ones that fail.
+    ///
+    /// Non-finite and non-positive rates are dropped. A rate that moved more than
+    /// the allowed change from the last accepted rate of its currency (from
+    /// `previous`, then from earlier days of the batch) is removed as a `Jump`.
+    /// Jumped rates that agree with each other form a new level, which is
+    /// accepted once it held for `NEW_LEVEL_DAYS` published days: its rates are
+    /// put back, including the ones quarantined by earlier syncs (`earlier`,
+    /// added to `rates` as days of their own), and become the new baseline.
+    /// Gap-filled copies of a jumped rate are removed without an issue.
+    pub fn check(
+        &self,
+        rates: &mut Vec<DailyRates>,
+        mut previous: HashMap<String, f64>,
+        earlier: &[QuarantinedRate],
+    ) -> Vec<QualityIssue> {
+        rates.sort_by_key(|daily| daily.date);
+
+        let mut levels: HashMap<String, Level> = HashMap::new();
+        for quarantined in earlier
+            .iter()
+            .filter(|q| q.reason == QualityReason::Jump.as_str())
+        {
+            let code = &quarantined.target_currency;
+            let level = self.level(&mut levels, code, quarantined.rate);
+            level.days += 1;
+            level
+                .cells
+                .push(Cell::Earlier(quarantined.date, quarantined.rate));
+        }
+
+        let mut issues: Vec<Option<QualityIssue>> = Vec::new();
+        let mut restored: BTreeMap<NaiveDate, HashMap<String, f64>> = BTreeMap::new();
+        for index in 0..rates.len() {
+            let daily = &mut rates[index];
+            let base = daily.base_currency.clone();
+            let mut codes: Vec<String> = daily.rates.keys().cloned().collect();
+            codes.sort();
+
+            for code in codes {
+                if code == base {
+                    continue;
+                }
+                let daily = &mut rates[index];
+                let rate = daily.rates[&code];
+
+                if !rate.is_finite() || rate <= 0.0 {
+                    daily.rates.remove(&code);
+                    issues.push(Some(QualityIssue {
+                        date: daily.date,
+                        currency: code.clone(),
+                        rate,
+                        reason: QualityReason::InvalidRate,
+                        previous_rate: None,
+                        change: None,
+                    }));
+                    continue;
+                }
+
+                let prev = match previous.get(&code) {
+                    Some(&prev) if (rate / prev - 1.0).abs() > self.max_change_for(&code) => prev,
+                    _ => {
+                        // Back at the accepted level: whatever jumped was a spike
+                        levels.remove(&code);
+                        previous.insert(code, rate);
+                        continue;
+                    }
+                };
+
+                daily.rates.remove(&code);
+                let issue = (!daily.estimated).then(|| {
+                    issues.push(Some(QualityIssue {
+                        date: daily.date,
+                        currency: code.clone(),
+                        rate,
+                        reason: QualityReason::Jump,
+                        previous_rate: Some(prev),
+                        change: Some(rate / prev - 1.0),
+                    }));
+                    issues.len() - 1
+                });
+
+                let level = self.level(&mut levels, &code, rate);
+                level.days += usize::from(issue.is_some());
+                level.cells.push(Cell::Batch { index, rate, issue });
+                if level.days < NEW_LEVEL_DAYS {
+                    continue;
+                }
+
+                let Some(level) = levels.remove(&code) else {
+                    continue;
+                };
+                for cell in level.cells {
+                    match cell {
+                        Cell::Batch { index, rate, issue } => {
+                            rates[index].rates.insert(code.clone(), rate);
+                            if let Some(issue) = issue {
+                                issues[issue] = None;
+                            }
+                        }
+                        Cell::Earlier(date, rate) => {
+                            restored.entry(date).or_default().insert(code.clone(), rate);
+                        }
+                    }
+                }
+                previous.insert(code, rate);
+            }
+        }
+
+        if let Some(first) = rates.first().cloned() {
+            rates.extend(restored.into_iter().map(|(date, restored)| DailyRates {
+                date,
+                rates: restored,
+                estimated: false,
+                ..first.clone()
+            }));
+            rates.sort_by_key(|daily| daily.date);
+        }
+
+        issues.into_iter().flatten().collect()
+    }
+
+    /// Level of jumped rates of `code` that `rate` belongs to: the current
+    /// one if `rate` agrees with it, otherwise a new one
+    fn level<'a>(
+        &self,
+        levels: &'a mut HashMap<String, Level>,
+        code: &str,
+        rate: f64,
+    ) -> &'a mut Level {
+        let max_change = self.max_change_for(code);
+        let level = levels.entry(code.to_string()).or_default();
+        if level
+            .cells
+            .last()
+            .is_some_and(|cell| (rate / cell.rate() - 1.0).abs() > max_change)
+        {
+            *level = Level::default();
+        }
+        level
+    }
+}
+
+/// Rates of one currency that jumped from its accepted rate but agree with
+/// each other
+#[derive(Debug, Default)]
+struct Level {
+    /// Published days in the level
+    days: usize,
+    cells: Vec<Cell>,
+}
+
+#[derive(Debug)]
+enum Cell {
+    /// Rate of `rates[index]`, and its issue unless it was gap-filled
+    Batch {
+        index: usize,
+        rate: f64,
+        issue: Option<usize>,
+    },
+    /// Rate quarantined by an earlier sync
+    Earlier(NaiveDate, f64),
+}
+
+impl Cell {
+    fn rate(&self) -> f64 {
+        match self {
+            Cell::Batch { rate, .. } | Cell::Earlier(_, rate) => *rate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn daily(day: u32, rates: &[(&str, f64)]) -> DailyRates {
+        DailyRates {
+            date: NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+            base_currency: "USD".to_string(),
+            rates: rates.iter().map(|(c, r)| (c.to_string(), *r)).collect(),
+            provider: "nbu".to_string(),
+            estimated: false,
+        }
+    }
+
+    #[test]
+    fn test_check_rates() {
+        let rules = QualityRules {
+            max_change_by_currency: HashMap::from([("ARS".to_string(), 0.6)]),
+            ..Default::default()
+        };
+        let mut rates = vec![
+            daily(4, &[("UAH", 41.6), ("EUR", 0.0), ("ARS", 1500.0)]),
+            daily(3, &[("UAH", 41.5), ("EUR", f64::NAN), ("ARS", 1000.0)]),
+            daily(5, &[("UAH", 4160.0), ("EUR", 0.95)]),
+            daily(6, &[("UAH", 41.7)]),
+        ];
+        let previous = HashMap::from([("ARS".to_string(), 900.0)]);
+
+        let issues = rules.check(&mut rates, previous, &[]);
+        let found: Vec<(u32, &str, QualityReason)> = issues
+            .iter()
+            .map(|i| (i.date.day(), i.currency.as_str(), i.reason))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, "EUR", QualityReason::InvalidRate),
+                (4, "EUR", QualityReason::InvalidRate),
+                (5, "UAH", QualityReason::Jump),
+            ]
+        );
+
+        // Sorted by date, bad rates removed, UAH on the 6th compared with the 4th
+        assert_eq!(rates[2].rates.get("UAH"), None);
+        assert_eq!(rates[3].rates.get("UAH"), Some(&41.7));
+        assert_eq!(rates[1].rates.get("ARS"), Some(&1500.0));
+    }
+
+    #[test]
+    fn test_new_level_accepted() {
+        let rules = QualityRules::default();
+        // A devaluation of 40% that holds
+        let mut rates = vec![
+            daily(3, &[("UAH", 16.0), ("EUR", 0.9)]),
+            daily(4, &[("UAH", 22.4), ("EUR", 0.9)]),
+            daily(5, &[("UAH", 22.5)]),
+            daily(6, &[("UAH", 22.6)]),
+        ];
+        let previous = HashMap::from([("UAH".to_string(), 16.0)]);
+
+        assert!(rules.check(&mut rates, previous, &[]).is_empty());
+        let uah: Vec<Option<&f64>> = rates.iter().map(|d| d.rates.get("UAH")).collect();
+        assert_eq!(uah, [Some(&16.0), Some(&22.4), Some(&22.5), Some(&22.6)]);
+    }
+
+    #[test]
+    fn test_new_level_confirms_earlier_quarantine() {
+        let rules = QualityRules::default();
+        let earlier = QuarantinedRate {
+            id: 1,
+            date: NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            base_currency: "USD".to_string(),
+            target_currency: "UAH".to_string(),
+            provider: "nbu".to_string(),
+            rate: 22.4,
+            previous_rate: Some(16.0),
+            reason: "jump".to_string(),
+            detected_at: "2025-03-03 12:00:00".to_string(),
+        };
+        let mut rates = vec![daily(4, &[("UAH", 22.5), ("EUR", 0.9)])];
+        let previous = HashMap::from([("UAH".to_string(), 16.0)]);
+
+        assert!(rules.check(&mut rates, previous, &[earlier]).is_empty());
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates[0].date.day(), 3);
+        assert_eq!(rates[0].rates, HashMap::from([("UAH".to_string(), 22.4)]));
+        assert_eq!(rates[0].provider, "nbu");
+        assert_eq!(rates[1].rates.get("UAH"), Some(&22.5));
+    }
+
+    #[test]
+    fn test_gap_filled_copies_of_a_spike() {
+        let rules = QualityRules::default();
+        let mut filled = daily(5, &[("UAH", 4160.0)]);
+        filled.estimated = true;
+        let mut rates = vec![
+            daily(3, &[("UAH", 41.5)]),
+            daily(4, &[("UAH", 4160.0)]),
+            filled,
+            daily(6, &[("UAH", 41.7)]),
+        ];
+
+        let issues = rules.check(&mut rates, HashMap::new(), &[]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].date.day(), 4);
+        assert!(rates[1].rates.is_empty() && rates[2].rates.is_empty());
+        assert_eq!(rates[3].rates.get("UAH"), Some(&41.7));
+    }
+}
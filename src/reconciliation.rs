@@ -0,0 +1,154 @@
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{
+    DivergentDay, ExchangeRate, RateDiscrepancy, ReconciliationPair, ReconciliationReport,
+};
+
+/// Default relative difference above which providers are reported as diverging (1%)
+pub const DEFAULT_TOLERANCE: f64 = 0.01;
+
+/// Days up to today compared again after every sync job that stored rates
+pub const RECONCILIATION_WINDOW_DAYS: i64 = 7;
+
+/// Difference of two rates relative to their mean
+pub fn relative_diff(a: f64, b: f64) -> f64 {
+    (a - b).abs() / ((a + b) / 2.0)
+}
+
+/// Compare every pair of providers publishing the same (date, currency)
+pub fn compare(rates: &[ExchangeRate]) -> Vec<RateDiscrepancy> {
+    let mut cells: BTreeMap<(NaiveDate, &str), Vec<&ExchangeRate>> = BTreeMap::new();
+    for rate in rates {
+        cells
+            .entry((rate.date, rate.target_currency.as_str()))
+            .or_default()
+            .push(rate);
+    }
+
+    let mut discrepancies = Vec::new();
+    for ((date, currency), mut published) in cells {
+        published.sort_by(|a, b| a.provider.cmp(&b.provider));
+        for (i, a) in published.iter().enumerate() {
+            for b in &published[i + 1..] {
+                discrepancies.push(RateDiscrepancy {
+                    date,
+                    currency: currency.to_string(),
+                    provider_a: a.provider.clone(),
+                    rate_a: a.rate,
+                    provider_b: b.provider.clone(),
+                    rate_b: b.rate,
+                    diff: relative_diff(a.rate, b.rate),
+                });
+            }
+        }
+    }
+
+    discrepancies
+}
+
+/// Summarize stored comparisons per pair and provider pair, keeping the
+/// pairs that diverged beyond `tolerance` on at least one day
+pub fn build_report(
+    start: NaiveDate,
+    end: NaiveDate,
+    tolerance: f64,
+    base: &str,
+    discrepancies: Vec<RateDiscrepancy>,
+) -> ReconciliationReport {
+    let mut grouped: HashMap<(String, String, String), Vec<RateDiscrepancy>> = HashMap::new();
+    for d in discrepancies {
+        grouped
+            .entry((
+                d.currency.clone(),
+                d.provider_a.clone(),
+                d.provider_b.clone(),
+            ))
+            .or_default()
+            .push(d);
+    }
+    let compared_pairs = grouped.len();
+
+    let mut pairs: Vec<ReconciliationPair> = grouped
+        .into_iter()
+        .map(|((currency, provider_a, provider_b), days)| {
+            let days_compared = days.len();
+            let max_diff = days.iter().map(|d| d.diff).fold(0.0, f64::max);
+            let mean_diff = days.iter().map(|d| d.diff).sum::<f64>() / days_compared as f64;
+            let divergences: Vec<DivergentDay> = days
+                .into_iter()
+                .filter(|d| d.diff > tolerance)
+                .map(|d| DivergentDay {
+                    date: d.date,
+                    rates: BTreeMap::from([
+                        (d.provider_a.clone(), d.rate_a),
+                        (d.provider_b.clone(), d.rate_b),
+                    ]),
+                    diff: d.diff,
+                })
+                .collect();
+
+            ReconciliationPair {
+                pair: format!("{}/{}", base, currency),
+                providers: vec![provider_a, provider_b],
+                days_compared,
+                days_diverging: divergences.len(),
+                max_diff,
+                mean_diff,
+                divergences,
+            }
+        })
+        .filter(|pair| pair.days_diverging > 0)
+        .collect();
+    pairs.sort_by(|a, b| b.max_diff.total_cmp(&a.max_diff));
+
+    ReconciliationReport {
+        start_date: start,
+        end_date: end,
+        tolerance,
+        compared_pairs,
+        pairs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(day: u32, currency: &str, provider: &str, rate: f64) -> ExchangeRate {
+        ExchangeRate {
+            date: NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+            base_currency: "USD".to_string(),
+            target_currency: currency.to_string(),
+            rate,
+            provider: provider.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reconciliation_report() {
+        let rates = vec![
+            rate(3, "EUR", "nbu", 0.9200),
+            rate(3, "EUR", "ecb", 0.9210),
+            rate(4, "EUR", "ecb", 0.9200),
+            rate(4, "EUR", "nbu", 0.9500),
+            rate(3, "GBP", "boe", 0.7800),
+            rate(3, "GBP", "ecb", 0.7801),
+        ];
+        let discrepancies = compare(&rates);
+        assert_eq!(discrepancies.len(), 3);
+        assert_eq!(discrepancies[0].provider_a, "ecb");
+
+        let start = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        let report = build_report(start, end, DEFAULT_TOLERANCE, "USD", discrepancies);
+
+        assert_eq!(report.compared_pairs, 2);
+        assert_eq!(report.pairs.len(), 1);
+        let pair = &report.pairs[0];
+        assert_eq!(pair.pair, "USD/EUR");
+        assert_eq!(pair.providers, vec!["ecb", "nbu"]);
+        assert_eq!((pair.days_compared, pair.days_diverging), (2, 1));
+        assert_eq!(pair.divergences[0].rates["nbu"], 0.95);
+    }
+}
@@ -72,6 +72,7 @@ pub fn parse_nbu_seed_file(path: &Path) -> Result<Vec<DailyRates>> {
             base_currency: INTERNAL_BASE.to_string(),
             rates: usd_rates,
             provider: "nbu".to_string(),
+            estimated: false,
         });
     }
 
@@ -172,6 +173,7 @@ fn parse_ecb_xml(xml: &str) -> Result<Vec<DailyRates>> {
             base_currency: "USD".to_string(),
             rates: usd_rates,
             provider: "ecb".to_string(),
+            estimated: false,
         });
     }
 
@@ -1,15 +1,112 @@
-use chrono::NaiveDate;
-use std::collections::HashMap;
-use std::sync::Arc;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use futures::future::join_all;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tracing::Instrument;
 
-use crate::db::RatesRepository;
-use crate::error::{AppError, Result};
-use crate::models::{RatesResponse, TimeSeriesResponse};
-use crate::providers::ProviderRegistry;
+use crate::config::{PartialDataPolicy, ProviderSettings};
+use crate::db::{RatesRepository, RevisionFilter, SyncHistoryFilter};
+use crate::error::{AppError, DateSpan, ErrorCode, ParamError, Result, Suggestions};
+use crate::metrics;
+use crate::models::{
+    ApiKey, ApiUsage, BackfillMode, BackfillRequest, DailyRates, ExchangeRate, ManualRate,
+    ManualRateAudit, ProviderSyncProgress, QualityIssue, QualityReason, QuarantinedRate,
+    RateRevision, RatesResponse, ReconciliationReport, Scope, SyncHistoryResponse, SyncJob,
+    SyncStatus, SyncTrigger, TimeSeriesResponse,
+};
+use crate::providers::{MANUAL_PROVIDER, Provider, ProviderRegistry, PublicationSchedule};
+use crate::quality::QualityRules;
+use crate::reconciliation;
+use crate::usage::MIN_KEY_RATE;
 
 /// Internal storage base currency - all providers store rates relative to USD
 const INTERNAL_BASE: &str = "USD";
 
+/// Finished sync jobs kept in memory for `GET /v1/sync/jobs/{id}`
+const MAX_TRACKED_JOBS: usize = 100;
+
+/// Quality findings listed per provider in a sync job (all are counted)
+const MAX_REPORTED_ISSUES: usize = 20;
+
+/// Prefix of generated API keys, so leaked keys are easy to recognize
+const API_KEY_PREFIX: &str = "cr_";
+
+/// Minimum interval between `last_used_at` writes for the same API key
+const API_KEY_TOUCH_INTERVAL_SECS: i64 = 60;
+
+/// Holes closer than this are refetched with one request range
+const BACKFILL_MERGE_GAP_DAYS: i64 = 7;
+
+/// Options affecting how stored rates are resolved for a query
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RateQueryOptions {
+    /// Apply manual override rates on top of provider rates
+    pub overrides: bool,
+    /// Answer with the data stored at this time, before later revisions and backfills
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Providers claimed by a running sync job. Claims still held when the job's
+/// future is dropped (e.g. the request waiting for it went away) are released,
+/// and the job is marked failed instead of staying `running`.
+struct JobClaims<'a> {
+    service: &'a RatesService,
+    job_id: &'a str,
+    held: Mutex<HashSet<String>>,
+}
+
+impl JobClaims<'_> {
+    /// Let other jobs sync `name` again
+    fn release(&self, name: &str) {
+        if self.held.lock().unwrap().remove(name) {
+            self.service.syncing.lock().unwrap().remove(name);
+        }
+    }
+}
+
+impl Drop for JobClaims<'_> {
+    fn drop(&mut self) {
+        let held = std::mem::take(self.held.get_mut().unwrap());
+        let mut syncing = self.service.syncing.lock().unwrap();
+        for name in &held {
+            syncing.remove(name);
+        }
+        drop(syncing);
+
+        let finished_at = Utc::now();
+        self.service.update_job(self.job_id, |job| {
+            if job.status != SyncStatus::Running {
+                return;
+            }
+            tracing::warn!("Sync job {} cancelled", job.id);
+            for run in &mut job.providers {
+                if !run.status.is_finished() {
+                    run.status = SyncStatus::Error;
+                    run.error = Some("Sync cancelled".to_string());
+                }
+            }
+            job.status = SyncStatus::Error;
+            job.finished_at = Some(finished_at);
+            job.duration_ms = Some((finished_at - job.created_at).num_milliseconds());
+        });
+    }
+}
+
+/// Result of syncing a single provider
+#[derive(Debug, Clone, Default)]
+pub struct SyncOutcome {
+    /// Number of rate records stored
+    pub records: usize,
+    /// Parts (e.g. currencies) that could not be fetched; stored data is partial
+    pub missing: Vec<String>,
+    /// Missing (date, currency) cells found by a fill-missing backfill
+    pub missing_cells: Option<usize>,
+    /// Rates dropped or quarantined by the data quality checks
+    pub issues: Vec<QualityIssue>,
+}
+
 /// Service for currency rate operations
 /// Handles base currency conversion and data aggregation
 ///
@@ -20,6 +117,20 @@ pub struct RatesService {
     providers: Arc<ProviderRegistry>,
     /// Default base currency for API responses (when client doesn't specify)
     default_api_base: String,
+    /// Providers waiting to be synced again after partial data, with the due time
+    pending_retries: Mutex<HashMap<String, DateTime<Utc>>>,
+    /// Serializes rate writes so parallel syncs don't contend for the SQLite write lock
+    write_lock: tokio::sync::Mutex<()>,
+    /// Recent sync jobs, oldest first
+    jobs: Mutex<VecDeque<SyncJob>>,
+    /// Providers claimed by a queued or running job
+    syncing: Mutex<HashSet<String>>,
+    /// Checks applied to fetched rates before they are stored
+    quality: QualityRules,
+    /// Default relative difference reported as a cross-provider divergence
+    reconciliation_tolerance: f64,
+    /// Last time each API key's `last_used_at` was written
+    key_usage: Mutex<HashMap<i64, DateTime<Utc>>>,
 }
 
 impl RatesService {
@@ -32,9 +143,28 @@ impl RatesService {
             repository,
             providers,
             default_api_base,
+            pending_retries: Mutex::new(HashMap::new()),
+            write_lock: tokio::sync::Mutex::new(()),
+            jobs: Mutex::new(VecDeque::new()),
+            syncing: Mutex::new(HashSet::new()),
+            quality: QualityRules::default(),
+            reconciliation_tolerance: reconciliation::DEFAULT_TOLERANCE,
+            key_usage: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Use custom data quality limits
+    pub fn with_quality_rules(mut self, quality: QualityRules) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Use a custom default reconciliation tolerance
+    pub fn with_reconciliation_tolerance(mut self, tolerance: f64) -> Self {
+        self.reconciliation_tolerance = tolerance;
+        self
+    }
+
     /// Convert rates from one base currency to another
     /// If rates are EUR-based and we want USD-based:
     /// New rate = Original EUR rate / EUR->USD rate
@@ -49,9 +179,13 @@ impl RatesService {
 
         // Get the conversion rate from the target base in the original rates
         // e.g., if from_base=EUR, to_base=USD, we need the USD rate in EUR terms
-        let conversion_rate = rates
-            .get(to_base)
-            .ok_or_else(|| AppError::InvalidCurrency(to_base.to_string()))?;
+        let conversion_rate = rates.get(to_base).ok_or_else(|| {
+            ParamError::new(
+                ErrorCode::CurrencyUnavailable,
+                format!("No {} rate to convert to", to_base),
+            )
+            .param("from", to_base)
+        })?;
 
         let mut converted: HashMap<String, f64> = HashMap::new();
 
@@ -73,6 +207,123 @@ impl RatesService {
         Ok(converted)
     }
 
+    /// Known codes differing from `code` in one letter, e.g. EUR for EUT
+    fn similar_codes<'a>(code: &str, known: impl Iterator<Item = &'a String>) -> Vec<String> {
+        let mut similar: Vec<String> = known
+            .filter(|k| {
+                k.len() == code.len()
+                    && k.chars().zip(code.chars()).filter(|(a, b)| a != b).count() == 1
+            })
+            .cloned()
+            .collect();
+        similar.sort();
+        similar.truncate(5);
+        similar
+    }
+
+    /// Dates each currency has rates for; USD, the internal base, spans all
+    async fn currency_spans(&self) -> Result<HashMap<String, DateSpan>> {
+        let mut spans = HashMap::new();
+        let mut all: Option<DateSpan> = None;
+        for (code, info) in self.repository.get_currencies(None).await? {
+            let (Some(start_date), Some(end_date)) = (info.min_date, info.max_date) else {
+                continue;
+            };
+            all = Some(match all {
+                Some(all) => DateSpan {
+                    start_date: all.start_date.min(start_date),
+                    end_date: all.end_date.max(end_date),
+                },
+                None => DateSpan {
+                    start_date,
+                    end_date,
+                },
+            });
+            spans.insert(
+                code,
+                DateSpan {
+                    start_date,
+                    end_date,
+                },
+            );
+        }
+        if let Some(all) = all {
+            spans.insert(INTERNAL_BASE.to_string(), all);
+        }
+        Ok(spans)
+    }
+
+    /// Error for a currency of a rate query (`param`) without rates on
+    /// `dates`: unknown codes get similar known ones, known codes the dates
+    /// they have rates for
+    async fn missing_currency(&self, param: &str, code: &str, dates: &str) -> AppError {
+        let spans = match self.currency_spans().await {
+            Ok(spans) => spans,
+            Err(e) => return e,
+        };
+
+        let error = match spans.get(code) {
+            Some(span) => ParamError::new(
+                ErrorCode::CurrencyUnavailable,
+                format!(
+                    "No {} rates for {}, available from {} to {}",
+                    code, dates, span.start_date, span.end_date
+                ),
+            )
+            .with_suggestions(Suggestions {
+                available: Some(span.clone()),
+                ..Default::default()
+            }),
+            None => ParamError::new(
+                ErrorCode::UnknownCurrency,
+                format!("Unknown currency: {}", code),
+            )
+            .with_suggestions(Suggestions {
+                currencies: Self::similar_codes(code, spans.keys()),
+                ..Default::default()
+            }),
+        };
+        error.param(param, code).into()
+    }
+
+    /// Error for a rate query without any rates in `start..=end`, pointing
+    /// to the closest dates that have them
+    async fn no_data(&self, start: NaiveDate, end: NaiveDate) -> AppError {
+        let nearby = match self
+            .repository
+            .get_nearby_dates(start, end, INTERNAL_BASE)
+            .await
+        {
+            Ok(nearby) => nearby,
+            Err(e) => return e,
+        };
+
+        let dates = if start == end {
+            start.to_string()
+        } else {
+            format!("{}..{}", start, end)
+        };
+        let available = match (nearby.first_date, nearby.last_date) {
+            (Some(start_date), Some(end_date)) => Some(DateSpan {
+                start_date,
+                end_date,
+            }),
+            _ => None,
+        };
+
+        ParamError::new(ErrorCode::NoData, format!("No rates for {}", dates))
+            .param("date", dates)
+            .with_suggestions(Suggestions {
+                available,
+                nearest_dates: [nearby.before, nearby.after]
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                ..Default::default()
+            })
+            .into()
+    }
+
     /// Round rate to reasonable precision (6 decimal places)
     fn round_rate(rate: f64) -> f64 {
         (rate * 1_000_000.0).round() / 1_000_000.0
@@ -87,56 +338,720 @@ impl RatesService {
             .collect()
     }
 
-    /// Sync rates from all providers
-    pub async fn sync_all_providers(&self) -> Result<()> {
-        for provider in self.providers.all() {
-            tracing::info!("Syncing rates from provider: {}", provider.name());
-
-            match self.sync_provider(provider.name()).await {
-                Ok(count) => {
-                    tracing::info!("Synced {} rates from {}", count, provider.name());
-                    self.repository
-                        .log_sync(provider.name(), count, "success")
-                        .await?;
+    /// Apply manual rates on top of USD-based rates (manual rates win).
+    /// A manual rate `1 BASE = rate TARGET` is expressed in USD terms using the
+    /// already resolved USD/BASE or USD/TARGET rate.
+    fn apply_manual_rates(usd_rates: &mut HashMap<String, f64>, manual: &[&ManualRate]) {
+        // Pairs involving USD first, so crosses can build on them
+        let mut manual = manual.to_vec();
+        manual.sort_by_key(|m| {
+            m.base_currency != INTERNAL_BASE && m.target_currency != INTERNAL_BASE
+        });
+
+        for m in manual {
+            let (base, target) = (m.base_currency.as_str(), m.target_currency.as_str());
+
+            if base == INTERNAL_BASE {
+                usd_rates.insert(target.to_string(), m.rate);
+            } else if target == INTERNAL_BASE {
+                usd_rates.insert(base.to_string(), 1.0 / m.rate);
+            } else if let Some(&usd_base) = usd_rates.get(base) {
+                // USD/TARGET = USD/BASE * BASE/TARGET
+                usd_rates.insert(target.to_string(), usd_base * m.rate);
+            } else if let Some(&usd_target) = usd_rates.get(target) {
+                // USD/BASE = USD/TARGET / BASE/TARGET
+                usd_rates.insert(base.to_string(), usd_target / m.rate);
+            } else {
+                tracing::warn!(
+                    "Manual rate {}/{} on {} has no {} reference, skipping",
+                    base,
+                    target,
+                    m.date,
+                    INTERNAL_BASE
+                );
+            }
+        }
+    }
+
+    /// Sorted list of flagged (derived or revised) currencies that appear in a response,
+    /// either as the requested base or among the returned rates
+    fn derived_labels(
+        derived: HashSet<String>,
+        base: &str,
+        in_rates: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let mut labels: Vec<String> = derived
+            .into_iter()
+            .filter(|c| c == base || in_rates(c))
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    /// Providers synced by `POST /sync` and the scheduler, in sync order
+    pub fn enabled_provider_names(&self) -> Vec<String> {
+        self.providers.enabled_names()
+    }
+
+    /// Sync rates from all enabled providers
+    pub async fn sync_all_providers(&self, trigger: SyncTrigger) -> Result<SyncJob> {
+        self.sync_providers(&self.providers.enabled_names(), trigger)
+            .await
+    }
+
+    /// Sync the given providers as a job and wait for it to finish.
+    /// Providers another job is already syncing are skipped.
+    pub async fn sync_providers(&self, names: &[String], trigger: SyncTrigger) -> Result<SyncJob> {
+        let job = self.create_job(names, trigger, None, false)?;
+        self.run_sync_job(&job.id).await
+    }
+
+    /// Queue a sync job for `names` without running it (see `run_sync_job`).
+    /// Fails with a conflict if another job is already syncing one of them.
+    pub fn create_sync_job(&self, names: &[String], trigger: SyncTrigger) -> Result<SyncJob> {
+        for name in names {
+            self.check_syncable(name)?;
+        }
+        self.create_job(names, trigger, None, true)
+    }
+
+    /// Queue a job refetching `request.start..=request.end` for one provider
+    /// (see `run_sync_job`). Fails with a conflict if the provider is being synced.
+    pub fn create_backfill_job(
+        &self,
+        name: &str,
+        request: BackfillRequest,
+        trigger: SyncTrigger,
+    ) -> Result<SyncJob> {
+        self.check_syncable(name)?;
+
+        let today = Utc::now().date_naive();
+        if request.start > request.end {
+            return Err(AppError::InvalidDate(
+                "Start date must be before or equal to end date".to_string(),
+            ));
+        }
+        if request.start > today {
+            return Err(AppError::InvalidDate(
+                "Backfill range is in the future".to_string(),
+            ));
+        }
+        let request = BackfillRequest {
+            end: request.end.min(today),
+            ..request
+        };
+
+        self.create_job(&[name.to_string()], trigger, Some(request), true)
+    }
+
+    fn check_syncable(&self, name: &str) -> Result<()> {
+        if self.providers.get(name).is_none() {
+            return Err(AppError::NotFound(format!("Unknown provider: {}", name)));
+        }
+        if !self.providers.is_enabled(name) {
+            return Err(AppError::Validation(format!(
+                "Provider {} is disabled",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Claim the providers and register a queued job. Providers that are already
+    /// claimed either fail the job (`conflict_is_error`) or are marked skipped.
+    fn create_job(
+        &self,
+        names: &[String],
+        trigger: SyncTrigger,
+        backfill: Option<BackfillRequest>,
+        conflict_is_error: bool,
+    ) -> Result<SyncJob> {
+        let mut progress: Vec<ProviderSyncProgress> = names
+            .iter()
+            .map(|name| ProviderSyncProgress::queued(name))
+            .collect();
+
+        {
+            let mut syncing = self.syncing.lock().unwrap();
+            let busy: Vec<&str> = names
+                .iter()
+                .filter(|name| syncing.contains(*name))
+                .map(String::as_str)
+                .collect();
+
+            if !busy.is_empty() && conflict_is_error {
+                return Err(AppError::Conflict(format!(
+                    "Sync already running for: {}",
+                    busy.join(", ")
+                )));
+            }
+
+            for run in &mut progress {
+                if syncing.insert(run.provider.clone()) {
+                    continue;
+                }
+                tracing::warn!("Skipping {}: already being synced", run.provider);
+                run.status = SyncStatus::Skipped;
+                run.error = Some("Sync already running".to_string());
+            }
+        }
+
+        let job = SyncJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            status: SyncStatus::Queued,
+            trigger,
+            backfill,
+            created_at: Utc::now(),
+            finished_at: None,
+            duration_ms: None,
+            records: 0,
+            providers: progress,
+        };
+
+        let mut jobs = self.jobs.lock().unwrap();
+        while jobs.len() >= MAX_TRACKED_JOBS {
+            match jobs.iter().position(|j| j.status.is_finished()) {
+                Some(idx) => jobs.remove(idx),
+                None => break,
+            };
+        }
+        jobs.push_back(job.clone());
+
+        Ok(job)
+    }
+
+    /// Current state of a sync job
+    pub fn get_sync_job(&self, id: &str) -> Option<SyncJob> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|job| job.id == id)
+            .cloned()
+    }
+
+    fn update_job(&self, id: &str, update: impl FnOnce(&mut SyncJob)) {
+        if let Some(job) = self.jobs.lock().unwrap().iter_mut().find(|j| j.id == id) {
+            update(job);
+        }
+    }
+
+    /// Run a queued job. Independent providers run in parallel; providers
+    /// computed from stored rates run afterwards, in order.
+    #[tracing::instrument(name = "sync_job", skip_all, fields(job_id = %id))]
+    pub async fn run_sync_job(&self, id: &str) -> Result<SyncJob> {
+        let job = self
+            .get_sync_job(id)
+            .ok_or_else(|| AppError::NotFound(format!("Sync job {}", id)))?;
+        self.update_job(id, |job| job.status = SyncStatus::Running);
+
+        let names: Vec<String> = job
+            .providers
+            .iter()
+            .filter(|run| run.status == SyncStatus::Queued)
+            .map(|run| run.provider.clone())
+            .collect();
+        let claims = JobClaims {
+            service: self,
+            job_id: id,
+            held: Mutex::new(names.iter().cloned().collect()),
+        };
+        let (dependent, independent): (Vec<&String>, Vec<&String>) =
+            names.iter().partition(|name| {
+                self.providers
+                    .get(name)
+                    .is_some_and(|p| p.depends_on_stored_rates())
+            });
+
+        let mut result = Ok(());
+        let backfill = job.backfill.as_ref();
+        let results = join_all(
+            independent
+                .into_iter()
+                .map(|name| self.sync_and_log(&claims, name, backfill)),
+        )
+        .await;
+        for r in results {
+            result = result.and(r);
+        }
+        for name in dependent {
+            result = result.and(self.sync_and_log(&claims, name, backfill).await);
+        }
+
+        let finished_at = Utc::now();
+        self.update_job(id, |job| {
+            let statuses: Vec<SyncStatus> = job
+                .providers
+                .iter()
+                .map(|run| run.status)
+                .filter(|status| *status != SyncStatus::Skipped)
+                .collect();
+            job.status = if statuses.is_empty() {
+                // Every provider was already being synced by another job
+                SyncStatus::Skipped
+            } else if statuses.iter().all(|s| *s == SyncStatus::Success) {
+                SyncStatus::Success
+            } else if statuses.iter().all(|s| *s == SyncStatus::Error) {
+                SyncStatus::Error
+            } else {
+                SyncStatus::Partial
+            };
+            job.finished_at = Some(finished_at);
+            job.duration_ms = Some((finished_at - job.created_at).num_milliseconds());
+        });
+
+        let job = self
+            .get_sync_job(id)
+            .ok_or_else(|| AppError::NotFound(format!("Sync job {}", id)))?;
+        if job.records > 0 {
+            let (start, end) = match &job.backfill {
+                Some(request) => (request.start, request.end),
+                None => {
+                    let today = Utc::now().date_naive();
+                    (
+                        today - Duration::days(reconciliation::RECONCILIATION_WINDOW_DAYS),
+                        today,
+                    )
+                }
+            };
+            if let Err(e) = self.reconcile(start, end).await {
+                tracing::warn!("Reconciliation after sync job {} failed: {}", id, e);
+            }
+        }
+
+        result?;
+        Ok(job)
+    }
+
+    /// Sync (or backfill) one provider as part of a job, then record the result in the sync log
+    #[tracing::instrument(
+        name = "sync",
+        skip_all,
+        fields(provider = %name, backfill = backfill.is_some(), status, records)
+    )]
+    async fn sync_and_log(
+        &self,
+        claims: &JobClaims<'_>,
+        name: &str,
+        backfill: Option<&BackfillRequest>,
+    ) -> Result<()> {
+        tracing::info!("Syncing rates from provider: {}", name);
+        let job_id = claims.job_id;
+
+        let started_at = Utc::now();
+        self.update_job(job_id, |job| {
+            if let Some(run) = job.providers.iter_mut().find(|r| r.provider == name) {
+                run.status = SyncStatus::Running;
+                run.started_at = Some(started_at);
+            }
+        });
+
+        let result = match backfill {
+            Some(request) => self.backfill_provider(name, request).await,
+            None => self.sync_provider(name).await,
+        };
+        claims.release(name);
+
+        let mut run = ProviderSyncProgress::queued(name);
+        if let Ok(outcome) = &result {
+            run.rejected = outcome
+                .issues
+                .iter()
+                .filter(|i| i.reason == QualityReason::InvalidRate)
+                .count();
+            run.quarantined = outcome.issues.len() - run.rejected;
+            run.issues = outcome
+                .issues
+                .iter()
+                .take(MAX_REPORTED_ISSUES)
+                .cloned()
+                .collect();
+        }
+        match result {
+            Ok(outcome) if outcome.missing.is_empty() => {
+                tracing::info!("Synced {} rates from {}", outcome.records, name);
+                run.status = SyncStatus::Success;
+                run.records = outcome.records;
+                run.missing_cells = outcome.missing_cells;
+            }
+            Ok(outcome) => {
+                tracing::warn!(
+                    "Synced {} rates from {}, missing: {}",
+                    outcome.records,
+                    name,
+                    outcome.missing.join(", ")
+                );
+                run.status = SyncStatus::Partial;
+                run.records = outcome.records;
+                run.missing = outcome.missing;
+                run.missing_cells = outcome.missing_cells;
+            }
+            Err(AppError::PartialData { missing, .. })
+                if self.providers.settings(name).partial_data == PartialDataPolicy::RetryLater =>
+            {
+                tracing::warn!(
+                    "Sync of {} postponed, missing: {}",
+                    name,
+                    missing.join(", ")
+                );
+                run.status = SyncStatus::RetryLater;
+                run.missing = missing;
+            }
+            Err(e) => {
+                tracing::error!("Failed to sync {}: {}", name, e);
+                run.status = SyncStatus::Error;
+                run.error = Some(e.to_string());
+                if let AppError::PartialData { missing, .. } = e {
+                    run.missing = missing;
+                }
+            }
+        }
+
+        let finished_at = Utc::now();
+        run.started_at = Some(started_at);
+        run.finished_at = Some(finished_at);
+        run.duration_ms = Some((finished_at - started_at).num_milliseconds());
+        metrics::observe_sync(&run);
+        let span = tracing::Span::current();
+        span.record("status", run.status.as_str());
+        span.record("records", run.records);
+
+        self.update_job(job_id, |job| {
+            job.records += run.records;
+            if let Some(slot) = job.providers.iter_mut().find(|r| r.provider == name) {
+                *slot = run.clone();
+            }
+        });
+
+        self.repository.log_sync_run(job_id, &run).await
+    }
+
+    /// Missing (date, currency) cells of a provider in `start..=end`.
+    /// A currency is expected from its first to its last known date, taken from
+    /// stored rates and, where the provider keeps one, its currency catalog.
+    pub async fn find_missing_cells(
+        &self,
+        provider_name: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<BTreeMap<NaiveDate, Vec<String>>> {
+        let provider = self
+            .providers
+            .get(provider_name)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown provider: {}", provider_name)))?;
+        let history_start = provider.capabilities().history_start;
+
+        let mut spans = self.repository.get_currency_spans(provider_name).await?;
+        for entry in self
+            .repository
+            .get_provider_currencies(provider_name)
+            .await?
+        {
+            if let Some((first, last)) = spans.get_mut(&entry.code) {
+                *first = entry.first_date.map_or(*first, |d| d.min(*first));
+                *last = entry.last_date.map_or(*last, |d| d.max(*last));
+            }
+        }
+
+        let stored = self
+            .repository
+            .get_stored_cells(provider_name, start, end)
+            .await?;
+
+        let mut missing: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+        for date in start.iter_days().take_while(|d| *d <= end) {
+            if history_start.is_some_and(|h| date < h) {
+                continue;
+            }
+            let present = stored.get(&date);
+            let mut codes: Vec<String> = spans
+                .iter()
+                .filter(|(code, (first, last))| {
+                    date >= *first && date <= *last && !present.is_some_and(|p| p.contains(*code))
+                })
+                .map(|(code, _)| code.clone())
+                .collect();
+            if !codes.is_empty() {
+                codes.sort();
+                missing.insert(date, codes);
+            }
+        }
+
+        Ok(missing)
+    }
+
+    /// Refetch a date range of one provider. `fill-missing` only requests and
+    /// stores the missing cells; `overwrite` replaces everything in the range.
+    pub async fn backfill_provider(
+        &self,
+        provider_name: &str,
+        request: &BackfillRequest,
+    ) -> Result<SyncOutcome> {
+        let provider = self
+            .providers
+            .get(provider_name)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown provider: {}", provider_name)))?;
+
+        // Partial results are kept: a backfill can always be run again
+        let collect = |result: Result<Vec<DailyRates>>| match result {
+            Ok(rates) => Ok((rates, Vec::new())),
+            Err(AppError::PartialData { rates, missing }) => Ok((rates, missing)),
+            Err(e) => Err(e),
+        };
+
+        let (rates, missing, missing_cells) = match request.mode {
+            BackfillMode::Overwrite => {
+                tracing::info!(
+                    "Backfilling {} from {} to {} (overwrite)",
+                    provider_name,
+                    request.start,
+                    request.end
+                );
+                let fetch = provider.fetch_range(request.start, request.end);
+                let (rates, missing) = collect(
+                    traced_fetch(provider_name, Some(request.start), request.end, fetch).await,
+                )?;
+                (rates, missing, None)
+            }
+            BackfillMode::FillMissing => {
+                let cells = self
+                    .find_missing_cells(provider_name, request.start, request.end)
+                    .await?;
+                let cell_count = cells.values().map(Vec::len).sum::<usize>();
+                tracing::info!(
+                    "Backfilling {} from {} to {}: {} missing cells on {} dates",
+                    provider_name,
+                    request.start,
+                    request.end,
+                    cell_count,
+                    cells.len()
+                );
+
+                let mut rates = Vec::new();
+                let mut missing = Vec::new();
+                for (from, to, currencies) in Self::backfill_runs(&cells) {
+                    let fetch = provider.fetch_range_for(from, to, &currencies);
+                    let (fetched, run_missing) =
+                        collect(traced_fetch(provider_name, Some(from), to, fetch).await)?;
+                    rates.extend(fetched);
+                    missing.extend(run_missing);
                 }
-                Err(e) => {
-                    tracing::error!("Failed to sync {}: {}", provider.name(), e);
-                    self.repository
-                        .log_sync(provider.name(), 0, &format!("error: {}", e))
-                        .await?;
+                missing.sort();
+                missing.dedup();
+
+                // Keep only the cells that were missing
+                for daily in &mut rates {
+                    let wanted = cells.get(&daily.date);
+                    let base = daily.base_currency.clone();
+                    daily.rates.retain(|code, _| {
+                        *code == base || wanted.is_some_and(|w| w.contains(code))
+                    });
+                }
+                rates.retain(|daily| cells.contains_key(&daily.date));
+
+                (rates, missing, Some(cell_count))
+            }
+        };
+
+        let (records, issues) = self.store_fetched(provider.as_ref(), rates).await?;
+
+        Ok(SyncOutcome {
+            records,
+            missing,
+            missing_cells,
+            issues,
+        })
+    }
+
+    /// Group dates with missing cells into fetch ranges, merging holes that are
+    /// less than `BACKFILL_MERGE_GAP_DAYS` apart. Returns (start, end, currencies).
+    fn backfill_runs(
+        cells: &BTreeMap<NaiveDate, Vec<String>>,
+    ) -> Vec<(NaiveDate, NaiveDate, Vec<String>)> {
+        let mut runs: Vec<(NaiveDate, NaiveDate, Vec<String>)> = Vec::new();
+
+        for (date, codes) in cells {
+            match runs.last_mut() {
+                Some((_, end, currencies))
+                    if (*date - *end).num_days() <= BACKFILL_MERGE_GAP_DAYS =>
+                {
+                    *end = *date;
+                    for code in codes {
+                        if !currencies.contains(code) {
+                            currencies.push(code.clone());
+                        }
+                    }
                 }
+                _ => runs.push((*date, *date, codes.clone())),
             }
         }
 
+        for (_, _, currencies) in &mut runs {
+            currencies.sort();
+        }
+        runs
+    }
+
+    /// Page through the sync log, newest first
+    pub async fn get_sync_history(
+        &self,
+        filter: &SyncHistoryFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<SyncHistoryResponse> {
+        let (entries, total) = self
+            .repository
+            .get_sync_history(filter, limit, offset)
+            .await?;
+
+        Ok(SyncHistoryResponse {
+            total,
+            limit,
+            offset,
+            entries,
+        })
+    }
+
+    /// Sync providers whose postponed retry is due
+    pub async fn run_due_retries(&self) -> Result<()> {
+        let now = Utc::now();
+        let due: Vec<String> = {
+            let mut pending = self.pending_retries.lock().unwrap();
+            let due: Vec<String> = self
+                .providers
+                .names()
+                .into_iter()
+                .filter(|name| pending.get(name).is_some_and(|at| *at <= now))
+                .collect();
+            for name in &due {
+                pending.remove(name);
+            }
+            due
+        };
+
+        if due.is_empty() {
+            return Ok(());
+        }
+        tracing::info!("Retrying postponed sync for {:?}", due);
+        self.sync_providers(&due, SyncTrigger::Retry).await?;
         Ok(())
     }
 
+    /// Time zone and local date/time a provider publishes in, with
+    /// settings taking precedence over the provider's own schedule
+    fn publication_clock(
+        schedule: &PublicationSchedule,
+        settings: &ProviderSettings,
+        now: DateTime<Utc>,
+    ) -> DateTime<Tz> {
+        let tz = settings
+            .timezone
+            .or_else(|| schedule.timezone.parse::<Tz>().ok())
+            .unwrap_or(Tz::UTC);
+        now.with_timezone(&tz)
+    }
+
+    /// Latest date a provider is expected to have published by `now`:
+    /// today in its time zone once the publication time has passed,
+    /// otherwise the previous publication day
+    fn expected_latest_date(
+        schedule: &PublicationSchedule,
+        settings: &ProviderSettings,
+        now: DateTime<Utc>,
+    ) -> NaiveDate {
+        let local = Self::publication_clock(schedule, settings, now);
+        let mut date = local.date_naive();
+
+        if let Some(time) = settings.publish_time.or(schedule.time) {
+            if local.time() < time {
+                date -= Duration::days(1);
+            }
+        }
+
+        if !schedule.weekdays.is_empty() {
+            while !schedule.weekdays.contains(&date.weekday()) {
+                date -= Duration::days(1);
+            }
+        }
+
+        date
+    }
+
     /// Sync rates from a specific provider
-    pub async fn sync_provider(&self, provider_name: &str) -> Result<usize> {
+    pub async fn sync_provider(&self, provider_name: &str) -> Result<SyncOutcome> {
         let provider = self
             .providers
             .get(provider_name)
             .ok_or_else(|| AppError::Provider(format!("Unknown provider: {}", provider_name)))?;
 
-        // Get last sync date
-        let last_date = self.repository.get_latest_date(Some(provider_name)).await?;
+        let settings = self.providers.settings(provider_name);
+        if !settings.enabled {
+            return Err(AppError::Validation(format!(
+                "Provider {} is disabled",
+                provider_name
+            )));
+        }
+
+        // Last published date: gap-filled days are fetched again
+        let last_date = self
+            .repository
+            .get_latest_published_date(provider_name)
+            .await?;
 
         let rates = if let Some(last) = last_date {
-            // Fetch only new data
-            let today = chrono::Utc::now().date_naive();
-            if last >= today {
+            // Fetch only new data, unless nothing new is published yet
+            let schedule = provider.capabilities().publication;
+            let now = Utc::now();
+            let expected = Self::expected_latest_date(&schedule, &settings, now);
+            if last >= expected {
                 tracing::info!("Provider {} is already up to date", provider_name);
-                return Ok(0);
+                return Ok(SyncOutcome::default());
             }
-            provider.fetch_range(last, today).await?
+
+            // Re-fetch the lookback window to pick up late corrections
+            let start = last - Duration::days(settings.lookback_days as i64);
+            let today = Self::publication_clock(&schedule, &settings, now).date_naive();
+            traced_fetch(
+                provider_name,
+                Some(start),
+                today,
+                provider.fetch_range(start, today),
+            )
+            .await
         } else {
             // First sync - fetch full history
             tracing::info!("First sync for {}, fetching full history", provider_name);
-            provider.fetch_full_history().await?
+            let today = Utc::now().date_naive();
+            traced_fetch(provider_name, None, today, provider.fetch_full_history()).await
+        };
+
+        let (rates, missing) = match rates {
+            Ok(rates) => (rates, Vec::new()),
+            Err(AppError::PartialData { rates, missing }) => match settings.partial_data {
+                PartialDataPolicy::StorePartial => (rates, missing),
+                PartialDataPolicy::Fail => {
+                    return Err(AppError::PartialData {
+                        rates: Vec::new(),
+                        missing,
+                    });
+                }
+                PartialDataPolicy::RetryLater => {
+                    let due = Utc::now() + Duration::minutes(settings.retry_later_minutes as i64);
+                    self.pending_retries
+                        .lock()
+                        .unwrap()
+                        .insert(provider_name.to_string(), due);
+                    return Err(AppError::PartialData {
+                        rates: Vec::new(),
+                        missing,
+                    });
+                }
+            },
+            Err(e) => return Err(e),
         };
 
-        // Store rates
-        let count = self.repository.store_daily_rates_batch(&rates).await?;
+        // Check and store rates
+        let (count, issues) = self.store_fetched(provider.as_ref(), rates).await?;
 
         // Store currencies
         let currencies = provider.supported_currencies().await?;
@@ -146,7 +1061,86 @@ impl RatesService {
             .store_currencies(&currency_pairs, provider_name)
             .await?;
 
-        Ok(count)
+        Ok(SyncOutcome {
+            records: count,
+            missing,
+            missing_cells: None,
+            issues,
+        })
+    }
+
+    /// Screen fetched rates and store the accepted ones, and the provider's
+    /// reference rates for reconciliation.
+    /// Returns the number of stored records and the quality issues found.
+    #[tracing::instrument(
+        name = "store",
+        skip_all,
+        fields(provider = %provider.name(), days = rates.len(), records, issues)
+    )]
+    async fn store_fetched(
+        &self,
+        provider: &dyn Provider,
+        mut rates: Vec<DailyRates>,
+    ) -> Result<(usize, Vec<QualityIssue>)> {
+        let provider_name = provider.name();
+        let reference = provider.reference_rates(&mut rates);
+        let issues = self.screen_rates(provider_name, &mut rates).await?;
+        let records = {
+            let _write = self.write_lock.lock().await;
+            if !reference.is_empty() {
+                self.repository.store_reference_rates(&reference).await?;
+            }
+            let records = self.repository.store_daily_rates_batch(&rates).await?;
+            self.repository
+                .resolve_quarantined(provider_name, &rates)
+                .await?;
+            records
+        };
+
+        let span = tracing::Span::current();
+        span.record("records", records);
+        span.record("issues", issues.len());
+        Ok((records, issues))
+    }
+
+    /// Run the data quality checks on fetched rates: invalid rates are dropped
+    /// and rates that jumped are moved to quarantine. Quarantined rates of a
+    /// level confirmed by `rates` are added to them, and released once stored.
+    async fn screen_rates(
+        &self,
+        provider_name: &str,
+        rates: &mut Vec<DailyRates>,
+    ) -> Result<Vec<QualityIssue>> {
+        let Some(first) = rates.iter().map(|daily| daily.date).min() else {
+            return Ok(Vec::new());
+        };
+        let base = rates[0].base_currency.clone();
+
+        let previous = self
+            .repository
+            .get_previous_rates(provider_name, &base, first)
+            .await?;
+        let earlier = self
+            .repository
+            .get_pending_jumps(provider_name, &base, first)
+            .await?;
+        let issues = self.quality.check(rates, previous, &earlier);
+        if issues.is_empty() {
+            return Ok(issues);
+        }
+
+        let quarantined = self
+            .repository
+            .quarantine_rates(provider_name, &base, &issues)
+            .await?;
+        tracing::warn!(
+            "{}: {} invalid rates dropped, {} quarantined",
+            provider_name,
+            issues.len() - quarantined,
+            quarantined
+        );
+
+        Ok(issues)
     }
 
     /// Get latest rates
@@ -155,18 +1149,20 @@ impl RatesService {
         base: Option<&str>,
         symbols: Option<&[String]>,
         amount: Option<f64>,
+        options: RateQueryOptions,
     ) -> Result<RatesResponse> {
         let base = base.unwrap_or(&self.default_api_base);
         let amount = amount.unwrap_or(1.0);
 
         // Get the latest date
-        let date = self
-            .repository
-            .get_latest_date(None)
-            .await?
-            .ok_or(AppError::NoDataAvailable)?;
+        let date = match options.as_of {
+            Some(as_of) => self.repository.get_latest_date_as_of(as_of).await?,
+            None => self.repository.get_latest_date(None).await?,
+        }
+        .ok_or(AppError::NoDataAvailable)?;
 
-        self.get_rates_for_date(date, base, symbols, amount).await
+        self.get_rates_for_date(date, base, symbols, amount, options)
+            .await
     }
 
     /// Get rates for a specific date
@@ -176,25 +1172,48 @@ impl RatesService {
         base: &str,
         symbols: Option<&[String]>,
         amount: f64,
+        options: RateQueryOptions,
     ) -> Result<RatesResponse> {
         tracing::debug!("get_rates_for_date: date={}, base={}", date, base);
 
         // All rates are stored internally as USD-based
-        let usd_rates = self
-            .repository
-            .get_rates_for_date(date, INTERNAL_BASE, None)
-            .await?;
+        let usd_rates = match options.as_of {
+            Some(as_of) => self
+                .repository
+                .get_rates_for_range_as_of(
+                    date,
+                    date,
+                    INTERNAL_BASE,
+                    as_of,
+                    &self.providers.names(),
+                )
+                .await?
+                .remove(&date)
+                .unwrap_or_default(),
+            None => {
+                self.repository
+                    .get_rates_for_date(date, INTERNAL_BASE, None, &self.providers.names())
+                    .await?
+            }
+        };
 
         tracing::debug!("{}-based rates found: {}", INTERNAL_BASE, usd_rates.len());
 
         if usd_rates.is_empty() {
-            return Err(AppError::NoDataAvailable);
+            return Err(self.no_data(date, date).await);
         }
 
         // Add USD = 1.0 to the rates for conversion
         let mut full_rates = usd_rates.clone();
         full_rates.insert(INTERNAL_BASE.to_string(), 1.0);
 
+        let manual = self.manual_rates_for_query(date, date, options).await?;
+        Self::apply_manual_rates(&mut full_rates, &manual.iter().collect::<Vec<_>>());
+
+        if !full_rates.contains_key(base) {
+            return Err(self.missing_currency("from", base, &date.to_string()).await);
+        }
+
         // Convert to requested base if needed
         let rates = if base == INTERNAL_BASE {
             full_rates
@@ -205,6 +1224,14 @@ impl RatesService {
         // Filter by symbols if specified
         let mut rates = rates;
         if let Some(symbols) = symbols {
+            if let Some(missing) = symbols
+                .iter()
+                .find(|s| *s != base && !rates.contains_key(*s))
+            {
+                return Err(self
+                    .missing_currency("to", missing, &date.to_string())
+                    .await);
+            }
             rates.retain(|k, _| symbols.contains(k));
         }
 
@@ -214,11 +1241,23 @@ impl RatesService {
             .map(|(k, v)| (k, Self::round_rate(v * amount)))
             .collect();
 
+        let derived = self.derived_currencies(date, date, options).await?;
+        let derived = Self::derived_labels(derived, base, |c| rates.contains_key(c));
+
+        let revised = self
+            .repository
+            .get_revised_currencies(date, date, options.as_of)
+            .await?;
+        let revised = Self::derived_labels(revised, base, |c| rates.contains_key(c));
+
         Ok(RatesResponse {
             amount,
             base: base.to_string(),
             date,
             rates,
+            derived,
+            revised,
+            as_of: options.as_of,
         })
     }
 
@@ -230,15 +1269,37 @@ impl RatesService {
         base: &str,
         symbols: Option<&[String]>,
         amount: f64,
+        options: RateQueryOptions,
     ) -> Result<TimeSeriesResponse> {
         // All rates are stored internally as USD-based
-        let usd_rates = self
-            .repository
-            .get_rates_for_range(start, end, INTERNAL_BASE, None)
-            .await?;
+        let usd_rates = match options.as_of {
+            Some(as_of) => {
+                self.repository
+                    .get_rates_for_range_as_of(
+                        start,
+                        end,
+                        INTERNAL_BASE,
+                        as_of,
+                        &self.providers.names(),
+                    )
+                    .await?
+            }
+            None => {
+                self.repository
+                    .get_rates_for_range(start, end, INTERNAL_BASE, None, &self.providers.names())
+                    .await?
+            }
+        };
 
         if usd_rates.is_empty() {
-            return Err(AppError::NoDataAvailable);
+            return Err(self.no_data(start, end).await);
+        }
+        let dates = format!("{}..{}", start, end);
+
+        let manual = self.manual_rates_for_query(start, end, options).await?;
+        let mut manual_by_date: HashMap<NaiveDate, Vec<&ManualRate>> = HashMap::new();
+        for m in &manual {
+            manual_by_date.entry(m.date).or_default().push(m);
         }
 
         // Convert each day's rates to requested base
@@ -248,6 +1309,14 @@ impl RatesService {
             // Add USD = 1.0 for conversion
             rates.insert(INTERNAL_BASE.to_string(), 1.0);
 
+            if let Some(manual) = manual_by_date.get(&date) {
+                Self::apply_manual_rates(&mut rates, manual);
+            }
+
+            // Days without a rate for the base can't be expressed in it
+            if !rates.contains_key(base) {
+                continue;
+            }
             let converted = if base == INTERNAL_BASE {
                 rates
             } else {
@@ -257,6 +1326,18 @@ impl RatesService {
             all_rates.insert(date, converted);
         }
 
+        if all_rates.is_empty() {
+            return Err(self.missing_currency("from", base, &dates).await);
+        }
+        if let Some(symbols) = symbols {
+            if let Some(missing) = symbols
+                .iter()
+                .find(|s| *s != base && !all_rates.values().any(|day| day.contains_key(*s)))
+            {
+                return Err(self.missing_currency("to", missing, &dates).await);
+            }
+        }
+
         // Filter and apply amount
         let rates: HashMap<NaiveDate, HashMap<String, f64>> = all_rates
             .into_iter()
@@ -276,36 +1357,1153 @@ impl RatesService {
             })
             .collect();
 
+        let derived = self.derived_currencies(start, end, options).await?;
+        let derived = Self::derived_labels(derived, base, |c| {
+            rates.values().any(|day| day.contains_key(c))
+        });
+
+        let revised = self
+            .repository
+            .get_revised_currencies(start, end, options.as_of)
+            .await?;
+        let revised = Self::derived_labels(revised, base, |c| {
+            rates.values().any(|day| day.contains_key(c))
+        });
+
         Ok(TimeSeriesResponse {
             amount,
             base: base.to_string(),
             start_date: start,
             end_date: end,
             rates,
+            derived,
+            revised,
+            as_of: options.as_of,
         })
     }
 
+    /// Manual rates a query applies: none without `overrides`, and those in
+    /// effect at `as_of` when given
+    async fn manual_rates_for_query(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        options: RateQueryOptions,
+    ) -> Result<Vec<ManualRate>> {
+        match (options.overrides, options.as_of) {
+            (false, _) => Ok(Vec::new()),
+            (true, Some(as_of)) => {
+                self.repository
+                    .get_manual_rates_as_of(start, end, as_of)
+                    .await
+            }
+            (true, None) => self.repository.get_manual_rates(start, end).await,
+        }
+    }
+
+    /// Currencies served from derived pegs on any date of `start..=end`
+    async fn derived_currencies(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        options: RateQueryOptions,
+    ) -> Result<HashSet<String>> {
+        match options.as_of {
+            Some(as_of) => {
+                self.repository
+                    .get_derived_currencies_as_of(start, end, as_of)
+                    .await
+            }
+            None => Ok(self
+                .repository
+                .get_derived_currencies(start, end)
+                .await?
+                .into_values()
+                .flatten()
+                .collect()),
+        }
+    }
+
+    /// List revisions of stored rates. The USD leg of a pair is never
+    /// revised, so it is dropped from the currency filter.
+    pub async fn get_revisions(
+        &self,
+        mut filter: RevisionFilter,
+        limit: i64,
+    ) -> Result<Vec<RateRevision>> {
+        if let (Some(start), Some(end)) = (filter.start, filter.end) {
+            if start > end {
+                return Err(AppError::Validation(
+                    "start must not be after end".to_string(),
+                ));
+            }
+        }
+        let only_usd =
+            !filter.currencies.is_empty() && filter.currencies.iter().all(|c| c == INTERNAL_BASE);
+        if only_usd {
+            return Ok(Vec::new());
+        }
+        filter.currencies.retain(|c| c != INTERNAL_BASE);
+
+        self.repository.get_revisions(&filter, limit).await
+    }
+
+    /// Compare rates published by more than one provider in `start..=end`
+    /// and store the results. Returns the number of comparisons.
+    pub async fn reconcile(&self, start: NaiveDate, end: NaiveDate) -> Result<usize> {
+        let rates = self.repository.get_overlapping_rates(start, end).await?;
+        let discrepancies = reconciliation::compare(&rates);
+        self.repository
+            .store_discrepancies(start, end, &discrepancies)
+            .await?;
+
+        let diverging = discrepancies
+            .iter()
+            .filter(|d| d.diff > self.reconciliation_tolerance)
+            .count();
+        tracing::info!(
+            "Reconciled {} to {}: {} comparisons, {} beyond tolerance",
+            start,
+            end,
+            discrepancies.len(),
+            diverging
+        );
+
+        Ok(discrepancies.len())
+    }
+
+    /// Pairs on which providers diverged in `start..=end`, from stored comparisons
+    pub async fn get_reconciliation(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        tolerance: Option<f64>,
+        currency: Option<&str>,
+    ) -> Result<ReconciliationReport> {
+        let tolerance = tolerance.unwrap_or(self.reconciliation_tolerance);
+        if !tolerance.is_finite() || tolerance < 0.0 {
+            return Err(AppError::Validation(
+                "Tolerance must be a non-negative number".to_string(),
+            ));
+        }
+
+        let discrepancies = self
+            .repository
+            .get_discrepancies(start, end, currency)
+            .await?;
+
+        Ok(reconciliation::build_report(
+            start,
+            end,
+            tolerance,
+            INTERNAL_BASE,
+            discrepancies,
+        ))
+    }
+
+    /// List quarantined rates, newest date first
+    pub async fn list_quarantined(
+        &self,
+        provider: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<QuarantinedRate>> {
+        self.repository.get_quarantined(provider, limit).await
+    }
+
+    /// Accept a quarantined rate as published: store it and drop it from quarantine
+    pub async fn release_quarantined(&self, id: i64) -> Result<QuarantinedRate> {
+        let _write = self.write_lock.lock().await;
+        let rate = self
+            .repository
+            .take_quarantined(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Quarantined rate {}", id)))?;
+
+        self.repository
+            .store_rate(&ExchangeRate {
+                date: rate.date,
+                base_currency: rate.base_currency.clone(),
+                target_currency: rate.target_currency.clone(),
+                rate: rate.rate,
+                provider: rate.provider.clone(),
+            })
+            .await?;
+        tracing::info!(
+            "Released quarantined {} {} rate for {}",
+            rate.provider,
+            rate.target_currency,
+            rate.date
+        );
+
+        Ok(rate)
+    }
+
+    /// Reject a quarantined rate
+    pub async fn discard_quarantined(&self, id: i64) -> Result<()> {
+        self.repository
+            .take_quarantined(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Quarantined rate {}", id)))?;
+        Ok(())
+    }
+
+    /// SHA-256 hex digest under which an API key is stored
+    fn hash_api_key(key: &str) -> String {
+        format!("{:x}", Sha256::digest(key.as_bytes()))
+    }
+
+    /// Create an API key, optionally with its own rate limit and daily quota.
+    /// Returns the key metadata and the key itself, which is not stored and
+    /// cannot be shown again.
+    pub async fn create_api_key(
+        &self,
+        name: &str,
+        scopes: &[Scope],
+        rate_limit: Option<f64>,
+        daily_quota: Option<u64>,
+    ) -> Result<(ApiKey, String)> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation(
+                "API key name must not be empty".to_string(),
+            ));
+        }
+        if scopes.is_empty() {
+            return Err(AppError::Validation(
+                "API key needs at least one scope".to_string(),
+            ));
+        }
+        if rate_limit.is_some_and(|rate| !rate.is_finite() || rate < MIN_KEY_RATE) {
+            return Err(AppError::Validation(format!(
+                "Rate limit must be at least {} requests per second",
+                MIN_KEY_RATE
+            )));
+        }
+
+        let key = format!(
+            "{}{}{}",
+            API_KEY_PREFIX,
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        let prefix = &key[..API_KEY_PREFIX.len() + 8];
+        let api_key = self
+            .repository
+            .insert_api_key(
+                name,
+                &Self::hash_api_key(&key),
+                prefix,
+                scopes,
+                rate_limit,
+                daily_quota,
+            )
+            .await?;
+
+        Ok((api_key, key))
+    }
+
+    /// Active API key matching `key`. Usage is recorded at most once per
+    /// `API_KEY_TOUCH_INTERVAL_SECS` per key.
+    pub async fn authenticate_api_key(&self, key: &str) -> Result<Option<ApiKey>> {
+        let Some(api_key) = self
+            .repository
+            .find_api_key(&Self::hash_api_key(key.trim()))
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        let touch = {
+            let mut usage = self.key_usage.lock().unwrap();
+            let due = usage
+                .get(&api_key.id)
+                .is_none_or(|last| now - *last >= Duration::seconds(API_KEY_TOUCH_INTERVAL_SECS));
+            if due {
+                usage.insert(api_key.id, now);
+            }
+            due
+        };
+        if touch {
+            if let Err(e) = self.repository.touch_api_key(api_key.id).await {
+                tracing::warn!("Failed to record use of API key {}: {}", api_key.name, e);
+            }
+        }
+
+        Ok(Some(api_key))
+    }
+
+    /// List API keys, including revoked ones
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKey>> {
+        self.repository.list_api_keys().await
+    }
+
+    /// Revoke an API key by name
+    pub async fn revoke_api_key(&self, name: &str) -> Result<()> {
+        if !self.repository.revoke_api_key(name.trim()).await? {
+            return Err(AppError::NotFound(format!("API key {}", name)));
+        }
+        Ok(())
+    }
+
+    /// Add request counts collected by the API rate limiter
+    pub async fn record_api_usage(&self, usage: &[ApiUsage]) -> Result<()> {
+        if usage.is_empty() {
+            return Ok(());
+        }
+        self.repository.add_api_usage(usage).await
+    }
+
+    /// Stored API key usage of `start..=end`, newest first
+    pub async fn get_api_usage(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        key: Option<&str>,
+    ) -> Result<Vec<ApiUsage>> {
+        self.repository.get_api_usage(start, end, key).await
+    }
+
+    /// Validate a manual rate pair and value, returning normalized currency codes
+    fn validate_manual_rate(base: &str, target: &str, rate: f64) -> Result<(String, String)> {
+        let (base, target) = (base.trim().to_uppercase(), target.trim().to_uppercase());
+
+        for code in [&base, &target] {
+            if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(AppError::Validation(format!(
+                    "Invalid currency code: {}",
+                    code
+                )));
+            }
+        }
+        if base == target {
+            return Err(AppError::Validation(
+                "Base and target currencies must differ".to_string(),
+            ));
+        }
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(AppError::Validation(
+                "Rate must be a positive number".to_string(),
+            ));
+        }
+
+        Ok((base, target))
+    }
+
+    /// List manual rates in a date range
+    pub async fn list_manual_rates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<ManualRate>> {
+        self.repository.get_manual_rates(start, end).await
+    }
+
+    /// Create a manual rate; fails if one already exists for the date and pair
+    pub async fn create_manual_rate(
+        &self,
+        date: NaiveDate,
+        base: &str,
+        target: &str,
+        rate: f64,
+        note: Option<&str>,
+        actor: &str,
+    ) -> Result<ManualRate> {
+        let (base, target) = Self::validate_manual_rate(base, target, rate)?;
+
+        if self
+            .repository
+            .get_manual_rate(date, &base, &target)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Conflict(format!(
+                "Manual rate {}/{} for {} already exists",
+                base, target, date
+            )));
+        }
+
+        self.repository
+            .upsert_manual_rate(date, &base, &target, rate, note, actor)
+            .await?;
+        tracing::info!(
+            "{} created manual rate {}/{} = {} for {}",
+            actor,
+            base,
+            target,
+            rate,
+            date
+        );
+
+        self.repository
+            .get_manual_rate(date, &base, &target)
+            .await?
+            .ok_or_else(|| AppError::Internal("Manual rate was not stored".to_string()))
+    }
+
+    /// Update an existing manual rate
+    pub async fn update_manual_rate(
+        &self,
+        date: NaiveDate,
+        base: &str,
+        target: &str,
+        rate: f64,
+        note: Option<&str>,
+        actor: &str,
+    ) -> Result<ManualRate> {
+        let (base, target) = Self::validate_manual_rate(base, target, rate)?;
+
+        if self
+            .repository
+            .get_manual_rate(date, &base, &target)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::NotFound(format!(
+                "Manual rate {}/{} for {}",
+                base, target, date
+            )));
+        }
+
+        self.repository
+            .upsert_manual_rate(date, &base, &target, rate, note, actor)
+            .await?;
+        tracing::info!(
+            "{} updated manual rate {}/{} = {} for {}",
+            actor,
+            base,
+            target,
+            rate,
+            date
+        );
+
+        self.repository
+            .get_manual_rate(date, &base, &target)
+            .await?
+            .ok_or_else(|| AppError::Internal("Manual rate was not stored".to_string()))
+    }
+
+    /// Delete a manual rate
+    pub async fn delete_manual_rate(
+        &self,
+        date: NaiveDate,
+        base: &str,
+        target: &str,
+        actor: &str,
+    ) -> Result<()> {
+        let (base, target) = (base.trim().to_uppercase(), target.trim().to_uppercase());
+
+        self.repository
+            .delete_manual_rate(date, &base, &target, actor)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Manual rate {}/{} for {}", base, target, date))
+            })?;
+        tracing::info!(
+            "{} deleted manual rate {}/{} for {}",
+            actor,
+            base,
+            target,
+            date
+        );
+
+        Ok(())
+    }
+
+    /// Get the manual rates audit trail for a date range
+    pub async fn get_manual_audit(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        limit: i64,
+    ) -> Result<Vec<ManualRateAudit>> {
+        self.repository.get_manual_audit(start, end, limit).await
+    }
+
     /// Get available currencies
     pub async fn get_currencies(&self) -> Result<crate::models::CurrenciesResponse> {
         self.repository.get_currencies(None).await
     }
 
+    /// Describe all registered providers in registration order
+    pub fn list_providers(&self) -> Vec<crate::models::ProviderDetails> {
+        self.providers
+            .all()
+            .iter()
+            .map(|provider| crate::models::ProviderDetails {
+                name: provider.name().to_string(),
+                description: provider.description().to_string(),
+                enabled: self.providers.is_enabled(provider.name()),
+                capabilities: provider.capabilities(),
+            })
+            .collect()
+    }
+
+    /// Update the metrics read at scrape time: age of the latest rate of each
+    /// registered provider and SQLite pool utilization
+    pub async fn refresh_metrics(&self) -> Result<()> {
+        let latest = self.repository.get_latest_dates_by_provider().await?;
+        let now = Utc::now();
+        for provider in self.providers.all() {
+            if let Some(date) = latest.get(provider.name()) {
+                let age = now - date.and_time(chrono::NaiveTime::MIN).and_utc();
+                metrics::set_latest_rate_age(provider.name(), age.to_std().unwrap_or_default());
+            }
+        }
+
+        let (size, idle, max) = self.repository.pool_stats();
+        metrics::set_db_pool(size.saturating_sub(idle), idle, max);
+        Ok(())
+    }
+
+    /// Check that the database answers
+    pub async fn check_database(&self) -> Result<()> {
+        self.repository.ping().await
+    }
+
+    /// A provider is stale when its last successful sync or its latest rate
+    /// date (from the start of that day, UTC) is older than `stale_after_hours`
+    fn is_stale(
+        last_sync: Option<DateTime<Utc>>,
+        latest_date: Option<NaiveDate>,
+        stale_after_hours: u32,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let cutoff = now - Duration::hours(stale_after_hours as i64);
+        let published_at = latest_date.map(|d| d.and_time(chrono::NaiveTime::MIN).and_utc());
+
+        last_sync.is_none_or(|t| t < cutoff) || published_at.is_none_or(|t| t < cutoff)
+    }
+
+    /// Sync state and stored data of one provider
+    async fn provider_info(
+        &self,
+        provider: &dyn Provider,
+        quarantined: &HashMap<String, usize>,
+        now: DateTime<Utc>,
+    ) -> Result<crate::models::ProviderInfo> {
+        let name = provider.name();
+        let last_sync = self.repository.get_last_sync(name).await?;
+        let last_failure = self.repository.get_last_failure(name).await?;
+        let stats = if name == MANUAL_PROVIDER {
+            self.repository.get_manual_stats().await?
+        } else {
+            self.repository.get_rate_stats(name).await?
+        };
+        let enabled = self.providers.is_enabled(name);
+        let stale = enabled
+            && Self::is_stale(
+                last_sync,
+                stats.last_date,
+                self.providers.settings(name).stale_after_hours,
+                now,
+            );
+
+        Ok(crate::models::ProviderInfo {
+            name: name.to_string(),
+            description: provider.description().to_string(),
+            enabled,
+            last_sync,
+            last_failure,
+            first_date: stats.first_date,
+            latest_date: stats.last_date,
+            stale,
+            currencies_count: stats.currencies as usize,
+            rows_count: stats.rows as usize,
+            quarantined: quarantined.get(name).copied().unwrap_or(0),
+            circuit: provider.circuit_status(),
+        })
+    }
+
     /// Get providers info for health check
     pub async fn get_providers_info(&self) -> Result<Vec<crate::models::ProviderInfo>> {
         let mut infos = Vec::new();
+        let quarantined = self.repository.count_quarantined().await?;
+        let now = Utc::now();
 
         for provider in self.providers.all() {
-            let last_sync = self.repository.get_last_sync(provider.name()).await?;
-            let count = self.repository.get_rates_count(provider.name()).await?;
-
-            infos.push(crate::models::ProviderInfo {
-                name: provider.name().to_string(),
-                enabled: true,
-                last_sync,
-                currencies_count: count as usize,
-            });
+            infos.push(
+                self.provider_info(provider.as_ref(), &quarantined, now)
+                    .await?,
+            );
         }
 
         Ok(infos)
     }
+
+    /// Status and capabilities of a single provider
+    pub async fn get_provider_status(&self, name: &str) -> Result<crate::models::ProviderStatus> {
+        let provider = self
+            .providers
+            .get(name)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown provider: {}", name)))?;
+        let quarantined = self.repository.count_quarantined().await?;
+        let info = self
+            .provider_info(provider.as_ref(), &quarantined, Utc::now())
+            .await?;
+
+        Ok(crate::models::ProviderStatus {
+            info,
+            capabilities: provider.capabilities(),
+        })
+    }
+}
+
+/// Run the fetch stage of a sync in a span with the provider, requested date
+/// range (no start for a full history) and number of fetched days
+async fn traced_fetch(
+    provider: &str,
+    start: Option<NaiveDate>,
+    end: NaiveDate,
+    fetch: impl Future<Output = Result<Vec<DailyRates>>>,
+) -> Result<Vec<DailyRates>> {
+    let span = tracing::info_span!(
+        "fetch",
+        provider,
+        start = start.map(tracing::field::display),
+        %end,
+        days = tracing::field::Empty,
+        missing = tracing::field::Empty,
+    );
+    let result = fetch.instrument(span.clone()).await;
+    match &result {
+        Ok(rates) => {
+            span.record("days", rates.len());
+        }
+        Err(AppError::PartialData { rates, missing }) => {
+            span.record("days", rates.len());
+            span.record("missing", missing.len());
+        }
+        Err(_) => {}
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Service over an in-memory database, with the providers added by `register`
+    async fn test_service(
+        register: impl FnOnce(&RatesRepository, &mut ProviderRegistry) -> Result<()>,
+    ) -> RatesService {
+        let repository = crate::db::test_repository().await;
+        let mut registry = ProviderRegistry::new();
+        register(&repository, &mut registry).unwrap();
+        RatesService::new(repository, Arc::new(registry), "USD".to_string())
+    }
+
+    fn manual(base: &str, target: &str, rate: f64) -> ManualRate {
+        ManualRate {
+            date: NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(),
+            base_currency: base.to_string(),
+            target_currency: target.to_string(),
+            rate,
+            note: None,
+            updated_by: "test".to_string(),
+            updated_at: "2025-01-02 10:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_manual_rates() {
+        let mut rates = HashMap::new();
+        rates.insert("USD".to_string(), 1.0);
+        rates.insert("EUR".to_string(), 0.9);
+        rates.insert("UAH".to_string(), 41.0);
+
+        let eur_uah = manual("EUR", "UAH", 45.0);
+        let gbp_usd = manual("GBP", "USD", 1.25);
+        RatesService::apply_manual_rates(&mut rates, &[&eur_uah, &gbp_usd]);
+
+        // USD/UAH = USD/EUR * EUR/UAH
+        assert!((rates["UAH"] - 0.9 * 45.0).abs() < 1e-9);
+        // USD/GBP = 1 / GBP/USD
+        assert!((rates["GBP"] - 0.8).abs() < 1e-9);
+        assert_eq!(rates["EUR"], 0.9);
+    }
+
+    #[test]
+    fn test_similar_codes() {
+        let known: Vec<String> = ["EUR", "EGP", "GBP", "USD"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+
+        assert_eq!(RatesService::similar_codes("EUT", known.iter()), ["EUR"]);
+        assert_eq!(
+            RatesService::similar_codes("GGP", known.iter()),
+            ["EGP", "GBP"]
+        );
+        assert!(RatesService::similar_codes("XYZ", known.iter()).is_empty());
+    }
+
+    #[test]
+    fn test_validate_manual_rate() {
+        assert_eq!(
+            RatesService::validate_manual_rate("eur", "uah", 45.0).unwrap(),
+            ("EUR".to_string(), "UAH".to_string())
+        );
+        assert!(RatesService::validate_manual_rate("EUR", "EUR", 1.0).is_err());
+        assert!(RatesService::validate_manual_rate("EURO", "UAH", 1.0).is_err());
+        assert!(RatesService::validate_manual_rate("EUR", "UAH", 0.0).is_err());
+        assert!(RatesService::validate_manual_rate("EUR", "UAH", f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_expected_latest_date() {
+        let schedule = PublicationSchedule::business_days(Some((16, 0)), "Europe/Berlin");
+        let settings = ProviderSettings::new(true);
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        // Wednesday 14:30 UTC = 16:30 CEST, already published
+        assert_eq!(
+            RatesService::expected_latest_date(&schedule, &settings, at("2025-06-04T14:30:00Z")),
+            date(2025, 6, 4)
+        );
+        // Wednesday 13:30 UTC = 15:30 CEST, still Tuesday's rates
+        assert_eq!(
+            RatesService::expected_latest_date(&schedule, &settings, at("2025-06-04T13:30:00Z")),
+            date(2025, 6, 3)
+        );
+        // Sunday, Friday's rates are the latest
+        assert_eq!(
+            RatesService::expected_latest_date(&schedule, &settings, at("2025-06-08T18:00:00Z")),
+            date(2025, 6, 6)
+        );
+
+        // Overridden publication time and zone
+        let settings = ProviderSettings {
+            publish_time: chrono::NaiveTime::from_hms_opt(9, 0, 0),
+            timezone: Some(chrono_tz::Asia::Tokyo),
+            ..ProviderSettings::new(true)
+        };
+        // Wednesday 01:00 UTC = 10:00 JST
+        assert_eq!(
+            RatesService::expected_latest_date(&schedule, &settings, at("2025-06-04T01:00:00Z")),
+            date(2025, 6, 4)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_job_conflicts() {
+        use crate::providers::DerivedProvider;
+
+        let service = test_service(|repository, registry| {
+            registry.register(DerivedProvider::new(repository.clone(), Vec::new()))
+        })
+        .await;
+        let names = vec!["derived".to_string()];
+
+        let job = service.create_sync_job(&names, SyncTrigger::Api).unwrap();
+        assert_eq!(job.status, SyncStatus::Queued);
+        assert!(matches!(
+            service.create_sync_job(&names, SyncTrigger::Api),
+            Err(AppError::Conflict(_))
+        ));
+        assert!(matches!(
+            service.create_sync_job(&["missing".to_string()], SyncTrigger::Api),
+            Err(AppError::NotFound(_))
+        ));
+
+        // Scheduled runs skip the provider instead of failing
+        let scheduled = service
+            .create_job(&names, SyncTrigger::Schedule, None, false)
+            .unwrap();
+        assert_eq!(scheduled.providers[0].status, SyncStatus::Skipped);
+        assert_eq!(service.get_sync_job(&job.id).unwrap().id, job.id);
+        let scheduled = service.run_sync_job(&scheduled.id).await.unwrap();
+        assert_eq!(scheduled.status, SyncStatus::Skipped);
+
+        // A job dropped while running (e.g. its waiting request went away)
+        // releases the provider and is reported as failed
+        use futures::FutureExt;
+        assert!(service.run_sync_job(&job.id).now_or_never().is_none());
+        let cancelled = service.get_sync_job(&job.id).unwrap();
+        assert_eq!(cancelled.status, SyncStatus::Error);
+        assert!(cancelled.finished_at.is_some());
+        assert!(service.create_sync_job(&names, SyncTrigger::Api).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_find_missing_cells() {
+        use crate::models::{DailyRates, ProviderCurrency};
+        use crate::providers::DerivedProvider;
+
+        let service = test_service(|repository, registry| {
+            registry.register(DerivedProvider::new(repository.clone(), Vec::new()))
+        })
+        .await;
+        let repository = &service.repository;
+
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let rates: Vec<DailyRates> = (1..=10)
+            .filter(|d| *d != 5)
+            .map(|d| {
+                let mut rates = HashMap::from([("EUR".to_string(), 0.9)]);
+                if d >= 3 {
+                    rates.insert("GBP".to_string(), 0.8);
+                }
+                DailyRates {
+                    date: day(d),
+                    base_currency: "USD".to_string(),
+                    rates,
+                    provider: "derived".to_string(),
+                    estimated: false,
+                }
+            })
+            .collect();
+        repository.store_daily_rates_batch(&rates).await.unwrap();
+        // The catalog knows GBP is published from the 2nd
+        repository
+            .upsert_provider_currencies(
+                "derived",
+                &[ProviderCurrency {
+                    code: "GBP".to_string(),
+                    name: "British Pound".to_string(),
+                    first_date: Some(day(2)),
+                    last_date: None,
+                }],
+            )
+            .await
+            .unwrap();
+
+        let missing = service
+            .find_missing_cells("derived", day(1), day(10))
+            .await
+            .unwrap();
+        assert_eq!(
+            missing,
+            BTreeMap::from([
+                (day(2), vec!["GBP".to_string()]),
+                (day(5), vec!["EUR".to_string(), "GBP".to_string()]),
+            ])
+        );
+
+        let runs = RatesService::backfill_runs(&missing);
+        assert_eq!(runs.len(), 1);
+        assert_eq!((runs[0].0, runs[0].1), (day(2), day(5)));
+
+        let far = BTreeMap::from([
+            (day(1), vec!["EUR".to_string()]),
+            (day(20), vec!["GBP".to_string()]),
+        ]);
+        assert_eq!(RatesService::backfill_runs(&far).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_provider_status_ignores_gap_filled_rows() {
+        use crate::models::DailyRates;
+        use crate::providers::{DerivedProvider, fill_gaps};
+
+        let service = test_service(|repository, registry| {
+            registry.register(DerivedProvider::new(repository.clone(), Vec::new()))
+        })
+        .await;
+        let repository = &service.repository;
+
+        // Last published ten days ago, gap-filled up to today
+        let today = Utc::now().date_naive();
+        let published: Vec<DailyRates> = [12, 10]
+            .into_iter()
+            .map(|days| DailyRates {
+                date: today - Duration::days(days),
+                base_currency: "USD".to_string(),
+                rates: HashMap::from([("EUR".to_string(), 0.9)]),
+                provider: "derived".to_string(),
+                estimated: false,
+            })
+            .collect();
+        repository
+            .store_daily_rates_batch(&fill_gaps(published, "derived"))
+            .await
+            .unwrap();
+        repository.log_sync("derived", 1, "success").await.unwrap();
+
+        let status = service.get_provider_status("derived").await.unwrap();
+        assert_eq!(status.info.first_date, Some(today - Duration::days(12)));
+        assert_eq!(status.info.latest_date, Some(today - Duration::days(10)));
+        assert!(status.info.stale);
+        assert_eq!(
+            service
+                .repository
+                .get_latest_published_date("derived")
+                .await
+                .unwrap(),
+            Some(today - Duration::days(10))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provider_status_counts() {
+        use crate::models::DailyRates;
+        use crate::providers::DerivedProvider;
+
+        let service = test_service(|repository, registry| {
+            registry.register(DerivedProvider::new(repository.clone(), Vec::new()))
+        })
+        .await;
+        let repository = &service.repository;
+
+        // EUR on three dates, GBP on one
+        let rates: Vec<DailyRates> = (1..=3)
+            .map(|day| DailyRates {
+                date: NaiveDate::from_ymd_opt(2025, 1, day).unwrap(),
+                base_currency: "USD".to_string(),
+                rates: if day == 3 {
+                    HashMap::from([("EUR".to_string(), 0.9), ("GBP".to_string(), 0.8)])
+                } else {
+                    HashMap::from([("EUR".to_string(), 0.9)])
+                },
+                provider: "derived".to_string(),
+                estimated: false,
+            })
+            .collect();
+        repository.store_daily_rates_batch(&rates).await.unwrap();
+        repository.log_sync("derived", 4, "success").await.unwrap();
+        repository.log_sync("derived", 0, "error").await.unwrap();
+
+        let stats = service.repository.get_rate_stats("derived").await.unwrap();
+        assert_eq!(stats.rows, 4);
+        assert_eq!(stats.currencies, 2);
+
+        // Body of GET /providers/derived
+        let status =
+            serde_json::to_value(service.get_provider_status("derived").await.unwrap()).unwrap();
+        assert_eq!(status["rows_count"], 4);
+        assert_eq!(status["currencies_count"], 2);
+        assert_eq!(status["first_date"], "2025-01-01");
+        assert_eq!(status["latest_date"], "2025-01-03");
+        assert_eq!(status["last_failure"]["status"], "error");
+        assert!(status["last_sync"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_devaluation_across_syncs() {
+        use crate::models::DailyRates;
+
+        let service = test_service(|_, _| Ok(())).await;
+
+        let nbu = crate::providers::NbuProvider::new();
+        let date = |day| NaiveDate::from_ymd_opt(2015, 2, day).unwrap();
+        let daily = |day, rate| DailyRates {
+            date: date(day),
+            base_currency: "USD".to_string(),
+            rates: HashMap::from([("UAH".to_string(), rate)]),
+            provider: "nbu".to_string(),
+            estimated: false,
+        };
+
+        // UAH loses a third of its value and stays there: the first day is
+        // quarantined, the next sync confirms the new level and releases it
+        let (_, issues) = service
+            .store_fetched(&nbu, vec![daily(4, 16.0), daily(5, 24.0)])
+            .await
+            .unwrap();
+        assert_eq!(issues.len(), 1);
+        let (records, issues) = service
+            .store_fetched(&nbu, vec![daily(6, 24.1)])
+            .await
+            .unwrap();
+        assert!(issues.is_empty());
+        assert_eq!(records, 2);
+
+        let repository = &service.repository;
+        assert!(
+            repository
+                .get_quarantined(None, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        for (before, rate) in [(6, 24.0), (7, 24.1)] {
+            let previous = repository
+                .get_previous_rates("nbu", "USD", date(before))
+                .await
+                .unwrap();
+            assert_eq!(previous.get("UAH"), Some(&rate));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_fetched_sets_reference_rates_aside() {
+        use crate::models::DailyRates;
+        use crate::providers::NbuProvider;
+
+        let service = test_service(|_, _| Ok(())).await;
+
+        // EUR is only fetched from NBU as a reference by default
+        let date = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let daily = |provider: &str, rates: &[(&str, f64)]| DailyRates {
+            date,
+            base_currency: "USD".to_string(),
+            rates: rates.iter().map(|(c, r)| (c.to_string(), *r)).collect(),
+            provider: provider.to_string(),
+            estimated: false,
+        };
+        let (records, _) = service
+            .store_fetched(
+                &NbuProvider::new(),
+                vec![daily("nbu", &[("UAH", 41.5), ("EUR", 0.92)])],
+            )
+            .await
+            .unwrap();
+        assert_eq!(records, 1);
+        service
+            .repository
+            .store_daily_rates(&daily("ecb", &[("EUR", 0.93)]))
+            .await
+            .unwrap();
+
+        let served = service
+            .repository
+            .get_rates_for_date(date, "USD", None, &[])
+            .await
+            .unwrap();
+        assert_eq!(served["EUR"], 0.93);
+        let overlapping = service
+            .repository
+            .get_overlapping_rates(date, date)
+            .await
+            .unwrap();
+        let providers: Vec<&str> = overlapping.iter().map(|r| r.provider.as_str()).collect();
+        assert_eq!(providers, ["ecb", "nbu"]);
+    }
+
+    #[tokio::test]
+    async fn test_manual_provider_status() {
+        use crate::providers::ManualProvider;
+
+        let service = test_service(|repository, registry| {
+            registry.register(ManualProvider::new(repository.clone()))
+        })
+        .await;
+        let repository = &service.repository;
+
+        for (day, target, rate) in [(2, "UAH", 41.2), (3, "UAH", 41.3), (3, "EUR", 0.9)] {
+            repository
+                .upsert_manual_rate(
+                    NaiveDate::from_ymd_opt(2025, 1, day).unwrap(),
+                    "USD",
+                    target,
+                    rate,
+                    None,
+                    "test",
+                )
+                .await
+                .unwrap();
+        }
+
+        let status = service.get_provider_status(MANUAL_PROVIDER).await.unwrap();
+        assert_eq!(status.info.rows_count, 3);
+        assert_eq!(status.info.currencies_count, 3);
+        assert_eq!(status.info.first_date, NaiveDate::from_ymd_opt(2025, 1, 2));
+        assert_eq!(status.info.latest_date, NaiveDate::from_ymd_opt(2025, 1, 3));
+
+        let currencies = service
+            .providers
+            .get(MANUAL_PROVIDER)
+            .unwrap()
+            .supported_currencies()
+            .await
+            .unwrap();
+        let codes: Vec<_> = currencies.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, ["EUR", "UAH", "USD"]);
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_providers() {
+        use crate::models::DailyRates;
+        use crate::providers::{BoeProvider, EcbProvider};
+
+        let service = test_service(|_, registry| {
+            registry.register(EcbProvider::new())?;
+            registry.register(BoeProvider::new())
+        })
+        .await;
+        let repository = &service.repository;
+
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let tuesday = monday + Duration::days(1);
+        let gbp = |date, rate, provider: &str, estimated| DailyRates {
+            date,
+            base_currency: "USD".to_string(),
+            rates: HashMap::from([("GBP".to_string(), rate)]),
+            provider: provider.to_string(),
+            estimated,
+        };
+        // Both publish on Monday; on Tuesday only the Bank of England does,
+        // the ECB rate is carried over from Monday
+        repository
+            .store_daily_rates_batch(&[
+                gbp(monday, 0.79, "boe", false),
+                gbp(tuesday, 0.78, "boe", false),
+                gbp(monday, 0.8, "ecb", false),
+                gbp(tuesday, 0.8, "ecb", true),
+            ])
+            .await
+            .unwrap();
+
+        // The first registered provider wins, a published rate beats a gap-filled one
+        for (date, expected) in [(monday, 0.8), (tuesday, 0.78)] {
+            let response = service
+                .get_rates_for_date(date, "USD", None, 1.0, RateQueryOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(response.rates["GBP"], expected);
+        }
+
+        let options = RateQueryOptions {
+            as_of: Some(Utc::now()),
+            ..Default::default()
+        };
+        for options in [RateQueryOptions::default(), options] {
+            let series = service
+                .get_time_series(monday, tuesday, "USD", None, 1.0, options)
+                .await
+                .unwrap();
+            assert_eq!(series.rates[&monday]["GBP"], 0.8);
+            assert_eq!(series.rates[&tuesday]["GBP"], 0.78);
+        }
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let now = NaiveDate::from_ymd_opt(2025, 1, 10)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d);
+        let at = |s| {
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                .unwrap()
+                .and_utc()
+        };
+
+        // Synced an hour ago, rates published yesterday
+        assert!(!RatesService::is_stale(
+            Some(at("2025-01-10 11:00:00")),
+            date(9),
+            48,
+            now
+        ));
+        // Syncs succeed but the provider publishes nothing new
+        assert!(RatesService::is_stale(
+            Some(at("2025-01-10 11:00:00")),
+            date(5),
+            48,
+            now
+        ));
+        // Fresh rates but syncs have been failing
+        assert!(RatesService::is_stale(
+            Some(at("2025-01-07 11:00:00")),
+            date(9),
+            48,
+            now
+        ));
+        // Never synced
+        assert!(RatesService::is_stale(None, None, 48, now));
+    }
 }
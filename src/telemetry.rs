@@ -0,0 +1,151 @@
+use std::str::FromStr;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Output format of log lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of the enclosing spans
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" | "plain" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("Unknown log format: {}", other)),
+        }
+    }
+}
+
+impl LogFormat {
+    /// Format from `LOG_FORMAT`, text by default
+    pub fn from_env() -> Self {
+        match std::env::var("LOG_FORMAT") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                eprintln!("{}, using text", e);
+                LogFormat::Text
+            }),
+            Err(_) => LogFormat::Text,
+        }
+    }
+}
+
+/// Installed logging and tracing. Keep it alive until exit: dropping it
+/// flushes buffered spans.
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush traces: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the global subscriber: `RUST_LOG` filter (or `default_filter`),
+/// logs in `LOG_FORMAT` and, with the `otel` feature and
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` set, span export over OTLP
+pub fn init(service: &str, default_filter: &str) -> Telemetry {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| default_filter.into());
+    let (text, json) = match LogFormat::from_env() {
+        LogFormat::Text => (Some(tracing_subscriber::fmt::layer()), None),
+        LogFormat::Json => (
+            None,
+            // Every enclosing span, so lines carry the request id, job id and provider
+            Some(tracing_subscriber::fmt::layer().json().with_span_list(true)),
+        ),
+    };
+
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(text)
+        .with(json);
+
+    #[cfg(feature = "otel")]
+    {
+        let provider = otel::provider(service);
+        let layer = provider.as_ref().map(|provider| {
+            use opentelemetry::trace::TracerProvider as _;
+            tracing_opentelemetry::layer().with_tracer(provider.tracer(service.to_string()))
+        });
+        registry.with(layer).init();
+        Telemetry { provider }
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        let _ = service;
+        registry.init();
+        Telemetry {}
+    }
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::{Resource, runtime, trace::TracerProvider};
+
+    /// OTLP (gRPC) tracer provider, if an endpoint is configured
+    pub(super) fn provider(service: &str) -> Option<TracerProvider> {
+        let configured = [
+            "OTEL_EXPORTER_OTLP_ENDPOINT",
+            "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+        ]
+        .iter()
+        .any(|name| std::env::var(name).is_ok_and(|v| !v.is_empty()));
+        if !configured {
+            return None;
+        }
+
+        let exporter = match opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .build()
+        {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                eprintln!(
+                    "Failed to create OTLP exporter, traces are not exported: {}",
+                    e
+                );
+                return None;
+            }
+        };
+
+        let service = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| service.to_string());
+        let resource = Resource::new([
+            KeyValue::new("service.name", service),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ]);
+
+        Some(
+            TracerProvider::builder()
+                .with_batch_exporter(exporter, runtime::Tokio)
+                .with_resource(resource)
+                .build(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!("json".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert_eq!(" Text ".parse::<LogFormat>(), Ok(LogFormat::Text));
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::models::ApiUsage;
+use crate::providers::limiter::TokenBucket;
+
+/// Default sustained requests per second allowed to each client
+pub const DEFAULT_RATE: f64 = 10.0;
+
+/// Slowest rate accepted for an API key (one request every ~17 minutes)
+pub const MIN_KEY_RATE: f64 = 0.001;
+
+/// Default requests a client can send in a burst
+pub const DEFAULT_BURST: u32 = 20;
+
+/// Request limits applied to each API key or, for requests without a key, each IP
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClientLimits {
+    /// Sustained requests per second (`RATE_LIMIT_RPS`, 0 = unlimited)
+    pub requests_per_second: f64,
+    /// Requests allowed in a burst (`RATE_LIMIT_BURST`)
+    pub burst: u32,
+    /// Requests per UTC day (`DAILY_QUOTA`, 0 = unlimited)
+    pub daily_quota: u64,
+}
+
+impl Default for ClientLimits {
+    fn default() -> Self {
+        Self {
+            requests_per_second: DEFAULT_RATE,
+            burst: DEFAULT_BURST,
+            daily_quota: 0,
+        }
+    }
+}
+
+/// Client a request is counted against
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Client {
+    /// API key name
+    Key(String),
+    Ip(IpAddr),
+}
+
+/// Limit, what is left of it and the time until it is fully available again
+#[derive(Debug, Clone, Copy)]
+pub struct Window {
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset: Duration,
+}
+
+/// Outcome of admitting a request, with the values for the rate limit headers
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    pub allowed: bool,
+    /// Burst window (absent when the rate is unlimited)
+    pub rate: Option<Window>,
+    /// Daily quota window (absent when there is no quota)
+    pub quota: Option<Window>,
+    /// How long a rejected client should wait
+    pub retry_after: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct ClientState {
+    limits: ClientLimits,
+    bucket: Option<TokenBucket>,
+    day: NaiveDate,
+    /// Requests admitted on `day`
+    used: u64,
+}
+
+fn bucket(limits: &ClientLimits) -> Option<TokenBucket> {
+    (limits.requests_per_second > 0.0)
+        .then(|| TokenBucket::new(limits.requests_per_second, limits.burst))
+}
+
+/// Per-client token buckets and daily counters.
+///
+/// Usage of API keys is also collected for the database; it is taken with
+/// `take_usage` and written in batches, so counting a request never waits
+/// for SQLite.
+#[derive(Debug, Default)]
+pub struct ClientLimiter {
+    clients: Mutex<HashMap<Client, ClientState>>,
+    /// API key usage not yet written, by (key, day): (requests, rejected)
+    pending: Mutex<HashMap<(String, NaiveDate), (u64, u64)>>,
+}
+
+impl ClientLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The client's daily count is known in memory for `day`
+    pub fn is_tracked(&self, client: &Client, day: NaiveDate) -> bool {
+        self.clients
+            .lock()
+            .unwrap()
+            .get(client)
+            .is_some_and(|state| state.day == day)
+    }
+
+    /// Admit or reject a request. `used_today` starts the daily count of a
+    /// client not yet tracked today (requests counted before a restart).
+    pub fn check(
+        &self,
+        client: &Client,
+        limits: ClientLimits,
+        used_today: u64,
+        now: DateTime<Utc>,
+    ) -> Verdict {
+        let today = now.date_naive();
+        let until_midnight = (today + ChronoDuration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .map(|midnight| midnight.and_utc() - now)
+            .and_then(|d| d.to_std().ok())
+            .unwrap_or_default();
+
+        let mut clients = self.clients.lock().unwrap();
+        let state = clients
+            .entry(client.clone())
+            .or_insert_with(|| ClientState {
+                limits,
+                bucket: bucket(&limits),
+                day: today,
+                used: used_today,
+            });
+        if state.limits != limits {
+            state.limits = limits;
+            state.bucket = bucket(&limits);
+        }
+        if state.day != today {
+            state.day = today;
+            state.used = used_today;
+        }
+
+        let retry_after = if limits.daily_quota > 0 && state.used >= limits.daily_quota {
+            Some(until_midnight)
+        } else {
+            state.bucket.as_ref().and_then(TokenBucket::try_acquire)
+        };
+        let allowed = retry_after.is_none();
+        if allowed {
+            state.used += 1;
+        }
+
+        let verdict = Verdict {
+            allowed,
+            rate: state.bucket.as_ref().map(|bucket| {
+                let (remaining, reset) = bucket.available();
+                Window {
+                    limit: limits.burst.max(1) as u64,
+                    remaining: remaining as u64,
+                    reset,
+                }
+            }),
+            quota: (limits.daily_quota > 0).then(|| Window {
+                limit: limits.daily_quota,
+                remaining: limits.daily_quota.saturating_sub(state.used),
+                reset: until_midnight,
+            }),
+            retry_after,
+        };
+        drop(clients);
+
+        if let Client::Key(name) = client {
+            let mut pending = self.pending.lock().unwrap();
+            let counts = pending.entry((name.clone(), today)).or_default();
+            if allowed {
+                counts.0 += 1;
+            } else {
+                counts.1 += 1;
+            }
+        }
+
+        verdict
+    }
+
+    /// How long until `check` would admit `client` again under its burst
+    /// limit, without counting a request
+    pub fn retry_after(&self, client: &Client) -> Option<Duration> {
+        self.clients
+            .lock()
+            .unwrap()
+            .get(client)?
+            .bucket
+            .as_ref()?
+            .wait()
+    }
+
+    /// Forget clients not seen since before `today`
+    pub fn prune(&self, today: NaiveDate) {
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|_, state| state.day >= today);
+    }
+
+    /// API key usage counted since the last call. Clients not seen since
+    /// before `today` are forgotten.
+    pub fn take_usage(&self, today: NaiveDate) -> Vec<ApiUsage> {
+        self.prune(today);
+
+        let mut usage: Vec<ApiUsage> = self
+            .pending
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|((key, date), (requests, rejected))| ApiUsage {
+                key,
+                date,
+                requests,
+                rejected,
+            })
+            .collect();
+        usage.sort_by(|a, b| (a.date, &a.key).cmp(&(b.date, &b.key)));
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_limits() {
+        let limiter = ClientLimiter::new();
+        let now = "2025-03-03T23:59:00Z".parse::<DateTime<Utc>>().unwrap();
+        let key = Client::Key("partner".to_string());
+        let limits = ClientLimits {
+            requests_per_second: 0.001,
+            burst: 3,
+            daily_quota: 0,
+        };
+
+        // Burst served, then rejected until a token is refilled
+        for remaining in [2, 1, 0] {
+            let verdict = limiter.check(&key, limits, 0, now);
+            assert!(verdict.allowed);
+            assert_eq!(verdict.rate.unwrap().remaining, remaining);
+        }
+        let verdict = limiter.check(&key, limits, 0, now);
+        assert!(!verdict.allowed);
+        assert!(verdict.retry_after.unwrap() > Duration::from_secs(900));
+        assert!(limiter.retry_after(&key).unwrap() > Duration::from_secs(900));
+
+        // Daily quota, with requests counted before a restart
+        let ip = Client::Ip("10.0.0.1".parse().unwrap());
+        let quota = ClientLimits {
+            requests_per_second: 0.0,
+            burst: 0,
+            daily_quota: 5,
+        };
+        assert!(!limiter.is_tracked(&ip, now.date_naive()));
+        assert_eq!(limiter.retry_after(&ip), None);
+        let verdict = limiter.check(&ip, quota, 4, now);
+        assert!(verdict.allowed && verdict.rate.is_none());
+        assert_eq!(verdict.quota.unwrap().remaining, 0);
+        let verdict = limiter.check(&ip, quota, 0, now);
+        assert_eq!(verdict.retry_after, Some(Duration::from_secs(60)));
+
+        // The quota starts over the next day
+        let tomorrow = now + ChronoDuration::minutes(2);
+        assert!(limiter.check(&ip, quota, 0, tomorrow).allowed);
+
+        // Only key usage is collected
+        let usage = limiter.take_usage(tomorrow.date_naive());
+        assert_eq!(usage.len(), 1);
+        assert_eq!((usage[0].requests, usage[0].rejected), (3, 1));
+        assert!(!limiter.is_tracked(&key, tomorrow.date_naive()));
+        assert!(limiter.take_usage(tomorrow.date_naive()).is_empty());
+    }
+}